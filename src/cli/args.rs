@@ -1,4 +1,9 @@
-use clap::Parser;
+use crate::cli::completions;
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{Args as _, CommandFactory, FromArgMatches, Parser, Subcommand};
+use clap_complete::ArgValueCandidates;
+use ipnetwork::IpNetwork;
 use std::path::PathBuf;
 
 /*-------------------------------------------------------------------------------------------------
@@ -6,8 +11,293 @@ use std::path::PathBuf;
 -------------------------------------------------------------------------------------------------*/
 
 #[derive(Parser, Debug)]
-#[command(author, version, about="Quickly query the AWS IP Ranges.", long_about = None)]
+#[command(
+    author,
+    version,
+    about = "Quickly query the AWS IP Ranges.",
+    long_about = None,
+    override_usage = "awsipranges [OPTIONS] [SEARCH_CIDRS]...\n       awsipranges [OPTIONS] <COMMAND>"
+)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Options used without a command (`awsipranges [OPTIONS] [SEARCH_CIDRS]...`)
+    #[command(flatten)]
+    pub legacy: LegacyArgs,
+
+    /// Options controlling how the AWS IP Ranges are retrieved
+    #[command(flatten)]
+    pub client: ClientArgs,
+
+    /// Log more detail to stderr; repeat for more (-v warnings, -vv info, -vvv debug, -vvvv
+    /// trace)
+    #[arg(long, short, action = clap::ArgAction::Count, global = true)]
+    pub verbose: u8,
+
+    /// Print nothing; report the result with the exit status (logging is off unless --verbose is
+    /// also used)
+    #[arg(long, short, global = true)]
+    pub quiet: bool,
+}
+
+impl Args {
+    /// Parse the CLI arguments. The global options (logging and client options) may be used
+    /// before or after a command; the legacy options cannot be combined with a command.
+    pub fn parse_args() -> Self {
+        let mut command = Args::command();
+        let matches = command.get_matches_mut();
+
+        if let Some((name, _)) = matches.subcommand() {
+            let legacy_args = LegacyArgs::augment_args(clap::Command::new("legacy"));
+            let legacy_arg = command.get_arguments().find(|arg| {
+                legacy_args
+                    .get_arguments()
+                    .any(|legacy_arg| legacy_arg.get_id() == arg.get_id())
+                    && matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine)
+            });
+            if let Some(legacy_arg) = legacy_arg.cloned() {
+                command
+                    .error(
+                        ErrorKind::ArgumentConflict,
+                        format!("'{legacy_arg}' cannot be used with the '{name}' command"),
+                    )
+                    .exit();
+            }
+        }
+
+        Args::from_arg_matches(&matches).unwrap_or_else(|error| error.exit())
+    }
+}
+
+/*--------------------------------------------------------------------------------------
+  Commands
+--------------------------------------------------------------------------------------*/
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Find the AWS IP Prefixes containing IP addresses or networks
+    Search(SearchCommand),
+
+    /// Select AWS IP Prefixes by region, network border group, service, IP version, prefix
+    /// length, or address range
+    Filter(FilterCommand),
+
+    /// List the regions, network border groups, or services in the AWS IP Ranges
+    List(ListCommand),
+
+    /// Show address-space statistics for the AWS IP Ranges
+    Stats(StatsCommand),
+
+    /// Compare AWS IP Ranges: two ip-ranges.json files (OLD NEW), one file against the current
+    /// AWS IP Ranges, or, with no files, the cache against a freshly downloaded copy
+    Diff(DiffCommand),
+
+    /// Export the AWS IP Ranges in AWS's native ip-ranges.json format, JSON, NDJSON, YAML, or CSV
+    Export(ExportCommand),
+
+    /// Manage the local ip-ranges.json cache
+    Cache(CacheCommand),
+
+    /// Print a shell completion script; completes region, network border group, and service
+    /// values from the cached AWS IP Ranges
+    Completions(CompletionsCommand),
+
+    /// Print the man page, or write man pages for all commands to a directory
+    Man(ManCommand),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct SearchCommand {
+    /// Find AWS IP Prefixes containing these IP addresses or networks; accepts CIDRs, `ADDRESS
+    /// NETMASK`, `START-END` ranges, `10.0.*.*` wildcards, and IPv4-mapped or NAT64 IPv6
+    /// addresses (`-` reads them from stdin, one per line)
+    #[arg(required_unless_present = "input_files")]
+    pub search_cidrs: Vec<String>,
+
+    /// Read search CIDRs from a file, one IP address or CIDR per line (blank lines and `#`
+    /// comments are skipped)
+    #[arg(long = "input-file", value_name = "PATH")]
+    pub input_files: Vec<PathBuf>,
+
+    #[command(flatten)]
+    pub search: SearchArgs,
+
+    #[command(flatten)]
+    pub filter: FilterArgs,
+
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct FilterCommand {
+    #[command(flatten)]
+    pub filter: FilterArgs,
+
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ListCommand {
+    /// Values to list
+    #[arg(value_enum)]
+    pub values: ListValues,
+
+    #[command(flatten)]
+    pub filter: FilterArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct StatsCommand {
+    /// Stats output format
+    #[arg(long, short, value_enum, default_value_t = StatsFormat::Table)]
+    pub format: StatsFormat,
+
+    #[command(flatten)]
+    pub filter: FilterArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct DiffCommand {
+    /// ip-ranges.json files to compare (OLD NEW, or OLD to compare against the current AWS IP
+    /// Ranges)
+    #[arg(value_name = "FILE", num_args(0..=2))]
+    pub files: Vec<PathBuf>,
+
+    /// Diff output format
+    #[arg(long, short, value_enum, default_value_t = DiffFormat::Table)]
+    pub format: DiffFormat,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ExportCommand {
+    /// Export format
+    #[arg(long, short, value_enum, default_value_t = ExportFormat::IpRangesJson)]
+    pub format: ExportFormat,
+
+    /// Write the export to this file instead of stdout
+    #[arg(value_name = "PATH")]
+    pub path: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub filter: FilterArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct CacheCommand {
+    #[command(subcommand)]
+    pub action: CacheAction,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct CompletionsCommand {
+    /// Shell to complete (for example, `source <(awsipranges completions bash)`)
+    #[arg(value_enum)]
+    pub shell: Shell,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ManCommand {
+    /// Write `awsipranges.1` and a man page for each command to this directory
+    #[arg(value_name = "DIRECTORY")]
+    pub directory: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CacheAction {
+    /// Print the path of the cache file
+    Path,
+
+    /// Show the cache file's path, age, size, freshness, syncToken, and createDate
+    Info,
+
+    /// Download the AWS IP Ranges and replace the cache file, regardless of its age
+    Refresh,
+
+    /// Delete the cache file
+    Clear,
+
+    /// Parse and validate the cache file, reporting any problems
+    Verify,
+}
+
+/*--------------------------------------------------------------------------------------
+  Client Arguments
+--------------------------------------------------------------------------------------*/
+
+#[derive(clap::Args, Debug)]
+pub struct ClientArgs {
+    /// How to retrieve the AWS IP Ranges; overrides AWSIPRANGES_FETCH_POLICY [default:
+    /// prefer-cache]
+    #[arg(long, global = true, value_enum, value_name = "POLICY")]
+    pub fetch_policy: Option<FetchPolicy>,
+
+    /// Use the cached AWS IP Ranges, regardless of their age, and never request them from AWS
+    /// (same as `--fetch-policy cache-only`)
+    #[arg(long, global = true, conflicts_with = "fetch_policy")]
+    pub offline: bool,
+
+    /// Fail when the AWS IP Ranges were published (createDate) more than this many seconds ago,
+    /// even when they come from a fresh cache; overrides AWSIPRANGES_MAX_DATA_AGE
+    #[arg(long, global = true, value_name = "SECONDS")]
+    pub max_data_age: Option<u64>,
+
+    /// Fail or warn when the AWS IP Ranges are older than the maximum data age; overrides
+    /// AWSIPRANGES_DATA_AGE_POLICY [default: error]
+    #[arg(long, global = true, value_enum, value_name = "POLICY")]
+    pub data_age_policy: Option<DataAgePolicy>,
+
+    /// Fail unless the AWS IP Ranges have this syncToken (for reproducible runs); overrides
+    /// AWSIPRANGES_EXPECTED_SYNC_TOKEN
+    #[arg(long, global = true, value_name = "SYNC_TOKEN")]
+    pub expected_sync_token: Option<String>,
+}
+
+/*--------------------------------------------------------------------------------------
+  Legacy Arguments
+--------------------------------------------------------------------------------------*/
+
+/// The flat (pre-command) CLI: positional CIDRs search, filter flags filter, and `--diff`
+/// compares AWS IP Ranges.
+#[derive(clap::Args, Debug)]
+pub struct LegacyArgs {
+    #[command(flatten)]
+    pub filter: FilterArgs,
+
+    #[command(flatten)]
+    pub search: SearchArgs,
+
+    #[command(flatten)]
+    pub output: OutputArgs,
+
+    /// Compare AWS IP Ranges: two ip-ranges.json files (OLD NEW), one file against the current
+    /// AWS IP Ranges, or, with no files, the cache against a freshly downloaded copy
+    #[arg(long, value_name = "FILE", num_args(0..=2))]
+    pub diff: Option<Vec<PathBuf>>,
+
+    /// Diff output format
+    #[arg(long, value_enum, default_value_t = DiffFormat::Table)]
+    pub diff_format: DiffFormat,
+
+    /// Search: Read search CIDRs from a file, one IP address or CIDR per line (blank lines and
+    /// `#` comments are skipped)
+    #[arg(long = "input-file", value_name = "PATH")]
+    pub input_files: Vec<PathBuf>,
+
+    /// Find AWS IP Prefixes containing these IP addresses or networks; accepts CIDRs, `ADDRESS
+    /// NETMASK`, `START-END` ranges, `10.0.*.*` wildcards, and IPv4-mapped or NAT64 IPv6
+    /// addresses (`-` reads them from stdin, one per line)
+    pub search_cidrs: Vec<String>,
+}
+
+/*--------------------------------------------------------------------------------------
+  Shared Arguments
+--------------------------------------------------------------------------------------*/
+
+#[derive(clap::Args, Debug)]
+pub struct FilterArgs {
     /// Include: IPv4 prefixes
     #[arg(short = '4', long)]
     pub ipv4: bool,
@@ -16,40 +306,148 @@ pub struct Args {
     #[arg(short = '6', long)]
     pub ipv6: bool,
 
-    /// Include: Region
-    #[arg(id = "REGION", short = 'r', long = "region", num_args(1..))]
+    /// Include: Region (case-insensitive; supports * and ? wildcards)
+    #[arg(
+        id = "REGION",
+        short = 'r',
+        long = "region",
+        num_args(1..),
+        add = ArgValueCandidates::new(completions::regions)
+    )]
     pub include_regions: Option<Vec<String>>,
 
-    /// Include: Network Border Group
+    /// Include: Network Border Group (case-insensitive; supports * and ? wildcards)
     #[arg(
         id = "NETWORK_BORDER_GROUP",
         short = 'g',
         long = "network-border-group",
-        num_args(1..)
+        num_args(1..),
+        add = ArgValueCandidates::new(completions::network_border_groups)
     )]
     pub include_network_border_groups: Option<Vec<String>>,
 
-    /// Include: Service
-    #[arg(id = "SERVICE", short = 's', long = "service", num_args(1..))]
+    /// Include: Service (case-insensitive; supports * and ? wildcards)
+    #[arg(
+        id = "SERVICE",
+        short = 's',
+        long = "service",
+        num_args(1..),
+        add = ArgValueCandidates::new(completions::services)
+    )]
     pub include_services: Option<Vec<String>>,
 
+    /// Exclude: Region
+    #[arg(
+        id = "EXCLUDE_REGION",
+        long = "exclude-region",
+        num_args(1..),
+        add = ArgValueCandidates::new(completions::regions)
+    )]
+    pub exclude_regions: Option<Vec<String>>,
+
+    /// Exclude: Network Border Group
+    #[arg(
+        id = "EXCLUDE_NETWORK_BORDER_GROUP",
+        long = "exclude-network-border-group",
+        num_args(1..),
+        add = ArgValueCandidates::new(completions::network_border_groups)
+    )]
+    pub exclude_network_border_groups: Option<Vec<String>>,
+
+    /// Exclude: Service (drops prefixes used by any excluded service)
+    #[arg(
+        id = "EXCLUDE_SERVICE",
+        long = "exclude-service",
+        num_args(1..),
+        add = ArgValueCandidates::new(completions::services)
+    )]
+    pub exclude_services: Option<Vec<String>>,
+
+    /// Exclude: Prefixes whose only service is AMAZON
+    #[arg(long)]
+    pub exclude_amazon_only: bool,
+
+    /// Include: IPv4 prefixes with at least this prefix length
+    #[arg(long, value_name = "LEN", value_parser = clap::value_parser!(u8).range(0..=32))]
+    pub min_ipv4_prefix_len: Option<u8>,
+
+    /// Include: IPv4 prefixes with at most this prefix length
+    #[arg(long, value_name = "LEN", value_parser = clap::value_parser!(u8).range(0..=32))]
+    pub max_ipv4_prefix_len: Option<u8>,
+
+    /// Include: IPv6 prefixes with at least this prefix length
+    #[arg(long, value_name = "LEN", value_parser = clap::value_parser!(u8).range(0..=128))]
+    pub min_ipv6_prefix_len: Option<u8>,
+
+    /// Include: IPv6 prefixes with at most this prefix length
+    #[arg(long, value_name = "LEN", value_parser = clap::value_parser!(u8).range(0..=128))]
+    pub max_ipv6_prefix_len: Option<u8>,
+
+    /// Include: Prefixes within these networks
+    #[arg(long, value_name = "CIDR", num_args(1..))]
+    pub within: Option<Vec<IpNetwork>>,
+
+    /// Exclude: Prefixes within these networks
+    #[arg(long, value_name = "CIDR", num_args(1..))]
+    pub not_within: Option<Vec<IpNetwork>>,
+}
+
+#[derive(clap::Args, Debug, Default)]
+pub struct SearchArgs {
+    /// Search: Find AWS IP Prefixes overlapping the search CIDRs (supernets, exact matches, and
+    /// subnets)
+    #[arg(long)]
+    pub overlapping: bool,
+
+    /// Search: Show only the most specific AWS IP Prefix containing each search CIDR, among the
+    /// prefixes matching the filters (broad AMAZON prefixes are overridden by prefixes used by
+    /// other services)
+    #[arg(long)]
+    pub most_specific: bool,
+
+    /// Search: How --most-specific treats the AMAZON service; `fallback` prefers prefixes used by
+    /// other services and only attributes AMAZON when no other service matches, `service` treats
+    /// AMAZON like any other service
+    #[arg(long, value_enum, default_value_t = AmazonService::Fallback, requires = "most_specific")]
+    pub amazon_service: AmazonService,
+
+    /// Search: Show the results grouped by search CIDR, listing the search CIDRs not found, or
+    /// merged into a single set of AWS IP Prefixes (applies to the table and CSV outputs)
+    #[arg(long, value_enum, default_value_t = SearchView::ByInput)]
+    pub search_view: SearchView,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct OutputArgs {
     /// Output format
     #[clap(long, short)]
     #[clap(value_enum, default_value_t=OutputFormat::Table)]
     pub output: OutputFormat,
 
+    /// Aggregate the prefixes into a minimal covering set (applies to the cidr, netmask, and CSV
+    /// outputs); optionally aggregate per region, network border group, or service
+    #[arg(
+        long,
+        value_enum,
+        num_args(0..=1),
+        require_equals(true),
+        default_missing_value("all")
+    )]
+    pub aggregate: Option<Aggregate>,
+
+    /// Stats output format (used with `--output stats`)
+    #[arg(long, value_enum, default_value_t = StatsFormat::Table)]
+    pub stats_format: StatsFormat,
+
     /// Save the results to a CSV file
     #[arg(long = "csv")]
     pub csv_file: Option<PathBuf>,
-
-    /// Logging verbosity
-    #[command(flatten)]
-    pub verbose: clap_verbosity_flag::Verbosity,
-
-    /// Find AWS IP Prefixes containing these IP addresses or networks
-    pub search_cidrs: Option<Vec<String>>,
 }
 
+/*--------------------------------------------------------------------------------------
+  Value Enums
+--------------------------------------------------------------------------------------*/
+
 #[derive(clap::ValueEnum, Clone, Debug)]
 pub enum OutputFormat {
     Table,
@@ -58,4 +456,100 @@ pub enum OutputFormat {
     Regions,
     NetworkBorderGroups,
     Services,
+    Stats,
+    Json,
+    Ndjson,
+    Yaml,
+    IpRangesJson,
+}
+
+impl std::fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = clap::ValueEnum::to_possible_value(self).expect("no skipped values");
+        write!(f, "{}", value.get_name())
+    }
+}
+
+#[derive(clap::ValueEnum, Clone, Debug, Default, PartialEq)]
+pub enum SearchView {
+    #[default]
+    ByInput,
+    Merged,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug, Default, PartialEq)]
+pub enum AmazonService {
+    /// AMAZON is a catch-all, attributed only when no other service matches
+    #[default]
+    Fallback,
+    /// AMAZON is a real service; the most specific prefix wins
+    Service,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+pub enum Aggregate {
+    All,
+    Region,
+    NetworkBorderGroup,
+    Service,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+pub enum DiffFormat {
+    Table,
+    Json,
+    Text,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+pub enum StatsFormat {
+    Table,
+    Json,
+    Csv,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+pub enum ListValues {
+    Regions,
+    #[value(name = "border-groups", alias = "network-border-groups")]
+    NetworkBorderGroups,
+    Services,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+pub enum FetchPolicy {
+    /// Use the cache when fresh; otherwise, download and fall back to a stale cache
+    PreferCache,
+    /// Use the cache, regardless of its age; never download
+    #[value(alias = "offline")]
+    CacheOnly,
+    /// Download; never read or write the cache
+    NetworkOnly,
+    /// Download and update the cache; never fall back to the cache
+    AlwaysRefresh,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+pub enum DataAgePolicy {
+    /// Exit with an error
+    Error,
+    /// Print a warning and continue
+    Warn,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Elvish,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+pub enum ExportFormat {
+    IpRangesJson,
+    Json,
+    Ndjson,
+    Yaml,
+    Csv,
 }
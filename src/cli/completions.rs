@@ -0,0 +1,105 @@
+use crate::cli;
+use awsipranges::{AwsIpRanges, Result};
+use clap::CommandFactory;
+use clap_complete::env::{Bash, Elvish, EnvCompleter, Fish, Zsh};
+use clap_complete::CompletionCandidate;
+use std::collections::BTreeSet;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+/*-------------------------------------------------------------------------------------------------
+  Shell Completions
+-------------------------------------------------------------------------------------------------*/
+
+/// Environment variable the completion scripts set when asking `awsipranges` for completions.
+pub const COMPLETE_VAR: &str = "COMPLETE";
+
+/// Answer a shell's completion request and exit, when `awsipranges` was invoked by a completion
+/// script; otherwise, return and run normally.
+pub fn complete() {
+    clap_complete::CompleteEnv::with_factory(cli::Args::command)
+        .var(COMPLETE_VAR)
+        .complete();
+}
+
+/*--------------------------------------------------------------------------------------
+  Completion Scripts
+--------------------------------------------------------------------------------------*/
+
+/// Print the completion script for a shell. The script asks `awsipranges` for completions, so
+/// completions stay current as the CLI and the cached AWS IP Ranges change.
+pub fn script(shell: &cli::Shell) -> Result<()> {
+    let completer: &dyn EnvCompleter = match shell {
+        cli::Shell::Bash => &Bash,
+        cli::Shell::Zsh => &Zsh,
+        cli::Shell::Fish => &Fish,
+        cli::Shell::Elvish => &Elvish,
+    };
+
+    let command = cli::Args::command();
+    let name = command.get_name();
+    completer
+        .write_registration(COMPLETE_VAR, name, name, name, &mut io::stdout())
+        .map_err(|error| awsipranges::Error::Stream {
+            stream: "stdout".to_string(),
+            source: error,
+        })
+}
+
+/*--------------------------------------------------------------------------------------
+  Man Pages
+--------------------------------------------------------------------------------------*/
+
+/// Print the `awsipranges` man page or, when a directory is provided, write a man page for
+/// `awsipranges` and each of its commands to the directory.
+pub fn man(directory: Option<&Path>) -> Result<()> {
+    let command = cli::Args::command();
+    match directory {
+        Some(directory) => {
+            clap_mangen::generate_to(command, directory).map_err(|error| awsipranges::Error::Io {
+                path: directory.to_path_buf(),
+                source: error,
+            })
+        }
+        None => clap_mangen::Man::new(command)
+            .render(&mut io::stdout())
+            .map_err(|error| awsipranges::Error::Stream {
+                stream: "stdout".to_string(),
+                source: error,
+            }),
+    }
+}
+
+/*--------------------------------------------------------------------------------------
+  Dynamic Value Candidates
+--------------------------------------------------------------------------------------*/
+
+/// Region candidates from the cached AWS IP Ranges.
+pub fn regions() -> Vec<CompletionCandidate> {
+    cached_candidates(AwsIpRanges::regions)
+}
+
+/// Network border group candidates from the cached AWS IP Ranges.
+pub fn network_border_groups() -> Vec<CompletionCandidate> {
+    cached_candidates(AwsIpRanges::network_border_groups)
+}
+
+/// Service candidates from the cached AWS IP Ranges.
+pub fn services() -> Vec<CompletionCandidate> {
+    cached_candidates(AwsIpRanges::services)
+}
+
+/// Get candidates from the cache file, regardless of its age; completions never request the AWS
+/// IP Ranges from the network, and offer no candidates when the cache is unavailable.
+fn cached_candidates(values: fn(&AwsIpRanges) -> &BTreeSet<Arc<str>>) -> Vec<CompletionCandidate> {
+    awsipranges::Client::new()
+        .get_cached_ranges()
+        .map(|aws_ip_ranges| {
+            values(&aws_ip_ranges)
+                .iter()
+                .map(|value| CompletionCandidate::new(&**value))
+                .collect()
+        })
+        .unwrap_or_default()
+}
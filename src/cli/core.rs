@@ -1,36 +1,155 @@
 use crate::cli;
-use awsipranges::{AwsIpRanges, Filter, Result};
-use cli::utils::to_lowercase;
+use awsipranges::{
+    AwsIpRanges, Client, ClientBuilder, DataAgePolicy, Error, FetchPolicy, Filter, Result,
+};
 use ipnetwork::IpNetwork;
-use log::error;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
 
 /*-------------------------------------------------------------------------------------------------
   Core functions
 -------------------------------------------------------------------------------------------------*/
 
+/*--------------------------------------------------------------------------------------
+  Build the client from the CLI arguments
+--------------------------------------------------------------------------------------*/
+
+/// Build a client configured from the environment, overriding the fetch policy, maximum data
+/// age, data age policy, and expected sync token when provided; returns an [Error::InvalidEnvVar]
+/// when an environment variable is set to an invalid value.
+pub fn build_client(client_args: &cli::ClientArgs) -> Result<Client> {
+    let fetch_policy = match (client_args.offline, &client_args.fetch_policy) {
+        (true, _) => Some(FetchPolicy::CacheOnly),
+        (false, Some(cli::FetchPolicy::PreferCache)) => Some(FetchPolicy::PreferCache),
+        (false, Some(cli::FetchPolicy::CacheOnly)) => Some(FetchPolicy::CacheOnly),
+        (false, Some(cli::FetchPolicy::NetworkOnly)) => Some(FetchPolicy::NetworkOnly),
+        (false, Some(cli::FetchPolicy::AlwaysRefresh)) => Some(FetchPolicy::AlwaysRefresh),
+        (false, None) => None,
+    };
+
+    let mut client_builder = ClientBuilder::try_new()?;
+    if let Some(fetch_policy) = fetch_policy {
+        client_builder.fetch_policy(fetch_policy);
+    }
+    if let Some(max_data_age) = client_args.max_data_age {
+        client_builder.max_data_age(max_data_age);
+    }
+    if let Some(data_age_policy) = &client_args.data_age_policy {
+        client_builder.data_age_policy(match data_age_policy {
+            cli::DataAgePolicy::Error => DataAgePolicy::Error,
+            cli::DataAgePolicy::Warn => DataAgePolicy::Warn,
+        });
+    }
+    if let Some(expected_sync_token) = &client_args.expected_sync_token {
+        client_builder.expected_sync_token(expected_sync_token);
+    }
+    Ok(client_builder.build())
+}
+
 /*--------------------------------------------------------------------------------------
   Parse IP Network prefixes from CLI arguments
 --------------------------------------------------------------------------------------*/
 
-pub fn parse_prefixes(args: &cli::Args) -> Option<Vec<IpNetwork>> {
-    args.search_cidrs.as_ref().map(|prefixes| {
-        prefixes
-            .iter()
-            .filter_map(|prefix| {
-                prefix.parse().ok().or_else(|| {
-                    error!("Invalid IP prefix: {:?}", prefix);
-                    None
-                })
-            })
-            .collect()
-    })
+/// Parse the search CIDRs and input files; returns `None` when neither was provided. Duplicate
+/// search CIDRs are removed, keeping the first occurrence.
+pub fn parse_prefixes(
+    search_cidrs: &[String],
+    input_files: &[PathBuf],
+) -> Result<Option<Vec<IpNetwork>>> {
+    if search_cidrs.is_empty() && input_files.is_empty() {
+        return Ok(None);
+    }
+
+    let mut prefixes: Vec<IpNetwork> = Vec::new();
+
+    // Positional arguments; `-` reads the search CIDRs from stdin
+    for value in search_cidrs {
+        if value == "-" {
+            let input = io::read_to_string(io::stdin()).map_err(|error| Error::Stream {
+                stream: "stdin".to_string(),
+                source: error,
+            })?;
+            prefixes.extend(parse_lines(&input, "<stdin>")?);
+        } else {
+            prefixes.extend(awsipranges::parse_input(value)?);
+        }
+    }
+
+    // Input files
+    for path in input_files {
+        let input = fs::read_to_string(path).map_err(|error| Error::Io {
+            path: path.clone(),
+            source: error,
+        })?;
+        prefixes.extend(parse_lines(&input, &path.display().to_string())?);
+    }
+
+    // Remove duplicate search CIDRs, preserving the order they were provided
+    let mut seen: BTreeSet<IpNetwork> = BTreeSet::new();
+    prefixes.retain(|prefix| seen.insert(*prefix));
+
+    Ok(Some(prefixes))
+}
+
+/// Parse one IP address, CIDR, range, or wildcard address per line (see
+/// [awsipranges::parse_input]), skipping blank lines and `#` comments.
+fn parse_lines(input: &str, origin: &str) -> Result<Vec<IpNetwork>> {
+    let mut prefixes: Vec<IpNetwork> = Vec::new();
+    for (index, line) in input.lines().enumerate() {
+        let value = line.split('#').next().unwrap_or_default().trim();
+        if value.is_empty() {
+            continue;
+        }
+
+        let networks = awsipranges::parse_input(value).map_err(|error| match error {
+            Error::InvalidPrefix { value, reason } => Error::InvalidInput {
+                origin: origin.to_string(),
+                line: index + 1,
+                value,
+                reason,
+            },
+            error => error,
+        })?;
+        prefixes.extend(networks);
+    }
+
+    Ok(prefixes)
 }
 
 /*--------------------------------------------------------------------------------------
   Build AWS IP Ranges filter from CLI arguments
 --------------------------------------------------------------------------------------*/
 
-pub fn build_filter(args: &cli::Args, aws_ip_ranges: &AwsIpRanges) -> Result<Filter> {
+/// Build a filter from the filter arguments; returns `None` when no filter arguments were
+/// provided.
+pub fn build_filter(args: &cli::FilterArgs, aws_ip_ranges: &AwsIpRanges) -> Result<Option<Filter>> {
+    let filters_enabled = [
+        args.ipv4,
+        args.ipv6,
+        args.include_regions.is_some(),
+        args.include_network_border_groups.is_some(),
+        args.include_services.is_some(),
+        args.exclude_regions.is_some(),
+        args.exclude_network_border_groups.is_some(),
+        args.exclude_services.is_some(),
+        args.exclude_amazon_only,
+        args.min_ipv4_prefix_len.is_some(),
+        args.max_ipv4_prefix_len.is_some(),
+        args.min_ipv6_prefix_len.is_some(),
+        args.max_ipv6_prefix_len.is_some(),
+        args.within.is_some(),
+        args.not_within.is_some(),
+    ]
+    .iter()
+    .any(|v| *v);
+
+    if !filters_enabled {
+        return Ok(None);
+    }
+
     let mut filter = aws_ip_ranges.filter_builder();
 
     // Prefix Type
@@ -44,30 +163,113 @@ pub fn build_filter(args: &cli::Args, aws_ip_ranges: &AwsIpRanges) -> Result<Fil
 
     // Regions
     if let Some(include_regions) = &args.include_regions {
-        let include_regions: Vec<String> = include_regions
-            .iter()
-            .map(|region| to_lowercase(region, ["GLOBAL"]))
-            .collect();
         filter = filter.regions(include_regions)?;
     };
 
     // Network Border Groups
     if let Some(include_network_border_groups) = &args.include_network_border_groups {
-        let include_network_border_groups: Vec<String> = include_network_border_groups
-            .iter()
-            .map(|group| to_lowercase(group, ["GLOBAL"]))
-            .collect();
         filter = filter.network_border_groups(include_network_border_groups)?;
     };
 
     // Services
     if let Some(include_services) = &args.include_services {
-        let include_services: Vec<String> = include_services
-            .iter()
-            .map(|service| service.to_uppercase())
-            .collect();
         filter = filter.services(include_services)?;
     };
 
-    Ok(filter.build())
+    // Exclusions
+    if let Some(exclude_regions) = &args.exclude_regions {
+        filter = filter.exclude_regions(exclude_regions)?;
+    };
+
+    if let Some(exclude_network_border_groups) = &args.exclude_network_border_groups {
+        filter = filter.exclude_network_border_groups(exclude_network_border_groups)?;
+    };
+
+    if let Some(exclude_services) = &args.exclude_services {
+        filter = filter.exclude_services(exclude_services)?;
+    };
+
+    if args.exclude_amazon_only {
+        filter = filter.exclude_amazon_only();
+    };
+
+    // Prefix Lengths
+    if let Some(len) = args.min_ipv4_prefix_len {
+        filter = filter.min_ipv4_prefix_len(len)?;
+    };
+
+    if let Some(len) = args.max_ipv4_prefix_len {
+        filter = filter.max_ipv4_prefix_len(len)?;
+    };
+
+    if let Some(len) = args.min_ipv6_prefix_len {
+        filter = filter.min_ipv6_prefix_len(len)?;
+    };
+
+    if let Some(len) = args.max_ipv6_prefix_len {
+        filter = filter.max_ipv6_prefix_len(len)?;
+    };
+
+    // Address Ranges
+    for network in args.within.iter().flatten() {
+        filter = filter.within(*network);
+    }
+
+    for network in args.not_within.iter().flatten() {
+        filter = filter.not_within(*network);
+    }
+
+    Ok(Some(filter.build()))
+}
+
+/// Apply the filter arguments to the AWS IP Ranges.
+pub fn filter_ranges(
+    args: &cli::FilterArgs,
+    aws_ip_ranges: Box<AwsIpRanges>,
+) -> Result<Box<AwsIpRanges>> {
+    Ok(match build_filter(args, &aws_ip_ranges)? {
+        Some(filter) => aws_ip_ranges.filter(&filter),
+        None => aws_ip_ranges,
+    })
+}
+
+/*--------------------------------------------------------------------------------------
+  Aggregate AWS IP Prefixes
+--------------------------------------------------------------------------------------*/
+
+pub fn aggregate_prefixes(
+    aggregate: &cli::Aggregate,
+    aws_ip_ranges: &AwsIpRanges,
+) -> BTreeMap<Option<Arc<str>>, Vec<IpNetwork>> {
+    let grouped = match aggregate {
+        cli::Aggregate::All => return BTreeMap::from([(None, aws_ip_ranges.aggregate())]),
+        cli::Aggregate::Region => aws_ip_ranges.aggregate_by_region(),
+        cli::Aggregate::NetworkBorderGroup => aws_ip_ranges.aggregate_by_network_border_group(),
+        cli::Aggregate::Service => aws_ip_ranges.aggregate_by_service(),
+    };
+
+    grouped
+        .into_iter()
+        .map(|(group, prefixes)| (Some(group), prefixes))
+        .collect()
+}
+
+/*--------------------------------------------------------------------------------------
+  Get the (old, new) AWS IP Ranges to compare
+--------------------------------------------------------------------------------------*/
+
+/// Get the (old, new) AWS IP Ranges to compare; the new AWS IP Ranges get the same sync token and
+/// data age checks as [Client::get_ranges].
+pub fn get_ranges_to_diff(
+    files: &[PathBuf],
+    client: &Client,
+) -> Result<(Box<AwsIpRanges>, Box<AwsIpRanges>)> {
+    let (old, new) = match files {
+        [old, new] => (AwsIpRanges::from_file(old)?, AwsIpRanges::from_file(new)?),
+        [old] => return Ok((AwsIpRanges::from_file(old)?, client.get_ranges()?)),
+        _ => (client.get_cached_ranges()?, client.download_ranges()?),
+    };
+    client.check_ranges(&new)?;
+
+    Ok((old, new))
 }
@@ -1,14 +1,35 @@
-use awsipranges::{AwsIpRanges, Result};
+use awsipranges::{Attribution, AwsIpRanges, Error, Result, SearchResults};
+use ipnetwork::IpNetwork;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
 use std::path::PathBuf;
-use std::rc::Rc;
+use std::sync::Arc;
 
 /*-------------------------------------------------------------------------------------------------
   Save AWS IP Ranges to CSV File
 -------------------------------------------------------------------------------------------------*/
 
 pub fn save(aws_ip_ranges: &AwsIpRanges, path: &PathBuf) -> Result<()> {
-    let mut writer = csv::Writer::from_path(path)?;
+    csv::Writer::from_path(path)
+        .and_then(|writer| write_prefixes(aws_ip_ranges, writer))
+        .map_err(|error| Error::Io {
+            path: path.clone(),
+            source: error.into(),
+        })
+}
+
+/// Write the AWS IP Prefixes in CSV format to a string.
+pub fn to_csv(aws_ip_ranges: &AwsIpRanges) -> String {
+    let mut buffer: Vec<u8> = Vec::new();
+    write_prefixes(aws_ip_ranges, csv::Writer::from_writer(&mut buffer))
+        .expect("Writing CSV records to memory succeeds");
+    String::from_utf8(buffer).expect("CSV records are valid UTF-8")
+}
 
+fn write_prefixes<W: io::Write>(
+    aws_ip_ranges: &AwsIpRanges,
+    mut writer: csv::Writer<W>,
+) -> csv::Result<()> {
     // Write header
     writer.serialize([
         "AWS IP Prefix",
@@ -27,7 +48,7 @@ pub fn save(aws_ip_ranges: &AwsIpRanges, path: &PathBuf) -> Result<()> {
                 .services
                 .iter()
                 .cloned()
-                .collect::<Vec<Rc<str>>>()
+                .collect::<Vec<Arc<str>>>()
                 .join(", "),
         );
         writer.serialize(record)?;
@@ -37,3 +58,128 @@ pub fn save(aws_ip_ranges: &AwsIpRanges, path: &PathBuf) -> Result<()> {
 
     Ok(())
 }
+
+/*-------------------------------------------------------------------------------------------------
+  Save Search Results to CSV File
+-------------------------------------------------------------------------------------------------*/
+
+/// Save the search results grouped by search CIDR; `attributions` adds an Effective Services
+/// column for the search CIDRs resolved with `--most-specific`.
+pub fn save_search_results(
+    search_cidrs: &[IpNetwork],
+    search_results: &SearchResults,
+    attributions: Option<&BTreeMap<IpNetwork, Attribution>>,
+    path: &PathBuf,
+) -> Result<()> {
+    write_search_results(search_cidrs, search_results, attributions, path).map_err(|error| {
+        Error::Io {
+            path: path.clone(),
+            source: error.into(),
+        }
+    })
+}
+
+fn write_search_results(
+    search_cidrs: &[IpNetwork],
+    search_results: &SearchResults,
+    attributions: Option<&BTreeMap<IpNetwork, Attribution>>,
+    path: &PathBuf,
+) -> csv::Result<()> {
+    let mut writer = csv::Writer::from_path(path)?;
+
+    // Write header
+    let mut header = vec![
+        "Search CIDR",
+        "AWS IP Prefix",
+        "Region",
+        "Network Border Group",
+        "Services",
+    ];
+    if attributions.is_some() {
+        header.push("Effective Services");
+    }
+    writer.write_record(header)?;
+
+    // Write search result records; search CIDRs not found have empty fields
+    for search_cidr in search_cidrs {
+        let effective_services = attributions.map(|attributions| {
+            attributions
+                .get(search_cidr)
+                .map(|attribution| join_services(&attribution.services))
+                .unwrap_or_default()
+        });
+
+        match search_results.prefix_matches.get(search_cidr) {
+            Some(aws_ip_prefixes) => {
+                for aws_ip_prefix in aws_ip_prefixes {
+                    let mut record = vec![
+                        search_cidr.to_string(),
+                        aws_ip_prefix.prefix.to_string(),
+                        aws_ip_prefix.region.to_string(),
+                        aws_ip_prefix.network_border_group.to_string(),
+                        join_services(&aws_ip_prefix.services),
+                    ];
+                    record.extend(effective_services.clone());
+                    writer.write_record(record)?;
+                }
+            }
+            None => {
+                let mut record = vec![search_cidr.to_string()];
+                record.resize(5, String::new());
+                record.extend(effective_services);
+                writer.write_record(record)?;
+            }
+        }
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+/*-------------------------------------------------------------------------------------------------
+  Save Aggregated AWS IP Prefixes to CSV File
+-------------------------------------------------------------------------------------------------*/
+
+pub fn save_aggregated(
+    aggregated_prefixes: &BTreeMap<Option<Arc<str>>, Vec<IpNetwork>>,
+    path: &PathBuf,
+) -> Result<()> {
+    write_aggregated_prefixes(aggregated_prefixes, path).map_err(|error| Error::Io {
+        path: path.clone(),
+        source: error.into(),
+    })
+}
+
+fn write_aggregated_prefixes(
+    aggregated_prefixes: &BTreeMap<Option<Arc<str>>, Vec<IpNetwork>>,
+    path: &PathBuf,
+) -> csv::Result<()> {
+    let mut writer = csv::Writer::from_path(path)?;
+
+    // Write header
+    writer.serialize(["Aggregated Prefix", "Group"])?;
+
+    // Write prefix records
+    for (group, prefixes) in aggregated_prefixes.iter() {
+        for prefix in prefixes.iter() {
+            writer.serialize((prefix, group.as_deref().unwrap_or("")))?;
+        }
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+/*-------------------------------------------------------------------------------------------------
+  Helper Functions
+-------------------------------------------------------------------------------------------------*/
+
+fn join_services(services: &BTreeSet<Arc<str>>) -> String {
+    services
+        .iter()
+        .map(|service| service.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
@@ -0,0 +1,252 @@
+use awsipranges::{AwsIpPrefix, PrefixChange, RangesDiff};
+use comfy_table::modifiers::UTF8_ROUND_CORNERS;
+use comfy_table::presets::{NOTHING, UTF8_FULL};
+use comfy_table::*;
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+/*-------------------------------------------------------------------------------------------------
+  Diff Output Functions
+-------------------------------------------------------------------------------------------------*/
+
+/*--------------------------------------------------------------------------------------
+  Diff Table
+--------------------------------------------------------------------------------------*/
+
+pub fn table(ranges_diff: &RangesDiff) {
+    if !ranges_diff.is_empty() {
+        let mut diff_table = Table::new();
+        diff_table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_content_arrangement(ContentArrangement::Dynamic);
+
+        diff_table.set_header(
+            [
+                "Change",
+                "IP Prefix",
+                "Region",
+                "Network Border Group",
+                "Services",
+            ]
+            .map(|header| {
+                Cell::new(header)
+                    .add_attribute(Attribute::Bold)
+                    .fg(Color::Green)
+            }),
+        );
+
+        for aws_ip_prefix in ranges_diff.added.values() {
+            diff_table.add_row(prefix_row(
+                Cell::new("Added").fg(Color::Green),
+                aws_ip_prefix,
+            ));
+        }
+
+        for aws_ip_prefix in ranges_diff.removed.values() {
+            diff_table.add_row(prefix_row(
+                Cell::new("Removed").fg(Color::Red),
+                aws_ip_prefix,
+            ));
+        }
+
+        for prefix_change in ranges_diff.changed.values() {
+            diff_table.add_row(vec![
+                Cell::new("Changed").fg(Color::Yellow),
+                Cell::new(prefix_change.new.prefix).add_attribute(Attribute::Bold),
+                Cell::new(change(&prefix_change.old.region, &prefix_change.new.region)),
+                Cell::new(change(
+                    &prefix_change.old.network_border_group,
+                    &prefix_change.new.network_border_group,
+                )),
+                Cell::new(change(
+                    &join(&prefix_change.old.services),
+                    &join(&prefix_change.new.services),
+                )),
+            ]);
+        }
+
+        // Right-align the IP Prefix column
+        let column = diff_table.column_mut(1).expect("The second column exists");
+        column.set_cell_alignment(CellAlignment::Right);
+
+        println!("{diff_table}");
+    }
+
+    // Diff Summary
+    let mut summary_table = Table::new();
+    summary_table.load_preset(NOTHING);
+
+    for (count, label) in [
+        (ranges_diff.added.len(), "Added"),
+        (ranges_diff.removed.len(), "Removed"),
+        (ranges_diff.changed.len(), "Changed"),
+    ] {
+        summary_table.add_row(vec![Cell::new(count), Cell::new(label)]);
+    }
+    summary_table.add_row(vec![
+        Cell::new("Sync Token").fg(Color::DarkGrey),
+        Cell::new(change(
+            &ranges_diff.old_sync_token,
+            &ranges_diff.new_sync_token,
+        ))
+        .fg(Color::DarkGrey),
+    ]);
+    summary_table.add_row(vec![
+        Cell::new("Data File Created").fg(Color::DarkGrey),
+        Cell::new(change(
+            &ranges_diff.old_create_date.to_string(),
+            &ranges_diff.new_create_date.to_string(),
+        ))
+        .fg(Color::DarkGrey),
+    ]);
+
+    let summary_numbers_column = summary_table
+        .column_mut(0)
+        .expect("The first column exists");
+    summary_numbers_column.set_cell_alignment(CellAlignment::Right);
+
+    println!("{summary_table}");
+}
+
+fn prefix_row(change: Cell, aws_ip_prefix: &AwsIpPrefix) -> Vec<Cell> {
+    vec![
+        change,
+        Cell::new(aws_ip_prefix.prefix).add_attribute(Attribute::Bold),
+        Cell::new(&aws_ip_prefix.region),
+        Cell::new(&aws_ip_prefix.network_border_group),
+        Cell::new(join(&aws_ip_prefix.services)),
+    ]
+}
+
+/*--------------------------------------------------------------------------------------
+  Diff JSON
+--------------------------------------------------------------------------------------*/
+
+pub fn json(ranges_diff: &RangesDiff) {
+    let value = json!({
+        "old": {
+            "syncToken": ranges_diff.old_sync_token,
+            "createDate": ranges_diff.old_create_date.to_rfc3339(),
+        },
+        "new": {
+            "syncToken": ranges_diff.new_sync_token,
+            "createDate": ranges_diff.new_create_date.to_rfc3339(),
+        },
+        "added": ranges_diff.added.values().map(prefix_json).collect::<Vec<Value>>(),
+        "removed": ranges_diff.removed.values().map(prefix_json).collect::<Vec<Value>>(),
+        "changed": ranges_diff.changed.values().map(change_json).collect::<Vec<Value>>(),
+    });
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&value).expect("JSON values serialize")
+    );
+}
+
+fn prefix_json(aws_ip_prefix: &AwsIpPrefix) -> Value {
+    json!({
+        "prefix": aws_ip_prefix.prefix.to_string(),
+        "region": &*aws_ip_prefix.region,
+        "network_border_group": &*aws_ip_prefix.network_border_group,
+        "services": aws_ip_prefix.services.iter().map(|service| &**service).collect::<Vec<&str>>(),
+    })
+}
+
+fn change_json(prefix_change: &PrefixChange) -> Value {
+    json!({
+        "prefix": prefix_change.new.prefix.to_string(),
+        "old": prefix_json(&prefix_change.old),
+        "new": prefix_json(&prefix_change.new),
+    })
+}
+
+/*--------------------------------------------------------------------------------------
+  Diff Text
+--------------------------------------------------------------------------------------*/
+
+pub fn text(ranges_diff: &RangesDiff) {
+    println!(
+        "Sync Token: {}",
+        change(&ranges_diff.old_sync_token, &ranges_diff.new_sync_token)
+    );
+    println!(
+        "Data File Created: {}",
+        change(
+            &ranges_diff.old_create_date.to_string(),
+            &ranges_diff.new_create_date.to_string()
+        )
+    );
+    println!(
+        "{} added, {} removed, {} changed",
+        ranges_diff.added.len(),
+        ranges_diff.removed.len(),
+        ranges_diff.changed.len()
+    );
+
+    for aws_ip_prefix in ranges_diff.added.values() {
+        println!("+ {}", prefix_text(aws_ip_prefix));
+    }
+
+    for aws_ip_prefix in ranges_diff.removed.values() {
+        println!("- {}", prefix_text(aws_ip_prefix));
+    }
+
+    for prefix_change in ranges_diff.changed.values() {
+        let mut changes: Vec<String> = Vec::new();
+        if prefix_change.region_changed() {
+            changes.push(format!(
+                "region {}",
+                change(&prefix_change.old.region, &prefix_change.new.region)
+            ));
+        }
+        if prefix_change.network_border_group_changed() {
+            changes.push(format!(
+                "network border group {}",
+                change(
+                    &prefix_change.old.network_border_group,
+                    &prefix_change.new.network_border_group
+                )
+            ));
+        }
+        for service in prefix_change.services_added() {
+            changes.push(format!("+service {service}"));
+        }
+        for service in prefix_change.services_removed() {
+            changes.push(format!("-service {service}"));
+        }
+
+        println!("~ {} {}", prefix_change.new.prefix, changes.join(", "));
+    }
+}
+
+fn prefix_text(aws_ip_prefix: &AwsIpPrefix) -> String {
+    format!(
+        "{} {} {} {}",
+        aws_ip_prefix.prefix,
+        aws_ip_prefix.region,
+        aws_ip_prefix.network_border_group,
+        join(&aws_ip_prefix.services)
+    )
+}
+
+/*--------------------------------------------------------------------------------------
+  Helper Functions
+--------------------------------------------------------------------------------------*/
+
+fn join(services: &BTreeSet<Arc<str>>) -> String {
+    services
+        .iter()
+        .map(|service| service.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn change(old: &str, new: &str) -> String {
+    if old == new {
+        old.to_string()
+    } else {
+        format!("{old} → {new}")
+    }
+}
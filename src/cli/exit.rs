@@ -0,0 +1,88 @@
+use awsipranges::Error;
+use std::error::Error as _;
+use std::fmt;
+use std::process::ExitCode;
+
+/*-------------------------------------------------------------------------------------------------
+  Exit Status
+-------------------------------------------------------------------------------------------------*/
+
+/// CLI exit statuses; documented in the README and stable for use in scripts.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExitStatus {
+    /// AWS IP Prefixes were output; when searching, every search CIDR was found.
+    Success = 0,
+
+    /// No AWS IP Prefixes match the provided criteria (no search CIDRs were found).
+    NotFound = 1,
+
+    /// Invalid arguments, search CIDRs, input files, or filter values (clap also exits with 2 on
+    /// usage errors).
+    InvalidInput = 2,
+
+    /// Some, but not all, of the search CIDRs were found.
+    PartiallyFound = 3,
+
+    /// The AWS IP Ranges could not be retrieved, read, or parsed, were older than the maximum data
+    /// age, or did not have the expected sync token; or the results could not be written.
+    DataUnavailable = 4,
+}
+
+impl From<ExitStatus> for ExitCode {
+    fn from(exit_status: ExitStatus) -> Self {
+        ExitCode::from(exit_status as u8)
+    }
+}
+
+/*-------------------------------------------------------------------------------------------------
+  Failure
+-------------------------------------------------------------------------------------------------*/
+
+/// An error returned by the CLI along with the exit status it maps to.
+#[derive(Debug)]
+pub struct Failure {
+    pub exit_status: ExitStatus,
+    pub error: Error,
+}
+
+impl Failure {
+    /// Report an error with the search input or arguments, regardless of the error kind (reading
+    /// an input file fails with an I/O error).
+    pub fn invalid_input(error: Error) -> Self {
+        Self {
+            exit_status: ExitStatus::InvalidInput,
+            error,
+        }
+    }
+}
+
+/// Displays the error message followed by the causes of the error's source; the error message
+/// already includes its immediate source.
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Error: {}", self.error)?;
+
+        let mut cause = self.error.source().and_then(|source| source.source());
+        while let Some(error) = cause {
+            write!(f, "\n  caused by: {error}")?;
+            cause = error.source();
+        }
+
+        Ok(())
+    }
+}
+
+impl From<Error> for Failure {
+    fn from(error: Error) -> Self {
+        let exit_status = match error {
+            Error::InvalidPrefix { .. }
+            | Error::InvalidInput { .. }
+            | Error::InvalidEnvVar { .. }
+            | Error::InvalidPrefixLenRange { .. }
+            | Error::UnknownFilterValue { .. } => ExitStatus::InvalidInput,
+            _ => ExitStatus::DataUnavailable,
+        };
+
+        Self { exit_status, error }
+    }
+}
@@ -1,6 +1,7 @@
-use awsipranges::SearchResults;
+use awsipranges::{Attribution, SearchResults};
 use ipnetwork::IpNetwork;
 use log::{info, warn};
+use std::collections::BTreeMap;
 
 /*-------------------------------------------------------------------------------------------------
   Logging Functions
@@ -25,9 +26,9 @@ pub fn search_results(
     info!("Searched for {count_search_cidrs} CIDR(s) in the AWS IP Ranges");
 
     let count_search_cidrs_found = search_results.prefix_matches.len();
-    let count_containing_prefixes = search_results.aws_ip_ranges.prefixes().len();
+    let count_matching_prefixes = search_results.aws_ip_ranges.prefixes().len();
     if count_search_cidrs_found > 0 {
-        info!("Found {count_search_cidrs_found} search CIDR(s) contained in {count_containing_prefixes} AWS IP Prefix(es)");
+        info!("Found {count_search_cidrs_found} search CIDR(s) matching {count_matching_prefixes} AWS IP Prefix(es)");
     };
 
     let count_search_cidrs_not_found = search_results.prefixes_not_found.len();
@@ -35,3 +36,29 @@ pub fn search_results(
         warn!("Did not find {count_search_cidrs_not_found} search CIDR(s)");
     };
 }
+
+/*--------------------------------------------------------------------------------------
+  Attributions
+--------------------------------------------------------------------------------------*/
+
+pub fn attributions(attributions: &BTreeMap<IpNetwork, Attribution>) {
+    for (search_prefix, attribution) in attributions {
+        let services = attribution
+            .services
+            .iter()
+            .map(|service| service.to_string())
+            .collect::<Vec<String>>()
+            .join(", ");
+        info!(
+            "{search_prefix} is attributed to {} ({services})",
+            attribution.prefix.prefix
+        );
+
+        for aws_ip_prefix in attribution.overridden.iter() {
+            info!(
+                "{search_prefix}: {} overrides {}",
+                attribution.prefix.prefix, aws_ip_prefix.prefix
+            );
+        }
+    }
+}
@@ -4,16 +4,27 @@
 
 mod args;
 mod core;
+mod exit;
 
+pub mod completions;
 pub mod csv;
+pub mod diff;
 pub mod log;
 pub mod output;
-pub mod utils;
+pub mod stats;
+pub mod structured;
 
 /*--------------------------------------------------------------------------------------
   CLI Module Interface
 --------------------------------------------------------------------------------------*/
 
-pub use args::Args;
-pub use args::OutputFormat;
-pub use core::{build_filter, parse_prefixes};
+pub use args::{
+    Aggregate, AmazonService, Args, CacheAction, CacheCommand, ClientArgs, Command, DataAgePolicy,
+    DiffFormat, ExportCommand, ExportFormat, FetchPolicy, FilterArgs, LegacyArgs, ListCommand,
+    ListValues, OutputArgs, OutputFormat, SearchArgs, SearchView, Shell, StatsCommand, StatsFormat,
+};
+pub use core::{
+    aggregate_prefixes, build_client, build_filter, filter_ranges, get_ranges_to_diff,
+    parse_prefixes,
+};
+pub use exit::{ExitStatus, Failure};
@@ -1,7 +1,10 @@
-use awsipranges::AwsIpRanges;
+use awsipranges::{Attribution, AwsIpRanges, SearchResults};
 use comfy_table::modifiers::UTF8_ROUND_CORNERS;
 use comfy_table::presets::{NOTHING, UTF8_FULL};
 use comfy_table::*;
+use ipnetwork::IpNetwork;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
 
 /*-------------------------------------------------------------------------------------------------
   Output Functions
@@ -57,7 +60,110 @@ pub fn prefix_table(aws_ip_ranges: &AwsIpRanges) {
 
     println!("{prefix_table}");
 
-    // Prefix Table Summary
+    summary_table(aws_ip_ranges);
+}
+
+/*--------------------------------------------------------------------------------------
+  Search Results Table
+--------------------------------------------------------------------------------------*/
+
+/// Display the search results grouped by search CIDR; `attributions` adds an Effective Services
+/// column for the search CIDRs resolved with `--most-specific`.
+pub fn search_results_table(
+    search_cidrs: &[IpNetwork],
+    search_results: &SearchResults,
+    attributions: Option<&BTreeMap<IpNetwork, Attribution>>,
+) {
+    // Search Results Table
+    let mut search_results_table = Table::new();
+    search_results_table
+        .load_preset(UTF8_FULL)
+        .apply_modifier(UTF8_ROUND_CORNERS)
+        .set_content_arrangement(ContentArrangement::Dynamic);
+
+    let mut header = vec![
+        "Search CIDR",
+        "IP Prefix",
+        "Region",
+        "Network Border Group",
+        "Services",
+    ];
+    if attributions.is_some() {
+        header.push("Effective Services");
+    }
+    search_results_table.set_header(header.into_iter().map(|header| {
+        Cell::new(header)
+            .add_attribute(Attribute::Bold)
+            .fg(Color::Green)
+    }));
+
+    for search_cidr in search_cidrs {
+        match search_results.prefix_matches.get(search_cidr) {
+            Some(aws_ip_prefixes) => {
+                for (index, prefix) in aws_ip_prefixes.iter().enumerate() {
+                    let mut sorted_services = prefix
+                        .services
+                        .iter()
+                        .map(|service| service.to_string())
+                        .collect::<Vec<String>>();
+                    sorted_services.sort();
+                    let services = sorted_services.join(", ");
+
+                    let mut row = vec![
+                        Cell::new(if index == 0 {
+                            search_cidr.to_string()
+                        } else {
+                            String::new()
+                        }),
+                        Cell::new(prefix.prefix).add_attribute(Attribute::Bold),
+                        Cell::new(&prefix.region),
+                        Cell::new(&prefix.network_border_group),
+                        Cell::new(services),
+                    ];
+                    if let Some(attributions) = attributions {
+                        let effective_services = attributions
+                            .get(search_cidr)
+                            .map(|attribution| {
+                                attribution
+                                    .services
+                                    .iter()
+                                    .map(|service| service.to_string())
+                                    .collect::<Vec<String>>()
+                                    .join(", ")
+                            })
+                            .unwrap_or_default();
+                        row.push(Cell::new(effective_services).add_attribute(Attribute::Bold));
+                    }
+                    search_results_table.add_row(row);
+                }
+            }
+            None => {
+                search_results_table.add_row(vec![
+                    Cell::new(search_cidr),
+                    Cell::new("Not found").fg(Color::Red),
+                ]);
+            }
+        };
+    }
+
+    // Right-align the Search CIDR and IP Prefix columns
+    for index in 0..=1 {
+        let column = search_results_table
+            .column_mut(index)
+            .expect("The column exists");
+        column.set_cell_alignment(CellAlignment::Right);
+    }
+
+    println!("{search_results_table}");
+
+    summary_table(&search_results.aws_ip_ranges);
+}
+
+/*--------------------------------------------------------------------------------------
+  Summary Table
+--------------------------------------------------------------------------------------*/
+
+fn summary_table(aws_ip_ranges: &AwsIpRanges) {
     let aws_ip_prefix_count = aws_ip_ranges.prefixes().len();
     let aws_region_count = aws_ip_ranges.regions().len();
     let sync_token = aws_ip_ranges.sync_token();
@@ -123,6 +229,36 @@ pub fn prefixes_in_netmask_format(aws_ip_ranges: &AwsIpRanges) {
     }
 }
 
+/*--------------------------------------------------------------------------------------
+  Aggregated Prefixes In CIDR Format
+--------------------------------------------------------------------------------------*/
+
+pub fn aggregated_prefixes_in_cidr_format(
+    aggregated_prefixes: &BTreeMap<Option<Arc<str>>, Vec<IpNetwork>>,
+) {
+    for prefix in unique_prefixes(aggregated_prefixes) {
+        println!("{prefix}");
+    }
+}
+
+/*--------------------------------------------------------------------------------------
+  Aggregated Prefixes In Netmask Format
+--------------------------------------------------------------------------------------*/
+
+pub fn aggregated_prefixes_in_netmask_format(
+    aggregated_prefixes: &BTreeMap<Option<Arc<str>>, Vec<IpNetwork>>,
+) {
+    for prefix in unique_prefixes(aggregated_prefixes) {
+        println!("{} {}", prefix.network(), prefix.mask());
+    }
+}
+
+fn unique_prefixes(
+    aggregated_prefixes: &BTreeMap<Option<Arc<str>>, Vec<IpNetwork>>,
+) -> BTreeSet<IpNetwork> {
+    aggregated_prefixes.values().flatten().copied().collect()
+}
+
 /*--------------------------------------------------------------------------------------
   Regions
 --------------------------------------------------------------------------------------*/
@@ -0,0 +1,176 @@
+use awsipranges::{FamilyStats, PrefixStats, RangesStats, Result};
+use comfy_table::modifiers::UTF8_ROUND_CORNERS;
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io;
+use std::sync::Arc;
+
+/*-------------------------------------------------------------------------------------------------
+  Stats Output Functions
+-------------------------------------------------------------------------------------------------*/
+
+/*--------------------------------------------------------------------------------------
+  Stats Table
+--------------------------------------------------------------------------------------*/
+
+pub fn table(ranges_stats: &RangesStats) {
+    let mut stats_table = Table::new();
+    stats_table
+        .load_preset(UTF8_FULL)
+        .apply_modifier(UTF8_ROUND_CORNERS)
+        .set_content_arrangement(ContentArrangement::Dynamic);
+
+    stats_table.set_header(
+        [
+            "Group",
+            "Name",
+            "Family",
+            "Prefixes",
+            "Total Addresses",
+            "Unique Addresses",
+            "Prefix Lengths",
+        ]
+        .map(|header| {
+            Cell::new(header)
+                .add_attribute(Attribute::Bold)
+                .fg(Color::Green)
+        }),
+    );
+
+    for row in rows(ranges_stats) {
+        stats_table.add_row(vec![
+            Cell::new(row.group),
+            Cell::new(row.name).add_attribute(Attribute::Bold),
+            Cell::new(row.family),
+            Cell::new(row.prefixes),
+            Cell::new(row.total_addresses),
+            Cell::new(row.unique_addresses),
+            Cell::new(prefix_lengths(row.prefix_lengths)),
+        ]);
+    }
+
+    // Right-align the count columns
+    for index in 3..=5 {
+        let column = stats_table.column_mut(index).expect("The column exists");
+        column.set_cell_alignment(CellAlignment::Right);
+    }
+
+    println!("{stats_table}");
+}
+
+/*--------------------------------------------------------------------------------------
+  Stats JSON
+--------------------------------------------------------------------------------------*/
+
+pub fn json(ranges_stats: &RangesStats) {
+    // Serialize the rows directly; serde_json::Value cannot hold u128 IPv6 address counts
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&rows(ranges_stats)).expect("Stats rows serialize to JSON")
+    );
+}
+
+/*--------------------------------------------------------------------------------------
+  Stats CSV
+--------------------------------------------------------------------------------------*/
+
+pub fn csv(ranges_stats: &RangesStats) -> Result<()> {
+    write_csv(ranges_stats).map_err(|error| awsipranges::Error::Stream {
+        stream: "stdout".to_string(),
+        source: error.into(),
+    })
+}
+
+fn write_csv(ranges_stats: &RangesStats) -> csv::Result<()> {
+    let mut writer = csv::Writer::from_writer(io::stdout());
+
+    // Write header
+    writer.serialize([
+        "Group",
+        "Name",
+        "Family",
+        "Prefixes",
+        "Total Addresses",
+        "Unique Addresses",
+        "Prefix Lengths",
+    ])?;
+
+    // Write stats records
+    for row in rows(ranges_stats) {
+        let record = (
+            row.group,
+            &row.name,
+            row.family,
+            row.prefixes,
+            row.total_addresses.to_string(),
+            row.unique_addresses.to_string(),
+            prefix_lengths(row.prefix_lengths),
+        );
+        writer.serialize(record)?;
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+/*--------------------------------------------------------------------------------------
+  Helper Functions
+--------------------------------------------------------------------------------------*/
+
+#[derive(Serialize)]
+struct StatsRow<'s> {
+    group: &'static str,
+    name: Arc<str>,
+    family: &'static str,
+    prefixes: usize,
+    total_addresses: u128,
+    unique_addresses: u128,
+    prefix_lengths: &'s BTreeMap<u8, usize>,
+}
+
+/// Flatten the stats into one row per group and address family; address families without
+/// prefixes are skipped.
+fn rows(ranges_stats: &RangesStats) -> Vec<StatsRow<'_>> {
+    let groups = [
+        ("Region", &ranges_stats.by_region),
+        (
+            "Network Border Group",
+            &ranges_stats.by_network_border_group,
+        ),
+        ("Service", &ranges_stats.by_service),
+    ];
+
+    let all = ("All", Arc::from("All"), &ranges_stats.all);
+    std::iter::once(all)
+        .chain(groups.into_iter().flat_map(|(group, stats)| {
+            stats
+                .iter()
+                .map(move |(name, family_stats)| (group, name.clone(), family_stats))
+        }))
+        .flat_map(|(group, name, family_stats): (_, _, &FamilyStats)| {
+            [("IPv4", &family_stats.ipv4), ("IPv6", &family_stats.ipv6)]
+                .into_iter()
+                .filter(|(_, prefix_stats)| prefix_stats.prefix_count > 0)
+                .map(move |(family, prefix_stats): (_, &PrefixStats)| StatsRow {
+                    group,
+                    name: name.clone(),
+                    family,
+                    prefixes: prefix_stats.prefix_count,
+                    total_addresses: prefix_stats.total_addresses,
+                    unique_addresses: prefix_stats.unique_addresses,
+                    prefix_lengths: &prefix_stats.prefix_lengths,
+                })
+        })
+        .collect()
+}
+
+fn prefix_lengths(prefix_lengths: &BTreeMap<u8, usize>) -> String {
+    prefix_lengths
+        .iter()
+        .map(|(prefix_length, count)| format!("/{prefix_length}: {count}"))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
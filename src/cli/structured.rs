@@ -0,0 +1,254 @@
+use awsipranges::{Attribution, AwsIpPrefix, AwsIpRanges, DataSource, SearchResults};
+use chrono::{DateTime, Utc};
+use ipnetwork::IpNetwork;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+
+/*-------------------------------------------------------------------------------------------------
+  Structured Output Functions
+-------------------------------------------------------------------------------------------------*/
+
+/// Version of the JSON, NDJSON, and YAML output schema (docs/cli_output_schema.md); incremented
+/// when a field is removed or changes meaning.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/*--------------------------------------------------------------------------------------
+  JSON
+--------------------------------------------------------------------------------------*/
+
+pub fn json(
+    aws_ip_ranges: &AwsIpRanges,
+    search: Option<(&[IpNetwork], &SearchResults)>,
+    attributions: Option<&BTreeMap<IpNetwork, Attribution>>,
+) {
+    println!("{}", to_json(aws_ip_ranges, search, attributions));
+}
+
+pub fn to_json(
+    aws_ip_ranges: &AwsIpRanges,
+    search: Option<(&[IpNetwork], &SearchResults)>,
+    attributions: Option<&BTreeMap<IpNetwork, Attribution>>,
+) -> String {
+    serde_json::to_string_pretty(&Document::new(aws_ip_ranges, search, attributions))
+        .expect("The output document serializes to JSON")
+}
+
+/*--------------------------------------------------------------------------------------
+  NDJSON
+--------------------------------------------------------------------------------------*/
+
+pub fn ndjson(
+    aws_ip_ranges: &AwsIpRanges,
+    search: Option<(&[IpNetwork], &SearchResults)>,
+    attributions: Option<&BTreeMap<IpNetwork, Attribution>>,
+) {
+    print!("{}", to_ndjson(aws_ip_ranges, search, attributions));
+}
+
+pub fn to_ndjson(
+    aws_ip_ranges: &AwsIpRanges,
+    search: Option<(&[IpNetwork], &SearchResults)>,
+    attributions: Option<&BTreeMap<IpNetwork, Attribution>>,
+) -> String {
+    let mut records: Vec<Record> = vec![Record::Metadata(Metadata::new(aws_ip_ranges))];
+
+    records.extend(
+        aws_ip_ranges
+            .prefixes()
+            .values()
+            .map(|aws_ip_prefix| Record::Prefix(Prefix::from(aws_ip_prefix))),
+    );
+
+    if let Some((search_cidrs, search_results)) = search {
+        records.extend(
+            search_result_records(search_cidrs, search_results, attributions)
+                .into_iter()
+                .map(Record::SearchResult),
+        );
+        records.extend(search_results.prefixes_not_found.iter().map(|search_cidr| {
+            Record::NotFound {
+                search_cidr: *search_cidr,
+            }
+        }));
+    }
+
+    records
+        .iter()
+        .map(|record| {
+            serde_json::to_string(record).expect("The output records serialize to JSON") + "\n"
+        })
+        .collect()
+}
+
+/*--------------------------------------------------------------------------------------
+  YAML
+--------------------------------------------------------------------------------------*/
+
+pub fn yaml(
+    aws_ip_ranges: &AwsIpRanges,
+    search: Option<(&[IpNetwork], &SearchResults)>,
+    attributions: Option<&BTreeMap<IpNetwork, Attribution>>,
+) {
+    print!("{}", to_yaml(aws_ip_ranges, search, attributions));
+}
+
+pub fn to_yaml(
+    aws_ip_ranges: &AwsIpRanges,
+    search: Option<(&[IpNetwork], &SearchResults)>,
+    attributions: Option<&BTreeMap<IpNetwork, Attribution>>,
+) -> String {
+    serde_norway::to_string(&Document::new(aws_ip_ranges, search, attributions))
+        .expect("The output document serializes to YAML")
+}
+
+/*--------------------------------------------------------------------------------------
+  Output Schema
+--------------------------------------------------------------------------------------*/
+
+#[derive(Serialize)]
+struct Document<'r> {
+    #[serde(flatten)]
+    metadata: Metadata<'r>,
+    prefixes: Vec<Prefix<'r>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    search: Option<Search<'r>>,
+}
+
+impl<'r> Document<'r> {
+    fn new(
+        aws_ip_ranges: &'r AwsIpRanges,
+        search: Option<(&[IpNetwork], &'r SearchResults)>,
+        attributions: Option<&'r BTreeMap<IpNetwork, Attribution>>,
+    ) -> Self {
+        Self {
+            metadata: Metadata::new(aws_ip_ranges),
+            prefixes: aws_ip_ranges
+                .prefixes()
+                .values()
+                .map(Prefix::from)
+                .collect(),
+            search: search.map(|(search_cidrs, search_results)| Search {
+                results: search_result_records(search_cidrs, search_results, attributions),
+                not_found: &search_results.prefixes_not_found,
+            }),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Metadata<'r> {
+    schema_version: u32,
+    sync_token: &'r str,
+    create_date: &'r DateTime<Utc>,
+    stale: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    provenance: Option<Provenance<'r>>,
+}
+
+impl<'r> Metadata<'r> {
+    fn new(aws_ip_ranges: &'r AwsIpRanges) -> Self {
+        let provenance = aws_ip_ranges.provenance();
+        Self {
+            schema_version: SCHEMA_VERSION,
+            sync_token: aws_ip_ranges.sync_token(),
+            create_date: aws_ip_ranges.create_date(),
+            stale: provenance.is_some_and(|provenance| provenance.is_stale()),
+            provenance: provenance.map(|provenance| Provenance {
+                source: match provenance.source {
+                    DataSource::Network => "network",
+                    DataSource::FreshCache => "fresh_cache",
+                    DataSource::StaleCache => "stale_cache",
+                    DataSource::File => "file",
+                    _ => "unknown",
+                },
+                location: &provenance.location,
+                fetched_at: &provenance.fetched_at,
+                cache_age_seconds: provenance.cache_age.map(|cache_age| cache_age.as_secs()),
+                http_status: provenance.http_status,
+                attempts: provenance.attempts,
+            }),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Provenance<'r> {
+    source: &'static str,
+    location: &'r str,
+    fetched_at: &'r DateTime<Utc>,
+    cache_age_seconds: Option<u64>,
+    http_status: Option<u16>,
+    attempts: u32,
+}
+
+#[derive(Serialize)]
+struct Prefix<'r> {
+    prefix: IpNetwork,
+    region: &'r Arc<str>,
+    network_border_group: &'r Arc<str>,
+    services: &'r BTreeSet<Arc<str>>,
+}
+
+impl<'r> From<&'r AwsIpPrefix> for Prefix<'r> {
+    fn from(aws_ip_prefix: &'r AwsIpPrefix) -> Self {
+        Self {
+            prefix: aws_ip_prefix.prefix,
+            region: &aws_ip_prefix.region,
+            network_border_group: &aws_ip_prefix.network_border_group,
+            services: &aws_ip_prefix.services,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Search<'r> {
+    results: Vec<SearchResult<'r>>,
+    not_found: &'r BTreeSet<IpNetwork>,
+}
+
+#[derive(Serialize)]
+struct SearchResult<'r> {
+    search_cidr: IpNetwork,
+    matches: Vec<Prefix<'r>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    effective_services: Option<&'r BTreeSet<Arc<str>>>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "record", rename_all = "snake_case")]
+enum Record<'r> {
+    Metadata(Metadata<'r>),
+    Prefix(Prefix<'r>),
+    SearchResult(SearchResult<'r>),
+    NotFound { search_cidr: IpNetwork },
+}
+
+/*--------------------------------------------------------------------------------------
+  Helper Functions
+--------------------------------------------------------------------------------------*/
+
+/// Get the matches for each search CIDR with matches, in the order the search CIDRs were
+/// provided, along with the effective services of the search CIDRs resolved with
+/// `--most-specific`.
+fn search_result_records<'r>(
+    search_cidrs: &[IpNetwork],
+    search_results: &'r SearchResults,
+    attributions: Option<&'r BTreeMap<IpNetwork, Attribution>>,
+) -> Vec<SearchResult<'r>> {
+    search_cidrs
+        .iter()
+        .filter_map(|search_cidr| {
+            search_results
+                .prefix_matches
+                .get(search_cidr)
+                .map(|aws_ip_prefixes| SearchResult {
+                    search_cidr: *search_cidr,
+                    matches: aws_ip_prefixes.iter().map(Prefix::from).collect(),
+                    effective_services: attributions
+                        .and_then(|attributions| attributions.get(search_cidr))
+                        .map(|attribution| &attribution.services),
+                })
+        })
+        .collect()
+}
@@ -0,0 +1,217 @@
+use crate::core::aws_ip_prefix::AwsIpPrefix;
+use crate::core::filter::AMAZON_SERVICE;
+use crate::core::utils::ipnetwork::is_supernet_of;
+use ipnetwork::IpNetwork;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+/*-------------------------------------------------------------------------------------------------
+  Attribution
+-------------------------------------------------------------------------------------------------*/
+
+/// Resolution of a searched IP address or prefix to the most specific AWS IP Prefix containing it
+/// and the effective set of services using it.
+///
+/// Created with [AwsIpRanges::attribute] and [SearchResults::attributions].
+///
+/// [AwsIpRanges::attribute]: crate::AwsIpRanges::attribute
+/// [SearchResults::attributions]: crate::SearchResults::attributions
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Attribution {
+    /// The searched IP address or prefix.
+    pub search_prefix: IpNetwork,
+
+    /// The most specific AWS IP Prefix containing the search prefix.
+    pub prefix: AwsIpPrefix,
+
+    /// The effective services using the search prefix.
+    pub services: BTreeSet<Arc<str>>,
+
+    /// The other AWS IP Prefixes containing the search prefix, overridden by `prefix`; most
+    /// specific first.
+    pub overridden: Vec<AwsIpPrefix>,
+}
+
+/*--------------------------------------------------------------------------------------
+  Attribution Implementation
+--------------------------------------------------------------------------------------*/
+
+impl Attribution {
+    /// Resolve a search prefix from a set of candidate AWS IP Prefixes; candidates that do not
+    /// contain the search prefix are ignored. Returns `None` when no candidate contains the
+    /// search prefix.
+    pub(crate) fn resolve<'p, I>(
+        search_prefix: &IpNetwork,
+        aws_ip_prefixes: I,
+        policy: AmazonServicePolicy,
+    ) -> Option<Attribution>
+    where
+        I: IntoIterator<Item = &'p AwsIpPrefix>,
+    {
+        let mut containing: Vec<&AwsIpPrefix> = aws_ip_prefixes
+            .into_iter()
+            .filter(|aws_ip_prefix| is_supernet_of(aws_ip_prefix.prefix, *search_prefix))
+            .collect();
+        if containing.is_empty() {
+            return None;
+        }
+        containing.sort_by_key(|aws_ip_prefix| Reverse(aws_ip_prefix.prefix.prefix()));
+
+        let index = match policy {
+            AmazonServicePolicy::Service => 0,
+            AmazonServicePolicy::Fallback => containing
+                .iter()
+                .position(|aws_ip_prefix| has_specific_service(aws_ip_prefix))
+                .unwrap_or(0),
+        };
+        let prefix = containing.remove(index).clone();
+
+        let mut services = prefix.services.clone();
+        if policy == AmazonServicePolicy::Fallback && has_specific_service(&prefix) {
+            services.remove(AMAZON_SERVICE);
+        }
+
+        Some(Attribution {
+            search_prefix: *search_prefix,
+            prefix,
+            services,
+            overridden: containing.into_iter().cloned().collect(),
+        })
+    }
+}
+
+/// Returns `true` when the AWS IP Prefix is used by a service other than AMAZON.
+fn has_specific_service(aws_ip_prefix: &AwsIpPrefix) -> bool {
+    aws_ip_prefix
+        .services
+        .iter()
+        .any(|service| &**service != AMAZON_SERVICE)
+}
+
+/*-------------------------------------------------------------------------------------------------
+  Amazon Service Policy
+-------------------------------------------------------------------------------------------------*/
+
+/// Selects how the `AMAZON` service is treated when attributing a search prefix.
+///
+/// AWS publishes broad prefixes used by the `AMAZON` service that contain the more specific
+/// prefixes used by individual services (like `EC2`); `AMAZON` prefixes also include addresses
+/// not used by any other listed service.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AmazonServicePolicy {
+    /// `AMAZON` is a catch-all: the most specific prefix used by another service wins over more
+    /// specific `AMAZON`-only prefixes, and `AMAZON` is dropped from the effective services when
+    /// another service uses the prefix. `AMAZON` is only attributed when no other service
+    /// matches.
+    #[default]
+    Fallback,
+
+    /// `AMAZON` is a real service: the most specific prefix wins and its services are the
+    /// effective services.
+    Service,
+}
+
+/*-------------------------------------------------------------------------------------------------
+  Unit Tests
+-------------------------------------------------------------------------------------------------*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::aws_ip_prefix::tests::test_aws_ipv4_prefix;
+
+    fn test_aws_ip_prefixes() -> Vec<AwsIpPrefix> {
+        vec![
+            AwsIpPrefix {
+                prefix: "10.0.0.0/8".parse().unwrap(),
+                services: [Arc::from("AMAZON")].into_iter().collect(),
+                ..test_aws_ipv4_prefix()
+            },
+            AwsIpPrefix {
+                prefix: "10.0.0.0/16".parse().unwrap(),
+                services: [Arc::from("AMAZON"), Arc::from("EC2")]
+                    .into_iter()
+                    .collect(),
+                ..test_aws_ipv4_prefix()
+            },
+            AwsIpPrefix {
+                prefix: "10.0.0.0/24".parse().unwrap(),
+                services: [Arc::from("AMAZON")].into_iter().collect(),
+                ..test_aws_ipv4_prefix()
+            },
+            AwsIpPrefix {
+                prefix: "10.1.0.0/16".parse().unwrap(),
+                ..test_aws_ipv4_prefix()
+            },
+        ]
+    }
+
+    #[test]
+    fn test_resolve_fallback() {
+        let aws_ip_prefixes = test_aws_ip_prefixes();
+
+        let attribution = Attribution::resolve(
+            &"10.0.0.1".parse().unwrap(),
+            &aws_ip_prefixes,
+            AmazonServicePolicy::Fallback,
+        )
+        .unwrap();
+        assert_eq!(attribution.prefix, aws_ip_prefixes[1]);
+        assert_eq!(attribution.services, [Arc::from("EC2")].into());
+        assert_eq!(
+            attribution.overridden,
+            vec![aws_ip_prefixes[2].clone(), aws_ip_prefixes[0].clone()]
+        );
+
+        // Only AMAZON prefixes contain the search prefix
+        let attribution = Attribution::resolve(
+            &"10.2.0.1".parse().unwrap(),
+            &aws_ip_prefixes,
+            AmazonServicePolicy::Fallback,
+        )
+        .unwrap();
+        assert_eq!(attribution.prefix, aws_ip_prefixes[0]);
+        assert_eq!(attribution.services, [Arc::from("AMAZON")].into());
+        assert!(attribution.overridden.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_service() {
+        let aws_ip_prefixes = test_aws_ip_prefixes();
+
+        let attribution = Attribution::resolve(
+            &"10.0.0.1".parse().unwrap(),
+            &aws_ip_prefixes,
+            AmazonServicePolicy::Service,
+        )
+        .unwrap();
+        assert_eq!(attribution.prefix, aws_ip_prefixes[2]);
+        assert_eq!(attribution.services, [Arc::from("AMAZON")].into());
+        assert_eq!(
+            attribution.overridden,
+            vec![aws_ip_prefixes[1].clone(), aws_ip_prefixes[0].clone()]
+        );
+    }
+
+    #[test]
+    fn test_resolve_not_found() {
+        let aws_ip_prefixes = test_aws_ip_prefixes();
+
+        // Subnets of the search prefix do not contain it
+        assert!(Attribution::resolve(
+            &"10.0.0.0/7".parse().unwrap(),
+            &aws_ip_prefixes,
+            AmazonServicePolicy::Fallback
+        )
+        .is_none());
+        assert!(Attribution::resolve(
+            &"192.168.0.1".parse().unwrap(),
+            &aws_ip_prefixes,
+            AmazonServicePolicy::Service
+        )
+        .is_none());
+    }
+}
@@ -1,6 +1,7 @@
 use ipnetwork::IpNetwork;
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeSet;
-use std::rc::Rc;
+use std::sync::Arc;
 
 /*-------------------------------------------------------------------------------------------------
   AWS IP Prefix
@@ -8,19 +9,19 @@ use std::rc::Rc;
 
 /// AWS IP Prefix record containing the IP prefix, region, network border group, and services
 /// associated with the prefix.
-#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
 pub struct AwsIpPrefix {
     /// IPv4 or IPv6 prefix.
     pub prefix: IpNetwork,
 
     /// AWS region the IP prefix is associated with.
-    pub region: Rc<str>,
+    pub region: Arc<str>,
 
     /// Network border group the IP prefix is associated with.
-    pub network_border_group: Rc<str>,
+    pub network_border_group: Arc<str>,
 
     /// AWS services that use the IP prefix.
-    pub services: BTreeSet<Rc<str>>,
+    pub services: BTreeSet<Arc<str>>,
 }
 
 /*-------------------------------------------------------------------------------------------------
@@ -38,18 +39,18 @@ pub(crate) mod tests {
     pub(crate) fn test_aws_ipv4_prefix() -> AwsIpPrefix {
         AwsIpPrefix {
             prefix: "10.0.0.0/8".parse().unwrap(),
-            region: Rc::from("us-east-1"),
-            network_border_group: Rc::from("us-east-1"),
-            services: [Rc::from("EC2")].into_iter().collect(),
+            region: Arc::from("us-east-1"),
+            network_border_group: Arc::from("us-east-1"),
+            services: [Arc::from("EC2")].into_iter().collect(),
         }
     }
 
     pub(crate) fn test_aws_ipv6_prefix() -> AwsIpPrefix {
         AwsIpPrefix {
             prefix: "2001:db8::/32".parse().unwrap(),
-            region: Rc::from("us-east-1"),
-            network_border_group: Rc::from("us-east-1"),
-            services: [Rc::from("EC2")].into_iter().collect(),
+            region: Arc::from("us-east-1"),
+            network_border_group: Arc::from("us-east-1"),
+            services: [Arc::from("EC2")].into_iter().collect(),
         }
     }
 
@@ -57,6 +58,12 @@ pub(crate) mod tests {
       AwsIpPrefix
     ----------------------------------------------------------------------------------*/
 
+    #[test]
+    fn test_aws_ip_prefix_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<AwsIpPrefix>();
+    }
+
     #[test]
     fn test_aws_ip_prefix_ordering() {
         let prefix1 = test_aws_ipv4_prefix();
@@ -72,22 +79,24 @@ pub(crate) mod tests {
         };
 
         let prefix4 = AwsIpPrefix {
-            region: Rc::from("us-east-2"),
+            region: Arc::from("us-east-2"),
             ..test_aws_ipv4_prefix()
         };
 
         let prefix5 = AwsIpPrefix {
-            network_border_group: Rc::from("us-east-2"),
+            network_border_group: Arc::from("us-east-2"),
             ..test_aws_ipv4_prefix()
         };
 
         let prefix6 = AwsIpPrefix {
-            services: [Rc::from("EC2"), Rc::from("ROUTE53")].into_iter().collect(),
+            services: [Arc::from("EC2"), Arc::from("ROUTE53")]
+                .into_iter()
+                .collect(),
             ..test_aws_ipv4_prefix()
         };
 
         let prefix7 = AwsIpPrefix {
-            services: [Rc::from("EC2"), Rc::from("ROUTE53_HEALTHCHECKS")]
+            services: [Arc::from("EC2"), Arc::from("ROUTE53_HEALTHCHECKS")]
                 .into_iter()
                 .collect(),
             ..test_aws_ipv4_prefix()
@@ -106,15 +115,15 @@ pub(crate) mod tests {
         let prefix1 = test_aws_ipv4_prefix();
         let prefix2 = test_aws_ipv4_prefix();
         let prefix3 = AwsIpPrefix {
-            region: Rc::from("us-west-1"),
+            region: Arc::from("us-west-1"),
             ..test_aws_ipv4_prefix()
         };
         let prefix4 = AwsIpPrefix {
-            network_border_group: Rc::from("us-west-1"),
+            network_border_group: Arc::from("us-west-1"),
             ..test_aws_ipv4_prefix()
         };
         let prefix5 = AwsIpPrefix {
-            services: [Rc::from("EC2"), Rc::from("S3")].into_iter().collect(),
+            services: [Arc::from("EC2"), Arc::from("S3")].into_iter().collect(),
             ..test_aws_ipv4_prefix()
         };
 
@@ -123,4 +132,28 @@ pub(crate) mod tests {
         assert_ne!(prefix1, prefix4); // Different network border groups
         assert_ne!(prefix1, prefix5); // Different services
     }
+
+    #[test]
+    fn test_aws_ip_prefix_serde() {
+        let aws_ip_prefix = AwsIpPrefix {
+            services: [Arc::from("EC2"), Arc::from("AMAZON")]
+                .into_iter()
+                .collect(),
+            ..test_aws_ipv4_prefix()
+        };
+
+        let value = serde_json::to_value(&aws_ip_prefix).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "prefix": "10.0.0.0/8",
+                "region": "us-east-1",
+                "network_border_group": "us-east-1",
+                "services": ["AMAZON", "EC2"],
+            })
+        );
+
+        let deserialized: AwsIpPrefix = serde_json::from_value(value).unwrap();
+        assert_eq!(deserialized, aws_ip_prefix);
+    }
 }
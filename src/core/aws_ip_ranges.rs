@@ -1,17 +1,26 @@
+use crate::core::attribution::{AmazonServicePolicy, Attribution};
 use crate::core::aws_ip_prefix::AwsIpPrefix;
-use crate::core::errors::Result;
+use crate::core::errors::{Error, Result};
 use crate::core::filter::Filter;
 use crate::core::filter::FilterBuilder;
 use crate::core::json;
-use crate::core::search_results::SearchResults;
+use crate::core::prefix_trie::PrefixTrie;
+use crate::core::provenance::{DataSource, Provenance};
+use crate::core::ranges_diff::{PrefixChange, RangesDiff};
+use crate::core::ranges_stats::RangesStats;
+use crate::core::search_results::{SearchMode, SearchResults};
 use crate::core::utils;
 use chrono::{DateTime, Utc};
 use ipnetwork::IpNetwork;
 use log::warn;
+use std::cmp::{Ordering, Reverse};
+use std::collections::btree_map::Entry;
 use std::collections::{BTreeMap, BTreeSet};
 use std::convert::From;
-use std::ops::Bound::Included;
-use std::rc::Rc;
+use std::fs;
+use std::ops::{BitAnd, BitOr, Sub};
+use std::path::Path;
+use std::sync::Arc;
 
 /*-------------------------------------------------------------------------------------------------
   AWS IP Ranges
@@ -19,16 +28,24 @@ use std::rc::Rc;
 
 /// Collection of AWS IP ranges providing methods to access, [AwsIpRanges::search], and
 /// [AwsIpRanges::filter] the AWS IP Ranges.
+///
+/// [AwsIpRanges] is `Send + Sync`; load the AWS IP Ranges once and share them across threads
+/// (for example, wrapped in an [Arc]).
 #[derive(Clone, Debug, Default)]
 pub struct AwsIpRanges {
     pub(crate) sync_token: String,
     pub(crate) create_date: DateTime<Utc>,
 
-    pub(crate) regions: BTreeSet<Rc<str>>,
-    pub(crate) network_border_groups: BTreeSet<Rc<str>>,
-    pub(crate) services: BTreeSet<Rc<str>>,
+    pub(crate) regions: BTreeSet<Arc<str>>,
+    pub(crate) network_border_groups: BTreeSet<Arc<str>>,
+    pub(crate) services: BTreeSet<Arc<str>>,
 
     pub(crate) prefixes: BTreeMap<IpNetwork, AwsIpPrefix>,
+
+    /// Index of the `prefixes` map keys; rebuilt whenever `prefixes` is set.
+    pub(crate) prefix_index: PrefixTrie,
+
+    pub(crate) provenance: Option<Provenance>,
 }
 
 /*--------------------------------------------------------------------------------------
@@ -50,18 +67,25 @@ impl AwsIpRanges {
         &self.create_date
     }
 
+    /// Where and when these AWS IP Ranges were loaded; `None` when they were built from prefixes
+    /// or deserialized. Search results, filtered AWS IP Ranges, and set operations keep the
+    /// provenance of these AWS IP Ranges.
+    pub fn provenance(&self) -> Option<&Provenance> {
+        self.provenance.as_ref()
+    }
+
     /// AWS regions represented in the current set of AWS IP Ranges.
-    pub fn regions(&self) -> &BTreeSet<Rc<str>> {
+    pub fn regions(&self) -> &BTreeSet<Arc<str>> {
         &self.regions
     }
 
     /// Network border groups represented in the current set of AWS IP Ranges.
-    pub fn network_border_groups(&self) -> &BTreeSet<Rc<str>> {
+    pub fn network_border_groups(&self) -> &BTreeSet<Arc<str>> {
         &self.network_border_groups
     }
 
     /// AWS services represented in the current set of AWS IP Ranges.
-    pub fn services(&self) -> &BTreeSet<Rc<str>> {
+    pub fn services(&self) -> &BTreeSet<Arc<str>> {
         &self.services
     }
 
@@ -85,17 +109,9 @@ impl AwsIpRanges {
 
     /// Get the longest matching [AwsIpPrefix] record for the provided [IpNetwork] CIDR.
     pub fn get_longest_match_prefix(&self, value: &IpNetwork) -> Option<&AwsIpPrefix> {
-        let lower_bound = match value {
-            IpNetwork::V4(_) => utils::ipnetwork::new_network_prefix(value, 8u8).unwrap(),
-            IpNetwork::V6(_) => utils::ipnetwork::new_network_prefix(value, 16u8).unwrap(),
-        };
-        let upper_bound = utils::ipnetwork::network_prefix(value);
-
-        self.prefixes
-            .range((Included(lower_bound), Included(upper_bound)))
-            .rev()
-            .map(|(_, aws_ip_prefix)| aws_ip_prefix)
-            .find(|&aws_ip_prefix| utils::ipnetwork::is_supernet_of(aws_ip_prefix.prefix, *value))
+        self.prefix_index
+            .longest_match(value)
+            .and_then(|prefix| self.prefixes.get(&prefix))
     }
 
     /*-------------------------------------------------------------------------
@@ -104,22 +120,56 @@ impl AwsIpRanges {
 
     /// Get all [AwsIpPrefix] records that are supernets of the provided [IpNetwork] CIDR.
     pub fn get_supernet_prefixes(&self, value: &IpNetwork) -> Option<BTreeSet<AwsIpPrefix>> {
-        let mut aws_ip_prefixes: BTreeSet<AwsIpPrefix> = BTreeSet::new();
+        let aws_ip_prefixes: BTreeSet<AwsIpPrefix> = self
+            .prefix_index
+            .supernets(value)
+            .iter()
+            .filter_map(|prefix| self.prefixes.get(prefix))
+            .cloned()
+            .collect();
 
-        let lower_bound = match value {
-            IpNetwork::V4(_) => utils::ipnetwork::new_network_prefix(value, 8u8).unwrap(),
-            IpNetwork::V6(_) => utils::ipnetwork::new_network_prefix(value, 16u8).unwrap(),
-        };
-        let upper_bound = utils::ipnetwork::network_prefix(value);
+        if !aws_ip_prefixes.is_empty() {
+            Some(aws_ip_prefixes)
+        } else {
+            None
+        }
+    }
 
-        for (_, aws_ip_prefix) in self
-            .prefixes
-            .range((Included(lower_bound), Included(upper_bound)))
-        {
-            if utils::ipnetwork::is_supernet_of(aws_ip_prefix.prefix, *value) {
-                aws_ip_prefixes.insert(aws_ip_prefix.clone());
-            }
+    /*-------------------------------------------------------------------------
+      Get Subnet Prefixes
+    -------------------------------------------------------------------------*/
+
+    /// Get all [AwsIpPrefix] records that are subnets of (contained in or equal to) the provided
+    /// [IpNetwork] CIDR.
+    pub fn get_subnet_prefixes(&self, value: &IpNetwork) -> Option<BTreeSet<AwsIpPrefix>> {
+        let aws_ip_prefixes: BTreeSet<AwsIpPrefix> = self
+            .prefix_index
+            .subnets(value)
+            .iter()
+            .filter_map(|prefix| self.prefixes.get(prefix))
+            .cloned()
+            .collect();
+
+        if !aws_ip_prefixes.is_empty() {
+            Some(aws_ip_prefixes)
+        } else {
+            None
         }
+    }
+
+    /*-------------------------------------------------------------------------
+      Get Overlapping Prefixes
+    -------------------------------------------------------------------------*/
+
+    /// Get all [AwsIpPrefix] records that overlap the provided [IpNetwork] CIDR; supernets of,
+    /// equal to, or subnets of the provided CIDR.
+    pub fn get_overlapping_prefixes(&self, value: &IpNetwork) -> Option<BTreeSet<AwsIpPrefix>> {
+        let aws_ip_prefixes: BTreeSet<AwsIpPrefix> = self
+            .get_supernet_prefixes(value)
+            .into_iter()
+            .chain(self.get_subnet_prefixes(value))
+            .flatten()
+            .collect();
 
         if !aws_ip_prefixes.is_empty() {
             Some(aws_ip_prefixes)
@@ -132,19 +182,19 @@ impl AwsIpRanges {
       Get Reference Counted Strings
     -------------------------------------------------------------------------*/
 
-    /// Get a reference-counted string (`Rc<str>`) region for the provided region name.
-    pub fn get_region(&self, value: &str) -> Option<Rc<str>> {
-        utils::get_rc_str_from_set(value, &self.regions)
+    /// Get an atomically reference-counted string (`Arc<str>`) region for the provided region name.
+    pub fn get_region(&self, value: &str) -> Option<Arc<str>> {
+        utils::get_arc_str_from_set(value, &self.regions)
     }
 
-    /// Get a reference-counted string (`Rc<str>`) network border group for the provided network border group name.
-    pub fn get_network_border_group(&self, value: &str) -> Option<Rc<str>> {
-        utils::get_rc_str_from_set(value, &self.network_border_groups)
+    /// Get an atomically reference-counted string (`Arc<str>`) network border group for the provided network border group name.
+    pub fn get_network_border_group(&self, value: &str) -> Option<Arc<str>> {
+        utils::get_arc_str_from_set(value, &self.network_border_groups)
     }
 
-    /// Get a reference-counted string (`Rc<str>`) service for the provided service name.
-    pub fn get_service(&self, value: &str) -> Option<Rc<str>> {
-        utils::get_rc_str_from_set(value, &self.services)
+    /// Get an atomically reference-counted string (`Arc<str>`) service for the provided service name.
+    pub fn get_service(&self, value: &str) -> Option<Arc<str>> {
+        utils::get_arc_str_from_set(value, &self.services)
     }
 
     /*-------------------------------------------------------------------------
@@ -165,6 +215,29 @@ impl AwsIpRanges {
     /// # }
     /// ```
     pub fn search<'p, I>(&self, values: I) -> Box<SearchResults>
+    where
+        I: IntoIterator<Item = &'p IpNetwork>,
+    {
+        self.search_with_mode(values, SearchMode::Supernets)
+    }
+
+    /// Search for the AWS IP Prefixes matching the provided [IpNetwork] CIDRs using the provided
+    /// [SearchMode]; use [SearchResults::relation] to tell whether each match is a supernet, an
+    /// exact match, or a subnet of the search CIDR.
+    ///
+    /// ```rust
+    /// # fn main() -> awsipranges::Result<()> {
+    /// use awsipranges::SearchMode;
+    /// use ipnetwork::IpNetwork;
+    ///
+    /// let aws_ip_ranges = awsipranges::get_ranges()?;
+    ///
+    /// let search_prefixes: Vec<IpNetwork> = vec!["3.0.0.0/8".parse().unwrap()];
+    /// let search_results = aws_ip_ranges.search_with_mode(&search_prefixes, SearchMode::Overlapping);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn search_with_mode<'p, I>(&self, values: I, mode: SearchMode) -> Box<SearchResults>
     where
         I: IntoIterator<Item = &'p IpNetwork>,
     {
@@ -177,7 +250,13 @@ impl AwsIpRanges {
         let mut result_aws_ip_prefixes: BTreeSet<AwsIpPrefix> = BTreeSet::new();
 
         for prefix in values.into_iter() {
-            if let Some(aws_ip_prefixes) = self.get_supernet_prefixes(prefix) {
+            let aws_ip_prefixes = match mode {
+                SearchMode::Supernets => self.get_supernet_prefixes(prefix),
+                SearchMode::Subnets => self.get_subnet_prefixes(prefix),
+                SearchMode::Overlapping => self.get_overlapping_prefixes(prefix),
+            };
+
+            if let Some(aws_ip_prefixes) = aws_ip_prefixes {
                 aws_ip_prefixes.iter().for_each(|aws_ip_prefix| {
                     result_aws_ip_prefixes.insert(aws_ip_prefix.clone());
                 });
@@ -197,10 +276,38 @@ impl AwsIpRanges {
             .sync_token
             .clone_from(&self.sync_token);
         search_results.aws_ip_ranges.create_date = self.create_date;
+        search_results
+            .aws_ip_ranges
+            .provenance
+            .clone_from(&self.provenance);
 
         search_results
     }
 
+    /*-------------------------------------------------------------------------
+      Attribute
+    -------------------------------------------------------------------------*/
+
+    /// Resolve an IP address or prefix to the most specific AWS IP Prefix containing it and its
+    /// effective services, along with the broader AWS IP Prefixes it overrides; see
+    /// [AmazonServicePolicy] for how the `AMAZON` service is treated.
+    ///
+    /// ```rust
+    /// # fn main() -> awsipranges::Result<()> {
+    /// use awsipranges::AmazonServicePolicy;
+    ///
+    /// let aws_ip_ranges = awsipranges::get_ranges()?;
+    ///
+    /// let attribution = aws_ip_ranges
+    ///     .attribute(&"3.141.102.225".parse().unwrap(), AmazonServicePolicy::Fallback);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn attribute(&self, value: &IpNetwork, policy: AmazonServicePolicy) -> Option<Attribution> {
+        let aws_ip_prefixes = self.get_supernet_prefixes(value)?;
+        Attribution::resolve(value, &aws_ip_prefixes, policy)
+    }
+
     /*-------------------------------------------------------------------------
       Filter
     -------------------------------------------------------------------------*/
@@ -220,7 +327,7 @@ impl AwsIpRanges {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn filter_builder(&self) -> FilterBuilder {
+    pub fn filter_builder(&self) -> FilterBuilder<'_> {
         FilterBuilder::new(self)
     }
 
@@ -252,16 +359,337 @@ impl AwsIpRanges {
         let mut aws_ip_ranges = Box::new(AwsIpRanges::from(filtered_aws_ip_prefix_map));
         aws_ip_ranges.sync_token.clone_from(&self.sync_token);
         aws_ip_ranges.create_date = self.create_date;
+        aws_ip_ranges.provenance.clone_from(&self.provenance);
+
+        aws_ip_ranges
+    }
+
+    /*-------------------------------------------------------------------------
+      Aggregate
+    -------------------------------------------------------------------------*/
+
+    /// Aggregate the AWS IP Prefixes into the minimal set of [IpNetwork] CIDRs covering exactly
+    /// the same address space; nested prefixes are dropped and adjacent sibling prefixes are
+    /// merged.
+    ///
+    /// ```rust
+    /// # fn main() -> awsipranges::Result<()> {
+    /// let aws_ip_ranges = awsipranges::get_ranges()?;
+    ///
+    /// let aggregated_prefixes = aws_ip_ranges.aggregate();
+    /// assert!(aggregated_prefixes.len() <= aws_ip_ranges.prefixes().len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn aggregate(&self) -> Vec<IpNetwork> {
+        utils::ipnetwork::aggregate(self.prefixes.keys().copied())
+    }
+
+    /// Aggregate the AWS IP Prefixes in each AWS region.
+    pub fn aggregate_by_region(&self) -> BTreeMap<Arc<str>, Vec<IpNetwork>> {
+        self.aggregate_by(|aws_ip_prefix| vec![aws_ip_prefix.region.clone()])
+    }
+
+    /// Aggregate the AWS IP Prefixes in each network border group.
+    pub fn aggregate_by_network_border_group(&self) -> BTreeMap<Arc<str>, Vec<IpNetwork>> {
+        self.aggregate_by(|aws_ip_prefix| vec![aws_ip_prefix.network_border_group.clone()])
+    }
+
+    /// Aggregate the AWS IP Prefixes used by each AWS service. Prefixes used by multiple
+    /// services are included in the aggregate for each service.
+    pub fn aggregate_by_service(&self) -> BTreeMap<Arc<str>, Vec<IpNetwork>> {
+        self.aggregate_by(|aws_ip_prefix| aws_ip_prefix.services.iter().cloned().collect())
+    }
+
+    fn aggregate_by<F>(&self, keys: F) -> BTreeMap<Arc<str>, Vec<IpNetwork>>
+    where
+        F: Fn(&AwsIpPrefix) -> Vec<Arc<str>>,
+    {
+        let mut groups: BTreeMap<Arc<str>, Vec<IpNetwork>> = BTreeMap::new();
+        for aws_ip_prefix in self.prefixes.values() {
+            for key in keys(aws_ip_prefix) {
+                groups.entry(key).or_default().push(aws_ip_prefix.prefix);
+            }
+        }
+
+        groups
+            .into_iter()
+            .map(|(key, prefixes)| (key, utils::ipnetwork::aggregate(prefixes)))
+            .collect()
+    }
+
+    /*-------------------------------------------------------------------------
+      Stats
+    -------------------------------------------------------------------------*/
+
+    /// Compute address-space statistics (prefix counts, total and unique address counts, and
+    /// prefix-length histograms) for all prefixes and by region, network border group, service,
+    /// and address family.
+    ///
+    /// ```rust
+    /// # fn main() -> awsipranges::Result<()> {
+    /// let aws_ip_ranges = awsipranges::get_ranges()?;
+    ///
+    /// let ranges_stats = aws_ip_ranges.stats();
+    /// let ipv4_addresses = ranges_stats.all.ipv4.unique_addresses;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stats(&self) -> RangesStats {
+        RangesStats::new(self.prefixes.values())
+    }
+
+    /*-------------------------------------------------------------------------
+      Set Operations
+    -------------------------------------------------------------------------*/
+
+    /// Get the AWS IP Prefixes in either these AWS IP Ranges or `other`; also available as
+    /// `&a | &b`. Prefixes present in both keep the region and network border group from `self`
+    /// and the services from both.
+    ///
+    /// ```rust
+    /// # fn main() -> awsipranges::Result<()> {
+    /// let aws_ip_ranges = awsipranges::get_ranges()?;
+    ///
+    /// let ipv4_ranges = aws_ip_ranges.filter_builder().ipv4().filter();
+    /// let ipv6_ranges = aws_ip_ranges.filter_builder().ipv6().filter();
+    ///
+    /// let union = ipv4_ranges.union(&ipv6_ranges);
+    /// assert_eq!(union.prefixes().len(), aws_ip_ranges.prefixes().len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn union(&self, other: &AwsIpRanges) -> Box<AwsIpRanges> {
+        let mut prefixes: BTreeMap<IpNetwork, AwsIpPrefix> = self.prefixes.clone();
+        for aws_ip_prefix in other.prefixes.values() {
+            insert_merged(&mut prefixes, aws_ip_prefix.clone());
+        }
+
+        self.with_prefixes(prefixes)
+    }
+
+    /// Get the address space covered by both these AWS IP Ranges and `other`; also available as
+    /// `&a & &b`. Where a prefix in one overlaps a prefix in the other, the result contains the
+    /// more specific of the two prefixes with its region, network border group, and services.
+    ///
+    /// ```rust
+    /// # fn main() -> awsipranges::Result<()> {
+    /// let aws_ip_ranges = awsipranges::get_ranges()?;
+    ///
+    /// let amazon_ranges = aws_ip_ranges.filter_builder().services(["AMAZON"])?.filter();
+    /// let ec2_ranges = aws_ip_ranges.filter_builder().services(["EC2"])?.filter();
+    ///
+    /// let intersection = amazon_ranges.intersection(&ec2_ranges);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn intersection(&self, other: &AwsIpRanges) -> Box<AwsIpRanges> {
+        let mut prefixes: BTreeMap<IpNetwork, AwsIpPrefix> = BTreeMap::new();
+        for aws_ip_prefix in self.prefixes.values() {
+            for other_aws_ip_prefix in other
+                .get_overlapping_prefixes(&aws_ip_prefix.prefix)
+                .into_iter()
+                .flatten()
+            {
+                match aws_ip_prefix
+                    .prefix
+                    .prefix()
+                    .cmp(&other_aws_ip_prefix.prefix.prefix())
+                {
+                    Ordering::Less => insert_merged(&mut prefixes, other_aws_ip_prefix),
+                    Ordering::Greater => insert_merged(&mut prefixes, aws_ip_prefix.clone()),
+                    Ordering::Equal => {
+                        insert_merged(&mut prefixes, aws_ip_prefix.clone());
+                        insert_merged(&mut prefixes, other_aws_ip_prefix);
+                    }
+                }
+            }
+        }
+
+        self.with_prefixes(prefixes)
+    }
+
+    /// Get the address space covered by these AWS IP Ranges but not by `other`; also available
+    /// as `&a - &b`. Prefixes partially covered by `other` are split into the sub-CIDRs that
+    /// remain, which keep the region, network border group, and services of the original prefix.
+    ///
+    /// ```rust
+    /// # fn main() -> awsipranges::Result<()> {
+    /// let aws_ip_ranges = awsipranges::get_ranges()?;
+    ///
+    /// let cloudfront_ranges = aws_ip_ranges.filter_builder().services(["CLOUDFRONT"])?.filter();
+    /// let ec2_ranges = aws_ip_ranges.filter_builder().services(["EC2"])?.filter();
+    ///
+    /// let difference = cloudfront_ranges.difference(&ec2_ranges);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn difference(&self, other: &AwsIpRanges) -> Box<AwsIpRanges> {
+        // Split the most specific prefixes first so that their metadata (including their
+        // services) is kept when a split supernet yields the same sub-CIDR
+        let mut aws_ip_prefixes: Vec<&AwsIpPrefix> = self.prefixes.values().collect();
+        aws_ip_prefixes.sort_by_key(|aws_ip_prefix| Reverse(aws_ip_prefix.prefix.prefix()));
+
+        let mut prefixes: BTreeMap<IpNetwork, AwsIpPrefix> = BTreeMap::new();
+        for aws_ip_prefix in aws_ip_prefixes {
+            let removed: Vec<IpNetwork> = other
+                .get_overlapping_prefixes(&aws_ip_prefix.prefix)
+                .into_iter()
+                .flatten()
+                .map(|other_aws_ip_prefix| other_aws_ip_prefix.prefix)
+                .collect();
+
+            for prefix in utils::ipnetwork::subtract(aws_ip_prefix.prefix, &removed) {
+                prefixes.entry(prefix).or_insert_with(|| AwsIpPrefix {
+                    prefix,
+                    ..aws_ip_prefix.clone()
+                });
+            }
+        }
+
+        self.with_prefixes(prefixes)
+    }
+
+    /// Create new AWS IP Ranges from the provided prefixes, keeping the sync token, create date,
+    /// and provenance of these AWS IP Ranges.
+    fn with_prefixes(&self, prefixes: BTreeMap<IpNetwork, AwsIpPrefix>) -> Box<AwsIpRanges> {
+        let mut aws_ip_ranges = Box::new(AwsIpRanges::from(prefixes));
+        aws_ip_ranges.sync_token.clone_from(&self.sync_token);
+        aws_ip_ranges.create_date = self.create_date;
+        aws_ip_ranges.provenance.clone_from(&self.provenance);
 
         aws_ip_ranges
     }
 
+    /*-------------------------------------------------------------------------
+      Diff
+    -------------------------------------------------------------------------*/
+
+    /// Compare these (old) AWS IP Ranges with `other` (new) AWS IP Ranges, returning the
+    /// prefixes that were added, removed, or whose region, network border group, or services
+    /// changed.
+    ///
+    /// ```rust
+    /// # fn main() -> awsipranges::Result<()> {
+    /// let aws_ip_ranges = awsipranges::get_ranges()?;
+    ///
+    /// let ranges_diff = aws_ip_ranges.diff(&aws_ip_ranges);
+    /// assert!(ranges_diff.is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn diff(&self, other: &AwsIpRanges) -> RangesDiff {
+        let mut ranges_diff = RangesDiff {
+            old_sync_token: self.sync_token.clone(),
+            new_sync_token: other.sync_token.clone(),
+            old_create_date: self.create_date,
+            new_create_date: other.create_date,
+            ..RangesDiff::default()
+        };
+
+        for (prefix, old) in self.prefixes.iter() {
+            match other.prefixes.get(prefix) {
+                None => {
+                    ranges_diff.removed.insert(*prefix, old.clone());
+                }
+                Some(new) if new != old => {
+                    ranges_diff.changed.insert(
+                        *prefix,
+                        PrefixChange {
+                            old: old.clone(),
+                            new: new.clone(),
+                        },
+                    );
+                }
+                Some(_) => {}
+            }
+        }
+
+        for (prefix, new) in other.prefixes.iter() {
+            if !self.prefixes.contains_key(prefix) {
+                ranges_diff.added.insert(*prefix, new.clone());
+            }
+        }
+
+        ranges_diff
+    }
+
+    /*-------------------------------------------------------------------------
+      AWS IP Ranges from File
+    -------------------------------------------------------------------------*/
+
+    /// Read and parse AWS IP Ranges from an `ip-ranges.json` file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Box<AwsIpRanges>> {
+        let path = path.as_ref();
+        let json = fs::read_to_string(path).map_err(|error| Error::Io {
+            path: path.to_path_buf(),
+            source: error,
+        })?;
+
+        let mut aws_ip_ranges = AwsIpRanges::from_json(&json, &path.display().to_string())?;
+        aws_ip_ranges.provenance = Some(Provenance {
+            source: DataSource::File,
+            location: path.display().to_string(),
+            fetched_at: Utc::now(),
+            cache_age: None,
+            http_status: None,
+            attempts: 0,
+        });
+
+        Ok(aws_ip_ranges)
+    }
+
+    /*-------------------------------------------------------------------------
+      AWS IP Ranges to JSON
+    -------------------------------------------------------------------------*/
+
+    /// Serialize the AWS IP Ranges into AWS's native `ip-ranges.json` format. Prefixes used by
+    /// multiple services are written as one record per service, matching the published file.
+    ///
+    /// ```rust
+    /// # fn main() -> awsipranges::Result<()> {
+    /// let aws_ip_ranges = awsipranges::get_ranges()?;
+    ///
+    /// let json = aws_ip_ranges.filter_builder().ipv4().filter().to_json();
+    /// assert!(json.contains("\"ip_prefix\""));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&json::JsonIpRanges::from(self))
+            .expect("AWS IP Ranges JSON data structures serialize to JSON")
+    }
+
+    /// Write the AWS IP Ranges to a file in AWS's native `ip-ranges.json` format (see
+    /// [AwsIpRanges::to_json]); the file can be read with [AwsIpRanges::from_file] or by any tool
+    /// that consumes the published `ip-ranges.json` file.
+    ///
+    /// ```rust,no_run
+    /// # fn main() -> awsipranges::Result<()> {
+    /// let aws_ip_ranges = awsipranges::get_ranges()?;
+    ///
+    /// aws_ip_ranges
+    ///     .filter_builder()
+    ///     .regions(["us-east-1"])?
+    ///     .filter()
+    ///     .write_json("./ip-ranges-us-east-1.json")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn write_json<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        fs::write(path, self.to_json()).map_err(|error| Error::Io {
+            path: path.to_path_buf(),
+            source: error,
+        })
+    }
+
     /*-------------------------------------------------------------------------
       (Internal) AWS IP Ranges from JSON
     -------------------------------------------------------------------------*/
 
-    pub(crate) fn from_json(json: &str) -> Result<Box<AwsIpRanges>> {
-        let json_ip_ranges = json::parse(json)?;
+    pub(crate) fn from_json(json: &str, origin: &str) -> Result<Box<AwsIpRanges>> {
+        let json_ip_ranges = json::parse(json, origin)?;
 
         let mut aws_ip_ranges = Box::new(AwsIpRanges::default());
 
@@ -278,7 +706,7 @@ impl AwsIpRanges {
                     .iter()
                     .map(|ipv6_prefix| ipv6_prefix.region),
             )
-            .map(Rc::from)
+            .map(Arc::from)
             .collect();
 
         aws_ip_ranges.network_border_groups = json_ip_ranges
@@ -291,7 +719,7 @@ impl AwsIpRanges {
                     .iter()
                     .map(|ipv6_prefix| ipv6_prefix.network_border_group),
             )
-            .map(Rc::from)
+            .map(Arc::from)
             .collect();
 
         aws_ip_ranges.services = json_ip_ranges
@@ -304,123 +732,149 @@ impl AwsIpRanges {
                     .iter()
                     .map(|ipv6_prefix| ipv6_prefix.service),
             )
-            .map(Rc::from)
+            .map(Arc::from)
             .collect();
 
         for json_ipv4_prefix in &json_ip_ranges.prefixes {
-            aws_ip_ranges
-                .prefixes
-                .entry(IpNetwork::V4(json_ipv4_prefix.ip_prefix))
-                .and_modify(|prefix| {
-                    // Verify IP prefix invariants
-                    // An IP prefix should always be assigned to a single region and network border group
-                    assert_eq!(
-                        prefix.region,
-                        utils::get_rc_str_from_set(json_ipv4_prefix.region, &aws_ip_ranges.regions)
-                            .unwrap()
-                    );
-                    assert_eq!(
-                        prefix.network_border_group,
-                        utils::get_rc_str_from_set(
-                            json_ipv4_prefix.network_border_group,
-                            &aws_ip_ranges.network_border_groups
-                        )
-                        .unwrap()
-                    );
-                    // Duplicate IP prefix entries are used to indicate multiple AWS services use a prefix
-                    prefix.services.insert(
-                        utils::get_rc_str_from_set(
-                            json_ipv4_prefix.service,
-                            &aws_ip_ranges.services,
-                        )
-                        .unwrap(),
-                    );
-                })
-                .or_insert(AwsIpPrefix {
-                    prefix: IpNetwork::V4(json_ipv4_prefix.ip_prefix),
-                    region: utils::get_rc_str_from_set(
-                        json_ipv4_prefix.region,
-                        &aws_ip_ranges.regions,
-                    )
-                    .unwrap(),
-                    network_border_group: utils::get_rc_str_from_set(
-                        json_ipv4_prefix.network_border_group,
-                        &aws_ip_ranges.network_border_groups,
-                    )
-                    .unwrap(),
-                    services: BTreeSet::from([utils::get_rc_str_from_set(
-                        json_ipv4_prefix.service,
-                        &aws_ip_ranges.services,
-                    )
-                    .unwrap()]),
-                });
+            aws_ip_ranges.insert_json_prefix(
+                IpNetwork::V4(json_ipv4_prefix.ip_prefix),
+                json_ipv4_prefix.region,
+                json_ipv4_prefix.network_border_group,
+                json_ipv4_prefix.service,
+                origin,
+            )?;
         }
 
         for json_ipv6_prefix in &json_ip_ranges.ipv6_prefixes {
-            aws_ip_ranges
-                .prefixes
-                .entry(IpNetwork::V6(json_ipv6_prefix.ipv6_prefix))
-                .and_modify(|prefix| {
-                    // Verify IP prefix invariants
-                    // An IP prefix should always be assigned to a single region and network border group
-                    assert_eq!(
-                        prefix.region,
-                        utils::get_rc_str_from_set(json_ipv6_prefix.region, &aws_ip_ranges.regions)
-                            .unwrap()
-                    );
-                    assert_eq!(
-                        prefix.network_border_group,
-                        utils::get_rc_str_from_set(
-                            json_ipv6_prefix.network_border_group,
-                            &aws_ip_ranges.network_border_groups
-                        )
-                        .unwrap()
-                    );
-                    // Duplicate IP prefix entries are used to indicate multiple AWS services use a prefix
-                    prefix.services.insert(
-                        utils::get_rc_str_from_set(
-                            json_ipv6_prefix.service,
-                            &aws_ip_ranges.services,
-                        )
-                        .unwrap(),
-                    );
-                })
-                .or_insert(AwsIpPrefix {
-                    prefix: IpNetwork::V6(json_ipv6_prefix.ipv6_prefix),
-                    region: utils::get_rc_str_from_set(
-                        json_ipv6_prefix.region,
-                        &aws_ip_ranges.regions,
-                    )
-                    .unwrap(),
-                    network_border_group: utils::get_rc_str_from_set(
-                        json_ipv6_prefix.network_border_group,
-                        &aws_ip_ranges.network_border_groups,
-                    )
-                    .unwrap(),
-                    services: BTreeSet::from([utils::get_rc_str_from_set(
-                        json_ipv6_prefix.service,
-                        &aws_ip_ranges.services,
-                    )
-                    .unwrap()]),
-                });
+            aws_ip_ranges.insert_json_prefix(
+                IpNetwork::V6(json_ipv6_prefix.ipv6_prefix),
+                json_ipv6_prefix.region,
+                json_ipv6_prefix.network_border_group,
+                json_ipv6_prefix.service,
+                origin,
+            )?;
         }
 
+        aws_ip_ranges.prefix_index = aws_ip_ranges.prefixes.keys().copied().collect();
+
         Ok(aws_ip_ranges)
     }
-}
-
-/*--------------------------------------------------------------------------------------
-  Create AWS IP Ranges from BTreeSet of AWS IP Prefixes
---------------------------------------------------------------------------------------*/
 
-impl From<BTreeSet<AwsIpPrefix>> for AwsIpRanges {
-    fn from(value: BTreeSet<AwsIpPrefix>) -> Self {
-        let aws_ip_prefix_map: BTreeMap<IpNetwork, AwsIpPrefix> = value
-            .into_iter()
-            .map(|aws_ip_prefix| (aws_ip_prefix.prefix, aws_ip_prefix))
-            .collect();
+    /// Insert a JSON prefix record, merging the services of duplicate prefix
+    /// records.
+    fn insert_json_prefix(
+        &mut self,
+        prefix: IpNetwork,
+        region: &str,
+        network_border_group: &str,
+        service: &str,
+        origin: &str,
+    ) -> Result<()> {
+        // The region, network border group, and service sets are built from the
+        // same JSON records, so these lookups always succeed
+        let region = utils::get_arc_str_from_set(region, &self.regions).unwrap();
+        let network_border_group =
+            utils::get_arc_str_from_set(network_border_group, &self.network_border_groups).unwrap();
+        let service = utils::get_arc_str_from_set(service, &self.services).unwrap();
+
+        match self.prefixes.entry(prefix) {
+            Entry::Occupied(mut entry) => {
+                let aws_ip_prefix = entry.get_mut();
+
+                // Verify IP prefix invariants
+                // An IP prefix should always be assigned to a single region and network border group
+                if aws_ip_prefix.region != region {
+                    return Err(Error::Validation {
+                        origin: origin.to_string(),
+                        message: format!(
+                            "prefix {prefix} is assigned to multiple regions: {} and {region}",
+                            aws_ip_prefix.region
+                        ),
+                    });
+                }
+                if aws_ip_prefix.network_border_group != network_border_group {
+                    return Err(Error::Validation {
+                        origin: origin.to_string(),
+                        message: format!(
+                            "prefix {prefix} is assigned to multiple network border groups: {} and {network_border_group}",
+                            aws_ip_prefix.network_border_group
+                        ),
+                    });
+                }
+
+                // Duplicate IP prefix entries are used to indicate multiple AWS services use a prefix
+                aws_ip_prefix.services.insert(service);
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(AwsIpPrefix {
+                    prefix,
+                    region,
+                    network_border_group,
+                    services: BTreeSet::from([service]),
+                });
+            }
+        }
 
-        Self::from(aws_ip_prefix_map)
+        Ok(())
+    }
+}
+
+/// Insert an AWS IP Prefix into a prefix map, adding its services to an existing record for the
+/// same prefix.
+fn insert_merged(prefixes: &mut BTreeMap<IpNetwork, AwsIpPrefix>, aws_ip_prefix: AwsIpPrefix) {
+    match prefixes.entry(aws_ip_prefix.prefix) {
+        Entry::Vacant(entry) => {
+            entry.insert(aws_ip_prefix);
+        }
+        Entry::Occupied(mut entry) => {
+            entry.get_mut().services.extend(aws_ip_prefix.services);
+        }
+    }
+}
+
+/*--------------------------------------------------------------------------------------
+  Set Operators
+--------------------------------------------------------------------------------------*/
+
+impl BitOr for &AwsIpRanges {
+    type Output = AwsIpRanges;
+
+    /// [AwsIpRanges::union]
+    fn bitor(self, rhs: Self) -> Self::Output {
+        *self.union(rhs)
+    }
+}
+
+impl BitAnd for &AwsIpRanges {
+    type Output = AwsIpRanges;
+
+    /// [AwsIpRanges::intersection]
+    fn bitand(self, rhs: Self) -> Self::Output {
+        *self.intersection(rhs)
+    }
+}
+
+impl Sub for &AwsIpRanges {
+    type Output = AwsIpRanges;
+
+    /// [AwsIpRanges::difference]
+    fn sub(self, rhs: Self) -> Self::Output {
+        *self.difference(rhs)
+    }
+}
+
+/*--------------------------------------------------------------------------------------
+  Create AWS IP Ranges from BTreeSet of AWS IP Prefixes
+--------------------------------------------------------------------------------------*/
+
+impl From<BTreeSet<AwsIpPrefix>> for AwsIpRanges {
+    fn from(value: BTreeSet<AwsIpPrefix>) -> Self {
+        let aws_ip_prefix_map: BTreeMap<IpNetwork, AwsIpPrefix> = value
+            .into_iter()
+            .map(|aws_ip_prefix| (aws_ip_prefix.prefix, aws_ip_prefix))
+            .collect();
+
+        Self::from(aws_ip_prefix_map)
     }
 }
 
@@ -433,6 +887,7 @@ impl From<BTreeMap<IpNetwork, AwsIpPrefix>> for AwsIpRanges {
         let mut aws_ip_ranges = AwsIpRanges::default();
 
         aws_ip_ranges.prefixes = value;
+        aws_ip_ranges.prefix_index = aws_ip_ranges.prefixes.keys().copied().collect();
 
         aws_ip_ranges.regions = aws_ip_ranges
             .prefixes
@@ -457,6 +912,54 @@ impl From<BTreeMap<IpNetwork, AwsIpPrefix>> for AwsIpRanges {
     }
 }
 
+/*--------------------------------------------------------------------------------------
+  Serialize and Deserialize AWS IP Ranges
+--------------------------------------------------------------------------------------*/
+
+#[derive(serde::Serialize)]
+struct SerializeAwsIpRanges<'a> {
+    sync_token: &'a str,
+    create_date: &'a DateTime<Utc>,
+    prefixes: Vec<&'a AwsIpPrefix>,
+}
+
+#[derive(serde::Deserialize)]
+struct DeserializeAwsIpRanges {
+    sync_token: String,
+    create_date: DateTime<Utc>,
+    prefixes: BTreeSet<AwsIpPrefix>,
+}
+
+/// Serializes the sync token, create date, and prefix records; the regions, network border
+/// groups, and services sets are derived from the prefix records when deserializing.
+impl serde::Serialize for AwsIpRanges {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        SerializeAwsIpRanges {
+            sync_token: &self.sync_token,
+            create_date: &self.create_date,
+            prefixes: self.prefixes.values().collect(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for AwsIpRanges {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let value = DeserializeAwsIpRanges::deserialize(deserializer)?;
+
+        let mut aws_ip_ranges = AwsIpRanges::from(value.prefixes);
+        aws_ip_ranges.sync_token = value.sync_token;
+        aws_ip_ranges.create_date = value.create_date;
+
+        Ok(aws_ip_ranges)
+    }
+}
+
 /*-------------------------------------------------------------------------------------------------
   Unit Tests
 -------------------------------------------------------------------------------------------------*/
@@ -466,6 +969,7 @@ pub(crate) mod tests {
     use super::*;
     use crate::core::aws_ip_prefix::tests::{test_aws_ipv4_prefix, test_aws_ipv6_prefix};
     use crate::core::filter::FilterBuilder;
+    use chrono::TimeZone;
 
     /*----------------------------------------------------------------------------------
       Test Helper Functions
@@ -482,9 +986,9 @@ pub(crate) mod tests {
             },
             AwsIpPrefix {
                 prefix: "10.1.0.0/16".parse().unwrap(),
-                region: Rc::from("us-west-1"),
-                network_border_group: Rc::from("us-west-1"),
-                services: [Rc::from("EC2"), Rc::from("S3")].into_iter().collect(),
+                region: Arc::from("us-west-1"),
+                network_border_group: Arc::from("us-west-1"),
+                services: [Arc::from("EC2"), Arc::from("S3")].into_iter().collect(),
             },
             test_aws_ipv6_prefix(),
             AwsIpPrefix {
@@ -493,9 +997,9 @@ pub(crate) mod tests {
             },
             AwsIpPrefix {
                 prefix: "2001:db8:1::/48".parse().unwrap(),
-                region: Rc::from("us-west-1"),
-                network_border_group: Rc::from("us-west-1"),
-                services: [Rc::from("EC2"), Rc::from("S3")].into_iter().collect(),
+                region: Arc::from("us-west-1"),
+                network_border_group: Arc::from("us-west-1"),
+                services: [Arc::from("EC2"), Arc::from("S3")].into_iter().collect(),
             },
         ]
         .into_iter()
@@ -516,6 +1020,34 @@ pub(crate) mod tests {
       Getters
     -------------------------------------------------------------------------*/
 
+    #[test]
+    fn test_aws_ip_ranges_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<AwsIpRanges>();
+    }
+
+    #[test]
+    fn test_aws_ip_ranges_shared_across_threads() {
+        let aws_ip_ranges: Arc<AwsIpRanges> = Arc::from(test_aws_ip_ranges());
+
+        let handles: Vec<_> = ["10.0.0.1/32", "10.1.0.1/32", "2001:db8::1/128"]
+            .into_iter()
+            .map(|search_prefix| {
+                let aws_ip_ranges = Arc::clone(&aws_ip_ranges);
+                std::thread::spawn(move || {
+                    let search_prefix: IpNetwork = search_prefix.parse().unwrap();
+                    aws_ip_ranges
+                        .get_longest_match_prefix(&search_prefix)
+                        .map(|aws_ip_prefix| aws_ip_prefix.prefix)
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            assert!(handle.join().unwrap().is_some());
+        }
+    }
+
     #[test]
     fn test_aws_ip_ranges_sync_token() {
         let create_date = Utc::now();
@@ -547,9 +1079,9 @@ pub(crate) mod tests {
             },
             AwsIpPrefix {
                 prefix: "10.1.0.0/16".parse().unwrap(),
-                region: Rc::from("us-west-1"),
-                network_border_group: Rc::from("us-west-1"),
-                services: [Rc::from("EC2"), Rc::from("S3")].into_iter().collect(),
+                region: Arc::from("us-west-1"),
+                network_border_group: Arc::from("us-west-1"),
+                services: [Arc::from("EC2"), Arc::from("S3")].into_iter().collect(),
             },
         ]
         .iter()
@@ -563,7 +1095,7 @@ pub(crate) mod tests {
 
     #[test]
     fn test_aws_ip_ranges_regions() {
-        let regions: BTreeSet<Rc<str>> = [Rc::from("us-east-1"), Rc::from("us-west-1")]
+        let regions: BTreeSet<Arc<str>> = [Arc::from("us-east-1"), Arc::from("us-west-1")]
             .into_iter()
             .collect();
         let aws_ip_ranges = AwsIpRanges {
@@ -575,8 +1107,8 @@ pub(crate) mod tests {
 
     #[test]
     fn test_aws_ip_ranges_network_border_groups() {
-        let network_border_groups: BTreeSet<Rc<str>> =
-            [Rc::from("us-east-1"), Rc::from("us-west-1")]
+        let network_border_groups: BTreeSet<Arc<str>> =
+            [Arc::from("us-east-1"), Arc::from("us-west-1")]
                 .into_iter()
                 .collect();
         let aws_ip_ranges = AwsIpRanges {
@@ -591,7 +1123,8 @@ pub(crate) mod tests {
 
     #[test]
     fn test_aws_ip_ranges_services() {
-        let services: BTreeSet<Rc<str>> = [Rc::from("EC2"), Rc::from("S3")].into_iter().collect();
+        let services: BTreeSet<Arc<str>> =
+            [Arc::from("EC2"), Arc::from("S3")].into_iter().collect();
         let aws_ip_ranges = AwsIpRanges {
             services: services.clone(),
             ..Default::default()
@@ -615,6 +1148,9 @@ pub(crate) mod tests {
 
         let prefix_not_in_range: IpNetwork = "192.168.0.0/24".parse().unwrap();
         assert_eq!(aws_ip_ranges.get_prefix(&prefix_not_in_range), None);
+
+        let prefix_with_host_bits: IpNetwork = "10.0.0.1/8".parse().unwrap();
+        assert_eq!(aws_ip_ranges.get_prefix(&prefix_with_host_bits), None);
     }
 
     /*-------------------------------------------------------------------------
@@ -664,13 +1200,200 @@ pub(crate) mod tests {
         );
     }
 
+    #[test]
+    fn test_get_supernet_prefixes_shorter_than_slash_8() {
+        let aws_ip_ranges = AwsIpRanges::from(
+            [
+                AwsIpPrefix {
+                    prefix: "8.0.0.0/6".parse().unwrap(),
+                    ..test_aws_ipv4_prefix()
+                },
+                AwsIpPrefix {
+                    prefix: "2000::/8".parse().unwrap(),
+                    ..test_aws_ipv6_prefix()
+                },
+            ]
+            .into_iter()
+            .collect::<BTreeSet<AwsIpPrefix>>(),
+        );
+
+        let ipv4_address: IpNetwork = "10.0.0.1/32".parse().unwrap();
+        let ipv6_address: IpNetwork = "2001:db8::1/128".parse().unwrap();
+
+        assert_eq!(
+            aws_ip_ranges
+                .get_longest_match_prefix(&ipv4_address)
+                .unwrap()
+                .prefix,
+            "8.0.0.0/6".parse().unwrap()
+        );
+        assert_eq!(
+            aws_ip_ranges
+                .get_supernet_prefixes(&ipv6_address)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    /*-------------------------------------------------------------------------
+      Get Subnet and Overlapping Prefixes
+    -------------------------------------------------------------------------*/
+
+    #[test]
+    fn test_get_subnet_prefixes() {
+        let aws_ip_ranges = test_aws_ip_ranges();
+
+        let wide_prefix: IpNetwork = "8.0.0.0/6".parse().unwrap();
+        let subnet_prefixes = aws_ip_ranges.get_subnet_prefixes(&wide_prefix).unwrap();
+        assert_eq!(subnet_prefixes.len(), 3);
+
+        let prefix: IpNetwork = "10.1.0.0/16".parse().unwrap();
+        let subnet_prefixes = aws_ip_ranges.get_subnet_prefixes(&prefix).unwrap();
+        assert_eq!(subnet_prefixes.len(), 1); // Exact match
+
+        let prefix_in_range: IpNetwork = "10.0.0.0/24".parse().unwrap();
+        assert_eq!(aws_ip_ranges.get_subnet_prefixes(&prefix_in_range), None);
+    }
+
+    #[test]
+    fn test_get_overlapping_prefixes() {
+        let aws_ip_ranges = test_aws_ip_ranges();
+
+        let prefix: IpNetwork = "10.0.0.0/15".parse().unwrap();
+        let overlapping_prefixes = aws_ip_ranges.get_overlapping_prefixes(&prefix).unwrap();
+        assert_eq!(overlapping_prefixes.len(), 3); // 10.0.0.0/8 supernet and two /16 subnets
+
+        let prefix_not_in_range: IpNetwork = "192.168.0.0/16".parse().unwrap();
+        assert_eq!(
+            aws_ip_ranges.get_overlapping_prefixes(&prefix_not_in_range),
+            None
+        );
+    }
+
+    /*-------------------------------------------------------------------------
+      Prefix Index vs. Range Scan
+    -------------------------------------------------------------------------*/
+
+    /// Previous `get_supernet_prefixes` implementation; scans the prefixes
+    /// between a /8 (IPv4) or /16 (IPv6) lower bound and the search prefix.
+    fn range_scan_supernet_prefixes(
+        aws_ip_ranges: &AwsIpRanges,
+        value: &IpNetwork,
+    ) -> BTreeSet<AwsIpPrefix> {
+        use std::ops::Bound::Included;
+
+        let lower_bound = match value {
+            IpNetwork::V4(_) => utils::ipnetwork::new_network_prefix(value, 8u8).unwrap(),
+            IpNetwork::V6(_) => utils::ipnetwork::new_network_prefix(value, 16u8).unwrap(),
+        };
+        let upper_bound = utils::ipnetwork::network_prefix(value);
+
+        aws_ip_ranges
+            .prefixes
+            .range((Included(lower_bound), Included(upper_bound)))
+            .map(|(_, aws_ip_prefix)| aws_ip_prefix)
+            .filter(|aws_ip_prefix| utils::ipnetwork::is_supernet_of(aws_ip_prefix.prefix, *value))
+            .cloned()
+            .collect()
+    }
+
+    #[test_log::test]
+    fn test_prefix_index_matches_range_scan() {
+        // Deterministic pseudo-random prefixes (linear congruential generator)
+        let mut state: u64 = 0x853c_49e6_748f_ea9b;
+        let mut next = move || {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            state
+        };
+
+        // Prefixes /8 and longer, which the range scan is able to find
+        let prefixes: BTreeSet<AwsIpPrefix> = (0..5000)
+            .map(|index| {
+                let prefix = if index % 4 == 0 {
+                    let address = std::net::Ipv6Addr::from(
+                        (0x2001_0db8_u128 << 96) | ((next() as u128) << 40),
+                    );
+                    let len = 32 + (next() % 33) as u8;
+                    IpNetwork::V6(ipnetwork::Ipv6Network::new(address, len).unwrap())
+                } else {
+                    let address = std::net::Ipv4Addr::from((next() >> 32) as u32 & 0x03ff_ffff);
+                    let len = 8 + (next() % 21) as u8;
+                    IpNetwork::V4(ipnetwork::Ipv4Network::new(address, len).unwrap())
+                };
+                AwsIpPrefix {
+                    prefix: utils::ipnetwork::network_prefix(&prefix),
+                    ..test_aws_ipv4_prefix()
+                }
+            })
+            .collect();
+        let aws_ip_ranges = AwsIpRanges::from(prefixes);
+
+        let queries: Vec<IpNetwork> = (0..5000)
+            .map(|index| {
+                if index % 4 == 0 {
+                    let address = std::net::Ipv6Addr::from(
+                        (0x2001_0db8_u128 << 96) | ((next() as u128) << 40),
+                    );
+                    IpNetwork::V6(ipnetwork::Ipv6Network::new(address, 128).unwrap())
+                } else {
+                    let address = std::net::Ipv4Addr::from((next() >> 32) as u32 & 0x03ff_ffff);
+                    IpNetwork::V4(ipnetwork::Ipv4Network::new(address, 32).unwrap())
+                }
+            })
+            .collect();
+
+        let start = std::time::Instant::now();
+        let range_scan_results: Vec<BTreeSet<AwsIpPrefix>> = queries
+            .iter()
+            .map(|query| range_scan_supernet_prefixes(&aws_ip_ranges, query))
+            .collect();
+        let range_scan_elapsed = start.elapsed();
+
+        let start = std::time::Instant::now();
+        let prefix_index_results: Vec<BTreeSet<AwsIpPrefix>> = queries
+            .iter()
+            .map(|query| {
+                aws_ip_ranges
+                    .get_supernet_prefixes(query)
+                    .unwrap_or_default()
+            })
+            .collect();
+        let prefix_index_elapsed = start.elapsed();
+
+        log::info!(
+            "{} supernet queries against {} prefixes: range scan {:?}, prefix index {:?}",
+            queries.len(),
+            aws_ip_ranges.prefixes().len(),
+            range_scan_elapsed,
+            prefix_index_elapsed
+        );
+
+        assert_eq!(prefix_index_results, range_scan_results);
+        assert!(prefix_index_results.iter().any(|result| !result.is_empty()));
+
+        for (query, expected) in queries.iter().zip(&range_scan_results) {
+            assert_eq!(
+                aws_ip_ranges
+                    .get_longest_match_prefix(query)
+                    .map(|aws_ip_prefix| aws_ip_prefix.prefix),
+                expected
+                    .iter()
+                    .map(|aws_ip_prefix| aws_ip_prefix.prefix)
+                    .max_by_key(|prefix| prefix.prefix())
+            );
+        }
+    }
+
     /*-------------------------------------------------------------------------
       Get Reference Counted Strings
     -------------------------------------------------------------------------*/
 
     #[test]
     fn test_aws_ip_ranges_get_region() {
-        let region: Rc<str> = Rc::from("us-east-1");
+        let region: Arc<str> = Arc::from("us-east-1");
         let aws_ip_ranges = AwsIpRanges {
             regions: [region.clone()].into_iter().collect(),
             ..Default::default()
@@ -680,7 +1403,7 @@ pub(crate) mod tests {
 
     #[test]
     fn test_aws_ip_ranges_get_network_border_group() {
-        let network_border_group: Rc<str> = Rc::from("us-east-1");
+        let network_border_group: Arc<str> = Arc::from("us-east-1");
         let aws_ip_ranges = AwsIpRanges {
             network_border_groups: [network_border_group.clone()].into_iter().collect(),
             ..Default::default()
@@ -693,7 +1416,7 @@ pub(crate) mod tests {
 
     #[test]
     fn test_aws_ip_ranges_get_service() {
-        let service: Rc<str> = Rc::from("EC2");
+        let service: Arc<str> = Arc::from("EC2");
         let aws_ip_ranges = AwsIpRanges {
             services: [service.clone()].into_iter().collect(),
             ..Default::default()
@@ -701,6 +1424,382 @@ pub(crate) mod tests {
         assert_eq!(aws_ip_ranges.get_service("EC2").unwrap(), service);
     }
 
+    /*-------------------------------------------------------------------------
+      Aggregate
+    -------------------------------------------------------------------------*/
+
+    #[test]
+    fn test_aggregate() {
+        let aws_ip_ranges = test_aws_ip_ranges();
+
+        let expected: Vec<IpNetwork> = vec![
+            "10.0.0.0/8".parse().unwrap(),
+            "2001:db8::/32".parse().unwrap(),
+        ];
+        assert_eq!(aws_ip_ranges.aggregate(), expected);
+    }
+
+    #[test]
+    fn test_aggregate_by_region() {
+        let aws_ip_ranges = test_aws_ip_ranges();
+
+        let aggregated_prefixes = aws_ip_ranges.aggregate_by_region();
+        assert_eq!(aggregated_prefixes.len(), 2);
+        assert_eq!(aggregated_prefixes["us-east-1"].len(), 2); // Nested /16 and /48 prefixes dropped
+        assert_eq!(aggregated_prefixes["us-west-1"].len(), 2);
+    }
+
+    #[test]
+    fn test_aggregate_by_service() {
+        let aws_ip_ranges = test_aws_ip_ranges();
+
+        let aggregated_prefixes = aws_ip_ranges.aggregate_by_service();
+        assert_eq!(aggregated_prefixes["EC2"].len(), 2);
+        assert_eq!(
+            aggregated_prefixes["S3"],
+            vec![
+                "10.1.0.0/16".parse::<IpNetwork>().unwrap(),
+                "2001:db8:1::/48".parse().unwrap()
+            ]
+        );
+    }
+
+    /*-------------------------------------------------------------------------
+      Stats
+    -------------------------------------------------------------------------*/
+
+    #[test]
+    fn test_stats() {
+        let aws_ip_ranges = test_aws_ip_ranges();
+
+        let ranges_stats = aws_ip_ranges.stats();
+        assert_eq!(ranges_stats.all.ipv4.prefix_count, 3);
+        assert_eq!(ranges_stats.all.ipv4.unique_addresses, 1 << 24);
+        assert_eq!(ranges_stats.by_region.len(), aws_ip_ranges.regions().len());
+        assert_eq!(
+            ranges_stats.by_service.len(),
+            aws_ip_ranges.services().len()
+        );
+    }
+
+    /*-------------------------------------------------------------------------
+      Set Operations
+    -------------------------------------------------------------------------*/
+
+    fn test_set_operand() -> Box<AwsIpRanges> {
+        let prefixes: BTreeSet<AwsIpPrefix> = [
+            AwsIpPrefix {
+                prefix: "10.0.0.0/16".parse().unwrap(),
+                services: [Arc::from("S3")].into_iter().collect(),
+                ..test_aws_ipv4_prefix()
+            },
+            AwsIpPrefix {
+                prefix: "10.1.128.0/17".parse().unwrap(),
+                region: Arc::from("us-west-2"),
+                network_border_group: Arc::from("us-west-2"),
+                services: [Arc::from("CLOUDFRONT")].into_iter().collect(),
+            },
+            AwsIpPrefix {
+                prefix: "192.168.0.0/24".parse().unwrap(),
+                ..test_aws_ipv4_prefix()
+            },
+        ]
+        .into_iter()
+        .collect();
+
+        Box::new(AwsIpRanges::from(prefixes))
+    }
+
+    #[test]
+    fn test_union() {
+        let aws_ip_ranges = test_aws_ip_ranges();
+        let other = test_set_operand();
+
+        let union = aws_ip_ranges.union(&other);
+        assert_eq!(union.sync_token, aws_ip_ranges.sync_token);
+        assert_eq!(union.prefixes().len(), aws_ip_ranges.prefixes().len() + 2);
+        assert_eq!(
+            union
+                .get_prefix(&"10.0.0.0/16".parse().unwrap())
+                .unwrap()
+                .services,
+            [Arc::from("EC2"), Arc::from("S3")].into_iter().collect()
+        );
+        assert!(union.regions().contains("us-west-2"));
+        assert_eq!((&*aws_ip_ranges | &*other).prefixes(), union.prefixes());
+    }
+
+    #[test]
+    fn test_intersection() {
+        let aws_ip_ranges = test_aws_ip_ranges();
+        let other = test_set_operand();
+
+        let intersection = aws_ip_ranges.intersection(&other);
+        assert_eq!(
+            intersection.prefixes().keys().collect::<Vec<_>>(),
+            vec![
+                &"10.0.0.0/16".parse::<IpNetwork>().unwrap(),
+                &"10.1.128.0/17".parse().unwrap()
+            ]
+        );
+        assert_eq!(
+            intersection
+                .get_prefix(&"10.0.0.0/16".parse().unwrap())
+                .unwrap()
+                .services,
+            [Arc::from("EC2"), Arc::from("S3")].into_iter().collect()
+        );
+        assert_eq!(
+            &*intersection
+                .get_prefix(&"10.1.128.0/17".parse().unwrap())
+                .unwrap()
+                .region,
+            "us-west-2" // Metadata from the more specific prefix
+        );
+        assert_eq!(
+            (&*aws_ip_ranges & &*other).prefixes(),
+            intersection.prefixes()
+        );
+        assert_eq!(
+            intersection.intersection(&intersection).prefixes(),
+            intersection.prefixes()
+        );
+    }
+
+    #[test]
+    fn test_difference() {
+        let aws_ip_ranges = test_aws_ip_ranges();
+        let other = test_set_operand();
+
+        let difference = aws_ip_ranges.difference(&other);
+        let ipv4_prefixes: Vec<IpNetwork> = difference
+            .prefixes()
+            .keys()
+            .filter(|prefix| prefix.is_ipv4())
+            .copied()
+            .collect();
+        assert_eq!(
+            ipv4_prefixes,
+            vec![
+                "10.1.0.0/17".parse::<IpNetwork>().unwrap(),
+                "10.2.0.0/15".parse().unwrap(),
+                "10.4.0.0/14".parse().unwrap(),
+                "10.8.0.0/13".parse().unwrap(),
+                "10.16.0.0/12".parse().unwrap(),
+                "10.32.0.0/11".parse().unwrap(),
+                "10.64.0.0/10".parse().unwrap(),
+                "10.128.0.0/9".parse().unwrap(),
+            ]
+        );
+        assert_eq!(
+            &*difference
+                .get_prefix(&"10.1.0.0/17".parse().unwrap())
+                .unwrap()
+                .region,
+            "us-west-1" // Split from 10.1.0.0/16 and 10.0.0.0/8; metadata from 10.1.0.0/16
+        );
+        assert_eq!(
+            (&*aws_ip_ranges - &*other).prefixes(),
+            difference.prefixes()
+        );
+        assert!(aws_ip_ranges
+            .difference(&aws_ip_ranges)
+            .prefixes()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_difference_nested_supernet() {
+        let prefixes: BTreeSet<AwsIpPrefix> = [
+            AwsIpPrefix {
+                prefix: "10.0.0.0/8".parse().unwrap(),
+                services: [Arc::from("AMAZON")].into_iter().collect(),
+                ..test_aws_ipv4_prefix()
+            },
+            AwsIpPrefix {
+                prefix: "10.1.0.0/16".parse().unwrap(),
+                services: [Arc::from("EC2")].into_iter().collect(),
+                ..test_aws_ipv4_prefix()
+            },
+        ]
+        .into_iter()
+        .collect();
+        let aws_ip_ranges = AwsIpRanges::from(prefixes);
+        let other = test_set_operand();
+
+        // 10.0.0.0/8 and 10.1.0.0/16 both yield 10.1.0.0/17; it keeps only the EC2 service
+        let difference = aws_ip_ranges.difference(&other);
+        assert_eq!(
+            difference
+                .get_prefix(&"10.1.0.0/17".parse().unwrap())
+                .unwrap()
+                .services,
+            [Arc::from("EC2")].into_iter().collect()
+        );
+        assert_eq!(
+            difference
+                .get_prefix(&"10.2.0.0/15".parse().unwrap())
+                .unwrap()
+                .services,
+            [Arc::from("AMAZON")].into_iter().collect()
+        );
+    }
+
+    /*-------------------------------------------------------------------------
+      Diff
+    -------------------------------------------------------------------------*/
+
+    #[test]
+    fn test_diff() {
+        let old = test_aws_ip_ranges();
+
+        let mut new_prefixes: BTreeMap<IpNetwork, AwsIpPrefix> = old.prefixes.clone();
+        let removed: IpNetwork = "10.0.0.0/16".parse().unwrap();
+        let changed: IpNetwork = "10.1.0.0/16".parse().unwrap();
+        let added: IpNetwork = "10.2.0.0/16".parse().unwrap();
+        new_prefixes.remove(&removed);
+        new_prefixes.get_mut(&changed).unwrap().services = [Arc::from("S3")].into();
+        new_prefixes.insert(
+            added,
+            AwsIpPrefix {
+                prefix: added,
+                ..test_aws_ipv4_prefix()
+            },
+        );
+        let mut new = AwsIpRanges::from(new_prefixes);
+        new.sync_token = "1".to_string();
+
+        let ranges_diff = old.diff(&new);
+        assert_eq!(ranges_diff.old_sync_token, old.sync_token);
+        assert_eq!(ranges_diff.new_sync_token, "1");
+        assert_eq!(ranges_diff.added.keys().collect::<Vec<_>>(), vec![&added]);
+        assert_eq!(
+            ranges_diff.removed.keys().collect::<Vec<_>>(),
+            vec![&removed]
+        );
+        assert_eq!(
+            ranges_diff.changed.keys().collect::<Vec<_>>(),
+            vec![&changed]
+        );
+        assert_eq!(
+            ranges_diff.changed[&changed].services_removed(),
+            [Arc::from("EC2")].into()
+        );
+
+        assert!(old.diff(&old).is_empty());
+    }
+
+    /*-------------------------------------------------------------------------
+      AWS IP Ranges from JSON
+    -------------------------------------------------------------------------*/
+
+    #[test]
+    fn test_to_json_round_trip() {
+        let mut aws_ip_ranges = test_aws_ip_ranges();
+        aws_ip_ranges.create_date = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+
+        let json = aws_ip_ranges.to_json();
+        let parsed = AwsIpRanges::from_json(&json, "test").unwrap();
+
+        assert_eq!(parsed.sync_token(), aws_ip_ranges.sync_token());
+        assert_eq!(parsed.create_date(), aws_ip_ranges.create_date());
+        assert_eq!(parsed.prefixes(), aws_ip_ranges.prefixes());
+    }
+
+    #[test]
+    fn test_write_json() {
+        let aws_ip_ranges = test_aws_ip_ranges();
+        let path = std::env::temp_dir().join("awsipranges_test_write_json.json");
+
+        aws_ip_ranges.write_json(&path).unwrap();
+        let read = AwsIpRanges::from_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(read.sync_token(), aws_ip_ranges.sync_token());
+        assert_eq!(read.prefixes(), aws_ip_ranges.prefixes());
+        assert!(aws_ip_ranges.provenance().is_none());
+        assert_eq!(read.provenance().unwrap().source, DataSource::File);
+        assert_eq!(
+            read.filter_builder().ipv4().filter().provenance(),
+            read.provenance()
+        );
+
+        assert!(matches!(
+            aws_ip_ranges.write_json(path.join("not-a-directory.json")),
+            Err(Error::Io { .. })
+        ));
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let aws_ip_ranges = test_aws_ip_ranges();
+
+        let value = serde_json::to_value(&aws_ip_ranges).unwrap();
+        assert_eq!(
+            value["create_date"],
+            aws_ip_ranges
+                .create_date
+                .to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)
+        );
+        assert_eq!(value["prefixes"][0]["prefix"], "10.0.0.0/8");
+
+        let deserialized: AwsIpRanges = serde_json::from_value(value).unwrap();
+        assert_eq!(deserialized.sync_token(), aws_ip_ranges.sync_token());
+        assert_eq!(deserialized.create_date(), aws_ip_ranges.create_date());
+        assert_eq!(deserialized.prefixes(), aws_ip_ranges.prefixes());
+        assert_eq!(deserialized.services(), aws_ip_ranges.services());
+        assert!(deserialized
+            .get_longest_match_prefix(&"10.0.0.1".parse().unwrap())
+            .is_some());
+    }
+
+    #[test]
+    fn test_from_json() {
+        let json = r#"{
+          "syncToken": "1640995200",
+          "createDate": "2022-01-01-00-00-00",
+          "prefixes": [
+            {"ip_prefix": "10.0.0.0/8", "region": "us-east-1", "network_border_group": "us-east-1", "service": "AMAZON"},
+            {"ip_prefix": "10.0.0.0/8", "region": "us-east-1", "network_border_group": "us-east-1", "service": "EC2"}
+          ],
+          "ipv6_prefixes": [
+            {"ipv6_prefix": "2001:db8::/32", "region": "us-east-1", "network_border_group": "us-east-1", "service": "AMAZON"}
+          ]
+        }"#;
+
+        let aws_ip_ranges = AwsIpRanges::from_json(json, "test").unwrap();
+
+        assert_eq!(aws_ip_ranges.prefixes().len(), 2);
+        assert_eq!(aws_ip_ranges.services().len(), 2);
+        assert_eq!(
+            aws_ip_ranges
+                .get_prefix(&"10.0.0.0/8".parse().unwrap())
+                .unwrap()
+                .services
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_from_json_invalid_prefix_region() {
+        let json = r#"{
+          "syncToken": "1640995200",
+          "createDate": "2022-01-01-00-00-00",
+          "prefixes": [
+            {"ip_prefix": "10.0.0.0/8", "region": "us-east-1", "network_border_group": "us-east-1", "service": "AMAZON"},
+            {"ip_prefix": "10.0.0.0/8", "region": "us-west-1", "network_border_group": "us-east-1", "service": "EC2"}
+          ],
+          "ipv6_prefixes": []
+        }"#;
+
+        let result = AwsIpRanges::from_json(json, "test");
+        assert!(matches!(
+            result,
+            Err(Error::Validation { origin, .. }) if origin == "test"
+        ));
+    }
+
     /*-------------------------------------------------------------------------
       Search
     -------------------------------------------------------------------------*/
@@ -738,6 +1837,57 @@ pub(crate) mod tests {
             .contains(&search_networks[5])); // No prefix match
     }
 
+    #[test]
+    fn test_aws_ip_ranges_search_with_mode() {
+        let aws_ip_ranges = test_aws_ip_ranges();
+
+        let search_networks: [IpNetwork; 2] = [
+            "10.0.0.0/15".parse().unwrap(),
+            "2001:db8::/31".parse().unwrap(),
+        ];
+
+        let supernet_results =
+            aws_ip_ranges.search_with_mode(&search_networks, SearchMode::Supernets);
+        assert_eq!(supernet_results.aws_ip_ranges.prefixes.len(), 1); // 10.0.0.0/8
+        assert!(supernet_results
+            .prefixes_not_found
+            .contains(&search_networks[1]));
+
+        let subnet_results = aws_ip_ranges.search_with_mode(&search_networks, SearchMode::Subnets);
+        assert_eq!(subnet_results.aws_ip_ranges.prefixes.len(), 5);
+        assert!(subnet_results.prefixes_not_found.is_empty());
+
+        let overlapping_results =
+            aws_ip_ranges.search_with_mode(&search_networks, SearchMode::Overlapping);
+        assert_eq!(overlapping_results.aws_ip_ranges.prefixes.len(), 6);
+        assert_eq!(
+            overlapping_results.prefix_matches[&search_networks[0]].len(),
+            3
+        );
+    }
+
+    /*-------------------------------------------------------------------------
+      Attribute
+    -------------------------------------------------------------------------*/
+
+    #[test]
+    fn test_attribute() {
+        let aws_ip_ranges = test_aws_ip_ranges();
+
+        let attribution = aws_ip_ranges
+            .attribute(&"10.0.1.1".parse().unwrap(), AmazonServicePolicy::Service)
+            .unwrap();
+        assert_eq!(attribution.prefix.prefix, "10.0.0.0/16".parse().unwrap());
+        assert_eq!(attribution.overridden.len(), 1);
+
+        assert!(aws_ip_ranges
+            .attribute(
+                &"192.168.0.1".parse().unwrap(),
+                AmazonServicePolicy::Service
+            )
+            .is_none());
+    }
+
     /*-------------------------------------------------------------------------
       Filter
     -------------------------------------------------------------------------*/
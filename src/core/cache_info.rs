@@ -0,0 +1,72 @@
+use chrono::{DateTime, Utc};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/*-------------------------------------------------------------------------------------------------
+  Cache Info
+-------------------------------------------------------------------------------------------------*/
+
+/// Details about the AWS IP Ranges cache file, created with [Client::cache_info].
+///
+/// [Client::cache_info]: crate::Client::cache_info
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CacheInfo {
+    /// Path of the cache file.
+    pub path: PathBuf,
+
+    /// Size of the cache file in bytes.
+    pub size: u64,
+
+    /// Elapsed time since the cache file was last modified.
+    pub age: Duration,
+
+    /// Amount of time (in seconds) the cache file is considered fresh.
+    pub cache_time: u64,
+
+    /// Publication time of the cached AWS IP Ranges in Unix epoch time format; `None` when the
+    /// cache file cannot be parsed.
+    pub sync_token: Option<String>,
+
+    /// Publication time of the cached AWS IP Ranges in UTC `DateTime` format; `None` when the
+    /// cache file cannot be parsed.
+    pub create_date: Option<DateTime<Utc>>,
+}
+
+/*--------------------------------------------------------------------------------------
+  Cache Info Implementation
+--------------------------------------------------------------------------------------*/
+
+impl CacheInfo {
+    /// Whether the cache file is fresh; [Client::get_ranges] uses a fresh cache file without
+    /// requesting the AWS IP Ranges from the URL.
+    ///
+    /// [Client::get_ranges]: crate::Client::get_ranges
+    pub fn is_fresh(&self) -> bool {
+        self.age.as_secs() <= self.cache_time
+    }
+}
+
+/*-------------------------------------------------------------------------------------------------
+  Unit Tests
+-------------------------------------------------------------------------------------------------*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_fresh() {
+        let mut cache_info = CacheInfo {
+            path: PathBuf::from("ip-ranges.json"),
+            size: 0,
+            age: Duration::from_secs(60),
+            cache_time: 60,
+            sync_token: None,
+            create_date: None,
+        };
+        assert!(cache_info.is_fresh());
+
+        cache_info.age = Duration::from_secs(61);
+        assert!(!cache_info.is_fresh());
+    }
+}
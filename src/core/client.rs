@@ -1,5 +1,8 @@
 use crate::core::aws_ip_ranges::AwsIpRanges;
+use crate::core::cache_info::CacheInfo;
 use crate::core::errors::{Error, Result};
+use crate::core::provenance::{DataSource, Provenance};
+use chrono::Utc;
 use log::{info, warn};
 use std::env;
 use std::fs;
@@ -13,6 +16,8 @@ use std::{thread, time};
 /// _**Simple library interface**_ quickly retrieves and parses the AWS IP Ranges using the default
 /// client configuration. Returns a boxed [AwsIpRanges] object that allows you to quickly query
 /// ([search](AwsIpRanges::search), [filter](AwsIpRanges::filter_builder), etc.) the AWS IP Ranges.
+/// Returns an [Error::InvalidEnvVar] when a configuration environment variable (see
+/// [ClientBuilder::new]) is set to an invalid value.
 ///
 /// ```rust
 /// # fn main() -> awsipranges::Result<()> {
@@ -35,7 +40,7 @@ use std::{thread, time};
 /// # }
 /// ```
 pub fn get_ranges() -> Result<Box<AwsIpRanges>> {
-    Client::new().get_ranges()
+    ClientBuilder::try_new()?.build().get_ranges()
 }
 
 /*-------------------------------------------------------------------------------------------------
@@ -55,6 +60,9 @@ pub fn get_ranges() -> Result<Box<AwsIpRanges>> {
 ///     .retry_initial_delay(200) // 200 ms
 ///     .retry_backoff_factor(2)
 ///     .retry_timeout(5000) // 5 seconds
+///     .fetch_policy(awsipranges::FetchPolicy::PreferCache)
+///     .max_data_age(7 * 24 * 60 * 60) // 7 days
+///     .data_age_policy(awsipranges::DataAgePolicy::Error)
 ///     .build();
 /// ```
 ///
@@ -72,6 +80,10 @@ pub struct ClientBuilder {
     retry_initial_delay: u64,
     retry_backoff_factor: u64,
     retry_timeout: u64,
+    fetch_policy: FetchPolicy,
+    max_data_age: Option<u64>,
+    data_age_policy: DataAgePolicy,
+    expected_sync_token: Option<String>,
 }
 
 /*--------------------------------------------------------------------------------------
@@ -91,6 +103,10 @@ impl Default for ClientBuilder {
     /// assert_eq!(client.retry_initial_delay(), 200);
     /// assert_eq!(client.retry_backoff_factor(), 2);
     /// assert_eq!(client.retry_timeout(), 5000);
+    /// assert_eq!(client.fetch_policy(), awsipranges::FetchPolicy::PreferCache);
+    /// assert_eq!(client.max_data_age(), None);
+    /// assert_eq!(client.data_age_policy(), awsipranges::DataAgePolicy::Error);
+    /// assert_eq!(client.expected_sync_token(), None);
     /// ```
     fn default() -> Self {
         Self {
@@ -104,6 +120,10 @@ impl Default for ClientBuilder {
             retry_initial_delay: 200, // 200 ms
             retry_backoff_factor: 2,
             retry_timeout: 5000, // 5 seconds
+            fetch_policy: FetchPolicy::PreferCache,
+            max_data_age: None,
+            data_age_policy: DataAgePolicy::Error,
+            expected_sync_token: None,
         }
     }
 }
@@ -115,24 +135,63 @@ impl ClientBuilder {
     //!
     #![doc = include_str!("../../docs/lib_configuration_table.md")]
 
+    /// Invalid environment variable values are logged and ignored; use
+    /// [ClientBuilder::try_new] to reject them.
     pub fn new() -> Self {
+        Self::from_env(false).expect("Invalid environment variable values are ignored")
+    }
+
+    /// Create a new [ClientBuilder] like [ClientBuilder::new]; returns an
+    /// [Error::InvalidEnvVar] when an environment variable is set to an
+    /// invalid value, instead of logging a warning and using the default
+    /// value.
+    ///
+    /// ```
+    /// let client = awsipranges::ClientBuilder::try_new()?.build();
+    /// # Ok::<(), awsipranges::Error>(())
+    /// ```
+    pub fn try_new() -> Result<Self> {
+        Self::from_env(true)
+    }
+
+    /// Read the configuration values from environment variables; invalid
+    /// values are errors when `strict` and are ignored otherwise.
+    fn from_env(strict: bool) -> Result<Self> {
         let default = ClientBuilder::default();
 
-        Self {
-            url: get_env_var("AWSIPRANGES_URL", default.url),
-            cache_file: get_env_var("AWSIPRANGES_CACHE_FILE", default.cache_file),
-            cache_time: get_env_var("AWSIPRANGES_CACHE_TIME", default.cache_time),
-            retry_count: get_env_var("AWSIPRANGES_RETRY_COUNT", default.retry_count),
+        Ok(Self {
+            url: get_env_var("AWSIPRANGES_URL", default.url, strict)?,
+            cache_file: get_env_var("AWSIPRANGES_CACHE_FILE", default.cache_file, strict)?,
+            cache_time: get_env_var("AWSIPRANGES_CACHE_TIME", default.cache_time, strict)?,
+            retry_count: get_env_var("AWSIPRANGES_RETRY_COUNT", default.retry_count, strict)?,
             retry_initial_delay: get_env_var(
                 "AWSIPRANGES_RETRY_INITIAL_DELAY",
                 default.retry_initial_delay,
-            ),
+                strict,
+            )?,
             retry_backoff_factor: get_env_var(
                 "AWSIPRANGES_RETRY_BACKOFF_FACTOR",
                 default.retry_backoff_factor,
-            ),
-            retry_timeout: get_env_var("AWSIPRANGES_RETRY_TIMEOUT", default.retry_timeout),
-        }
+                strict,
+            )?,
+            retry_timeout: get_env_var("AWSIPRANGES_RETRY_TIMEOUT", default.retry_timeout, strict)?,
+            fetch_policy: get_env_var("AWSIPRANGES_FETCH_POLICY", default.fetch_policy, strict)?,
+            max_data_age: get_optional_env_var(
+                "AWSIPRANGES_MAX_DATA_AGE",
+                default.max_data_age,
+                strict,
+            )?,
+            data_age_policy: get_env_var(
+                "AWSIPRANGES_DATA_AGE_POLICY",
+                default.data_age_policy,
+                strict,
+            )?,
+            expected_sync_token: get_optional_env_var(
+                "AWSIPRANGES_EXPECTED_SYNC_TOKEN",
+                default.expected_sync_token,
+                strict,
+            )?,
+        })
     }
 
     /*-------------------------------------------------------------------------
@@ -203,6 +262,39 @@ impl ClientBuilder {
         self
     }
 
+    /// Set the policy that decides whether the AWS IP Ranges are retrieved from
+    /// the cache file or the URL; defaults to [FetchPolicy::PreferCache].
+    pub fn fetch_policy(&mut self, fetch_policy: FetchPolicy) -> &mut Self {
+        self.fetch_policy = fetch_policy;
+        self
+    }
+
+    /// Set the maximum age (in seconds) of the AWS IP Ranges data, measured
+    /// from its `createDate` (publication time) rather than the cache file's
+    /// modified timestamp; unlimited by default. When the AWS IP Ranges
+    /// returned by `get_ranges()` (including a stale-cache fallback) are
+    /// older, the [DataAgePolicy] decides whether `get_ranges()` returns an
+    /// [Error::StaleData] or logs a warning.
+    pub fn max_data_age(&mut self, max_data_age: u64) -> &mut Self {
+        self.max_data_age = Some(max_data_age);
+        self
+    }
+
+    /// Set what happens when the AWS IP Ranges are older than the
+    /// `max_data_age`; defaults to [DataAgePolicy::Error].
+    pub fn data_age_policy(&mut self, data_age_policy: DataAgePolicy) -> &mut Self {
+        self.data_age_policy = data_age_policy;
+        self
+    }
+
+    /// Pin the `syncToken` the AWS IP Ranges must have for reproducible runs;
+    /// `get_ranges()` returns an [Error::SyncTokenMismatch] for any other
+    /// version of the AWS IP Ranges. Not pinned by default.
+    pub fn expected_sync_token(&mut self, expected_sync_token: &str) -> &mut Self {
+        self.expected_sync_token = Some(expected_sync_token.to_string());
+        self
+    }
+
     /*-------------------------------------------------------------------------
       Build Method
     -------------------------------------------------------------------------*/
@@ -217,6 +309,10 @@ impl ClientBuilder {
             retry_initial_delay: self.retry_initial_delay,
             retry_backoff_factor: self.retry_backoff_factor,
             retry_timeout: self.retry_timeout,
+            fetch_policy: self.fetch_policy,
+            max_data_age: self.max_data_age,
+            data_age_policy: self.data_age_policy,
+            expected_sync_token: self.expected_sync_token.clone(),
         }
     }
 }
@@ -248,6 +344,10 @@ pub struct Client {
     retry_initial_delay: u64,
     retry_backoff_factor: u64,
     retry_timeout: u64,
+    fetch_policy: FetchPolicy,
+    max_data_age: Option<u64>,
+    data_age_policy: DataAgePolicy,
+    expected_sync_token: Option<String>,
 }
 
 /*--------------------------------------------------------------------------------------
@@ -267,6 +367,10 @@ impl Default for Client {
     /// assert_eq!(client.retry_initial_delay(), 200);
     /// assert_eq!(client.retry_backoff_factor(), 2);
     /// assert_eq!(client.retry_timeout(), 5000);
+    /// assert_eq!(client.fetch_policy(), awsipranges::FetchPolicy::PreferCache);
+    /// assert_eq!(client.max_data_age(), None);
+    /// assert_eq!(client.data_age_policy(), awsipranges::DataAgePolicy::Error);
+    /// assert_eq!(client.expected_sync_token(), None);
     /// ```
     fn default() -> Self {
         ClientBuilder::default().build()
@@ -365,25 +469,237 @@ impl Client {
         self.retry_timeout
     }
 
+    /// Get the policy that decides whether the AWS IP Ranges are retrieved
+    /// from the cache file or the URL. Defaults to [FetchPolicy::PreferCache].
+    ///
+    /// ```
+    /// let client = awsipranges::Client::default();
+    /// assert_eq!(client.fetch_policy(), awsipranges::FetchPolicy::PreferCache);
+    /// ```
+    pub fn fetch_policy(&self) -> FetchPolicy {
+        self.fetch_policy
+    }
+
+    /// Get the maximum age (in seconds) of the AWS IP Ranges data, measured
+    /// from its `createDate`. Defaults to `None` (unlimited).
+    ///
+    /// ```
+    /// let client = awsipranges::Client::default();
+    /// assert_eq!(client.max_data_age(), None);
+    /// ```
+    pub fn max_data_age(&self) -> Option<u64> {
+        self.max_data_age
+    }
+
+    /// Get what happens when the AWS IP Ranges are older than the
+    /// `max_data_age`. Defaults to [DataAgePolicy::Error].
+    ///
+    /// ```
+    /// let client = awsipranges::Client::default();
+    /// assert_eq!(client.data_age_policy(), awsipranges::DataAgePolicy::Error);
+    /// ```
+    pub fn data_age_policy(&self) -> DataAgePolicy {
+        self.data_age_policy
+    }
+
+    /// Get the `syncToken` the AWS IP Ranges must have. Defaults to `None`
+    /// (not pinned).
+    ///
+    /// ```
+    /// let client = awsipranges::Client::default();
+    /// assert_eq!(client.expected_sync_token(), None);
+    /// ```
+    pub fn expected_sync_token(&self) -> Option<&str> {
+        self.expected_sync_token.as_deref()
+    }
+
     /*-------------------------------------------------------------------------
       Get Ranges
     -------------------------------------------------------------------------*/
 
-    /// Retrieves, parses, and returns a boxed [AwsIpRanges] object. Uses
-    /// locally cached JSON, when available and fresh. Requests the AWS IP
-    /// Ranges JSON from the URL when the local cache is stale or
-    /// unavailable.
+    /// Retrieves, parses, and returns a boxed [AwsIpRanges] object from the
+    /// cache file or the URL, as directed by the client's [FetchPolicy]. By
+    /// default, uses locally cached JSON, when available and fresh, and
+    /// requests the AWS IP Ranges JSON from the URL when the local cache is
+    /// stale or unavailable.
+    ///
+    /// The returned AWS IP Ranges record where they came from; check
+    /// [AwsIpRanges::provenance] to detect stale data. Returns an
+    /// [Error::SyncTokenMismatch] when the `expected_sync_token` is pinned and
+    /// an [Error::StaleData] when the AWS IP Ranges are older than the
+    /// `max_data_age` (unless the [DataAgePolicy] is `Warn`).
     pub fn get_ranges(&self) -> Result<Box<AwsIpRanges>> {
-        let json = self.get_json()?;
-        AwsIpRanges::from_json(&json)
+        let (json, provenance) = self.get_json()?;
+        let aws_ip_ranges = with_provenance(&json, provenance)?;
+        self.check_ranges(&aws_ip_ranges)?;
+
+        Ok(aws_ip_ranges)
+    }
+
+    /// Applies the checks [Client::get_ranges] makes to AWS IP Ranges
+    /// retrieved another way (for example, with [AwsIpRanges::from_file] or
+    /// [Client::download_ranges]): returns an [Error::SyncTokenMismatch] when
+    /// the `expected_sync_token` is pinned and an [Error::StaleData] when the
+    /// AWS IP Ranges are older than the `max_data_age` (unless the
+    /// [DataAgePolicy] is `Warn`).
+    pub fn check_ranges(&self, aws_ip_ranges: &AwsIpRanges) -> Result<()> {
+        self.check_sync_token(aws_ip_ranges)?;
+        match (self.check_data_age(aws_ip_ranges), self.data_age_policy) {
+            (Err(error), DataAgePolicy::Error) => Err(error),
+            (Err(error), DataAgePolicy::Warn) => {
+                warn!("{error}");
+                Ok(())
+            }
+            (Ok(()), _) => Ok(()),
+        }
+    }
+
+    /// Check the AWS IP Ranges were published (`createDate`) within the
+    /// `max_data_age`; returns an [Error::StaleData] when they are older,
+    /// regardless of the [DataAgePolicy].
+    pub fn check_data_age(&self, aws_ip_ranges: &AwsIpRanges) -> Result<()> {
+        let Some(max_data_age) = self.max_data_age else {
+            return Ok(());
+        };
+
+        let data_age = Utc::now().signed_duration_since(aws_ip_ranges.create_date());
+        if data_age.num_seconds() > i64::try_from(max_data_age).unwrap_or(i64::MAX) {
+            return Err(Error::StaleData {
+                origin: data_origin(aws_ip_ranges),
+                create_date: *aws_ip_ranges.create_date(),
+                max_data_age,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Check the AWS IP Ranges have the `expected_sync_token`, when pinned;
+    /// returns an [Error::SyncTokenMismatch] when they do not.
+    pub fn check_sync_token(&self, aws_ip_ranges: &AwsIpRanges) -> Result<()> {
+        match &self.expected_sync_token {
+            Some(expected) if expected != aws_ip_ranges.sync_token() => {
+                Err(Error::SyncTokenMismatch {
+                    origin: data_origin(aws_ip_ranges),
+                    expected: expected.clone(),
+                    actual: aws_ip_ranges.sync_token().clone(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Reads, parses, and returns a boxed [AwsIpRanges] object from the
+    /// cache file, regardless of the cache's age. Does not request the AWS IP
+    /// Ranges JSON from the URL.
+    pub fn get_cached_ranges(&self) -> Result<Box<AwsIpRanges>> {
+        let json = self.get_json_from_file()?;
+        with_provenance(&json, self.cache_provenance(0, None)?)
+    }
+
+    /// Requests, parses, and returns a boxed [AwsIpRanges] object from the
+    /// URL, regardless of the cache's age. Does not read or update the cache
+    /// file.
+    pub fn download_ranges(&self) -> Result<Box<AwsIpRanges>> {
+        let (json, provenance) = self.get_json_from_url()?;
+        with_provenance(&json, provenance)
+    }
+
+    /*-------------------------------------------------------------------------
+      Cache Management
+    -------------------------------------------------------------------------*/
+
+    /// Get details about the cache file: its path, size, age, freshness, and
+    /// the `syncToken` and `createDate` of the cached AWS IP Ranges. Returns
+    /// an [Error::Io] when the cache file does not exist.
+    pub fn cache_info(&self) -> Result<CacheInfo> {
+        let metadata = fs::metadata(&self.cache_file).map_err(|error| Error::Io {
+            path: self.cache_file.clone(),
+            source: error,
+        })?;
+        let cached_ranges = self.get_cached_ranges().ok();
+
+        Ok(CacheInfo {
+            path: self.cache_file.clone(),
+            size: metadata.len(),
+            age: self.cache_age()?,
+            cache_time: self.cache_time,
+            sync_token: cached_ranges
+                .as_ref()
+                .map(|aws_ip_ranges| aws_ip_ranges.sync_token().clone()),
+            create_date: cached_ranges
+                .as_ref()
+                .map(|aws_ip_ranges| *aws_ip_ranges.create_date()),
+        })
+    }
+
+    /// Requests the AWS IP Ranges JSON from the URL, regardless of the
+    /// cache's age, and updates the cache file. The downloaded JSON is parsed
+    /// and validated before it replaces the cache file.
+    pub fn refresh_cache(&self) -> Result<Box<AwsIpRanges>> {
+        let (json, provenance) = self.get_json_from_url()?;
+        let aws_ip_ranges = with_provenance(&json, provenance)?;
+        self.cache_json_to_file(&json)?;
+        Ok(aws_ip_ranges)
+    }
+
+    /// Deletes the cache file. Returns `false` when there was no cache file to
+    /// delete.
+    pub fn clear_cache(&self) -> Result<bool> {
+        match fs::remove_file(&self.cache_file) {
+            Ok(()) => {
+                info!("Deleted cache file: {:?}", &self.cache_file);
+                Ok(true)
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(error) => Err(Error::Io {
+                path: self.cache_file.clone(),
+                source: error,
+            }),
+        }
+    }
+
+    /// Reads, parses, and validates the cache file, regardless of the
+    /// cache's age; returns the first problem found. The cached AWS IP Ranges
+    /// must contain at least one prefix.
+    pub fn verify_cache(&self) -> Result<Box<AwsIpRanges>> {
+        let aws_ip_ranges = self.get_cached_ranges()?;
+        if aws_ip_ranges.prefixes().is_empty() {
+            return Err(Error::Validation {
+                origin: self.cache_origin(),
+                message: "no prefixes".to_string(),
+            });
+        }
+
+        Ok(aws_ip_ranges)
     }
 
     /*-------------------------------------------------------------------------
       Private Methods
     -------------------------------------------------------------------------*/
 
-    /// Get the AWS IP Ranges JSON from the cache file or URL.
-    fn get_json(&self) -> Result<String> {
+    /// Get the AWS IP Ranges JSON, and where it came from, from the cache
+    /// file or URL.
+    fn get_json(&self) -> Result<(String, Provenance)> {
+        info!("Fetch policy: {}", self.fetch_policy);
+        match self.fetch_policy {
+            FetchPolicy::PreferCache => self.get_json_preferring_cache(),
+            FetchPolicy::CacheOnly => {
+                let json = self.get_json_from_file()?;
+                Ok((json, self.cache_provenance(0, None)?))
+            }
+            FetchPolicy::NetworkOnly => self.get_json_from_url(),
+            FetchPolicy::AlwaysRefresh => {
+                let (json, provenance) = self.get_json_from_url()?;
+                let _ = self.cache_json_to_file(&json);
+                Ok((json, provenance))
+            }
+        }
+    }
+
+    /// Get the AWS IP Ranges JSON from the cache file, when fresh, or the
+    /// URL; falling back to a stale cache file when the URL is unavailable.
+    fn get_json_preferring_cache(&self) -> Result<(String, Provenance)> {
         info!("Cache time {} seconds", self.cache_time);
         info!("Cache file path: {:?}", &self.cache_file);
 
@@ -396,66 +712,150 @@ impl Client {
         };
 
         // Check if cache file is fresh
-        let cache_is_fresh = cache_exists
-            && fs::metadata(&self.cache_file)?
-                .modified()?
-                .elapsed()?
-                .as_secs()
-                <= self.cache_time;
+        let cache_is_fresh = cache_exists && self.cache_age()?.as_secs() <= self.cache_time;
         if cache_is_fresh {
             info!("Cache file is fresh");
         } else {
             info!("Cache file is stale; refresh cache");
         };
 
-        // Fresh cached JSON
+        // Fresh cached JSON; a fresh cache file holding AWS IP Ranges older
+        // than the maximum data age is refreshed like a stale cache file
+        let mut cache_is_over_age = false;
         if cache_is_fresh {
-            let fresh_cached_json = self.get_json_from_file();
-            if fresh_cached_json.is_ok() {
-                return fresh_cached_json;
+            if let Ok(fresh_cached_json) = self.get_json_from_file() {
+                cache_is_over_age = self.is_over_age(&fresh_cached_json);
+                if !cache_is_over_age {
+                    return Ok((fresh_cached_json, self.cache_provenance(0, None)?));
+                }
+                info!("Cached AWS IP Ranges are older than the maximum data age; refresh cache");
             }
         };
 
         // Fresh URL JSON
-        let fresh_url_json = self.get_json_from_url();
-        if let Ok(fresh_url_json) = fresh_url_json {
-            let _ = self.cache_json_to_file(&fresh_url_json);
-            return Ok(fresh_url_json);
+        let url_fetch = self.fetch_json_from_url();
+        let url_provenance = self.url_provenance(&url_fetch);
+        let url_error = match url_fetch.json {
+            Ok(fresh_url_json) => {
+                let _ = self.cache_json_to_file(&fresh_url_json);
+                return Ok((fresh_url_json, url_provenance));
+            }
+            Err(url_error) => url_error,
         };
-        let url_result = fresh_url_json;
 
-        // Stale cached JSON
-        if cache_exists && !cache_is_fresh {
-            let stale_cache_json = self.get_json_from_file();
-            if stale_cache_json.is_ok() {
-                return stale_cache_json;
+        // Stale (or over-age) cached JSON
+        if cache_exists && (!cache_is_fresh || cache_is_over_age) {
+            if let Ok(stale_cache_json) = self.get_json_from_file() {
+                warn!(
+                    "Using stale cached AWS IP Ranges from {:?}: {}",
+                    &self.cache_file, url_error
+                );
+                let provenance =
+                    self.cache_provenance(url_fetch.attempts, url_fetch.http_status)?;
+                return Ok((stale_cache_json, provenance));
             }
         };
 
-        // Return result (Err) retrieving AWS IP Ranges JSON from URL
-        url_result
+        // Return the error retrieving AWS IP Ranges JSON from URL
+        Err(url_error)
+    }
+
+    /// Whether the AWS IP Ranges JSON was published (`createDate`) longer ago
+    /// than the `max_data_age`; `false` when no maximum data age is set or the
+    /// JSON cannot be parsed.
+    fn is_over_age(&self, json: &str) -> bool {
+        self.max_data_age.is_some()
+            && AwsIpRanges::from_json(json, &self.cache_origin())
+                .is_ok_and(|aws_ip_ranges| self.check_data_age(&aws_ip_ranges).is_err())
     }
 
-    /// Get the AWS IP Ranges JSON from the URL.
-    fn get_json_from_url(&self) -> Result<String> {
+    /// Get the elapsed time since the cache file was last modified.
+    fn cache_age(&self) -> Result<time::Duration> {
+        let modified = fs::metadata(&self.cache_file)
+            .and_then(|metadata| metadata.modified())
+            .map_err(|error| Error::Io {
+                path: self.cache_file.clone(),
+                source: error,
+            })?;
+
+        // A modified timestamp in the future is treated as brand new
+        Ok(modified.elapsed().unwrap_or_default())
+    }
+
+    /// Describe the cache file as the origin of the AWS IP Ranges JSON.
+    fn cache_origin(&self) -> String {
+        self.cache_file.display().to_string()
+    }
+
+    /// Describe AWS IP Ranges JSON read from the cache file; fresh or stale
+    /// depending on the cache's age.
+    fn cache_provenance(&self, attempts: u32, http_status: Option<u16>) -> Result<Provenance> {
+        let cache_age = self.cache_age()?;
+        let source = match cache_age.as_secs() <= self.cache_time {
+            true => DataSource::FreshCache,
+            false => DataSource::StaleCache,
+        };
+
+        Ok(Provenance {
+            source,
+            location: self.cache_origin(),
+            fetched_at: Utc::now(),
+            cache_age: Some(cache_age),
+            http_status,
+            attempts,
+        })
+    }
+
+    /// Describe AWS IP Ranges JSON requested from the URL.
+    fn url_provenance(&self, url_fetch: &UrlFetch) -> Provenance {
+        Provenance {
+            source: DataSource::Network,
+            location: self.url.clone(),
+            fetched_at: Utc::now(),
+            cache_age: None,
+            http_status: url_fetch.http_status,
+            attempts: url_fetch.attempts,
+        }
+    }
+
+    /// Get the AWS IP Ranges JSON, and where it came from, from the URL.
+    fn get_json_from_url(&self) -> Result<(String, Provenance)> {
+        let url_fetch = self.fetch_json_from_url();
+        let provenance = self.url_provenance(&url_fetch);
+        url_fetch.json.map(|json| (json, provenance))
+    }
+
+    /// Request the AWS IP Ranges JSON from the URL, retrying failed attempts.
+    fn fetch_json_from_url(&self) -> UrlFetch {
         let start_time = time::Instant::now();
         let max_elapsed_time = time::Duration::from_millis(self.retry_timeout);
 
         let mut attempt: u32 = 0;
+        let mut http_status: Option<u16> = None;
         loop {
             info!(
                 "Get AWS IP Ranges from URL; Attempt {}: GET {}",
                 attempt, self.url
             );
             let json: Result<String> = reqwest::blocking::get(&self.url)
-                .map_err(Error::from)
-                .and_then(|response| response.text().map_err(Error::from))
-                .and_then(validate_json);
+                .inspect(|response| http_status = Some(response.status().as_u16()))
+                .and_then(|response| response.error_for_status())
+                .and_then(|response| response.text())
+                .map_err(|error| Error::Http {
+                    url: self.url.clone(),
+                    attempts: attempt + 1,
+                    source: error,
+                })
+                .and_then(|json| validate_json(json, &self.url));
 
             match json {
                 Ok(json) => {
                     info!("Get AWS IP Ranges from URL; Attempt {}: Ok", attempt);
-                    break Ok(json);
+                    break UrlFetch {
+                        json: Ok(json),
+                        attempts: attempt + 1,
+                        http_status,
+                    };
                 }
                 Err(error) => {
                     log::error!(
@@ -476,7 +876,11 @@ impl Client {
                         thread::sleep(delay);
                         continue;
                     } else {
-                        break Err(error);
+                        break UrlFetch {
+                            json: Err(error),
+                            attempts: attempt,
+                            http_status,
+                        };
                     }
                 }
             }
@@ -495,7 +899,10 @@ impl Client {
                     &self.cache_file
                 )
             })
-            .map_err(Error::from)
+            .map_err(|error| Error::Io {
+                path: self.cache_file.clone(),
+                source: error,
+            })
             .inspect_err(|error| {
                 log::error!(
                     "Failed to cache AWS IP Ranges to `{:?}`: {}",
@@ -508,8 +915,11 @@ impl Client {
     /// Get the AWS IP Ranges JSON from the cache file.
     fn get_json_from_file(&self) -> Result<String> {
         fs::read_to_string(&self.cache_file)
-            .map_err(Error::from)
-            .and_then(validate_json)
+            .map_err(|error| Error::Io {
+                path: self.cache_file.clone(),
+                source: error,
+            })
+            .and_then(|json| validate_json(json, &self.cache_origin()))
             .inspect(|_| {
                 info!(
                     "Successfully read AWS IP Ranges JSON from: {:?}",
@@ -526,29 +936,195 @@ impl Client {
     }
 }
 
+/*--------------------------------------------------------------------------------------
+  URL Fetch
+--------------------------------------------------------------------------------------*/
+
+/// Result of requesting the AWS IP Ranges JSON from the URL, with the number of attempts made
+/// and the HTTP status code of the last response.
+struct UrlFetch {
+    json: Result<String>,
+    attempts: u32,
+    http_status: Option<u16>,
+}
+
+/*-------------------------------------------------------------------------------------------------
+  Fetch Policy
+-------------------------------------------------------------------------------------------------*/
+
+/// Policies deciding whether [Client::get_ranges] retrieves the AWS IP Ranges from the cache file
+/// or the URL. Set the policy with [ClientBuilder::fetch_policy] or the `AWSIPRANGES_FETCH_POLICY`
+/// environment variable (`prefer-cache`, `cache-only` or `offline`, `network-only`, or
+/// `always-refresh`).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum FetchPolicy {
+    /// Use the cache file when fresh; otherwise, request the URL and update the cache file,
+    /// falling back to a stale cache file when the URL is unavailable. Returns an [Error::Http]
+    /// when neither is available. A cache file holding AWS IP Ranges older than the
+    /// [max_data_age](ClientBuilder::max_data_age) is treated as stale.
+    #[default]
+    PreferCache,
+
+    /// Use the cache file, regardless of its age, and never request the URL (offline). Returns an
+    /// [Error::Io] when the cache file cannot be read or an [Error::Parse] when it is corrupt.
+    CacheOnly,
+
+    /// Request the URL and never read or write the cache file. Returns an [Error::Http] when the
+    /// URL is unavailable.
+    NetworkOnly,
+
+    /// Request the URL, regardless of the cache's age, and update the cache file; never falls
+    /// back to the cache file. Returns an [Error::Http] when the URL is unavailable.
+    AlwaysRefresh,
+}
+
+/*--------------------------------------------------------------------------------------
+  Fetch Policy Implementation
+--------------------------------------------------------------------------------------*/
+
+impl std::str::FromStr for FetchPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().replace('_', "-").as_str() {
+            "prefer-cache" => Ok(FetchPolicy::PreferCache),
+            "cache-only" | "offline" => Ok(FetchPolicy::CacheOnly),
+            "network-only" => Ok(FetchPolicy::NetworkOnly),
+            "always-refresh" => Ok(FetchPolicy::AlwaysRefresh),
+            _ => Err(format!("unknown fetch policy: {value}")),
+        }
+    }
+}
+
+impl std::fmt::Display for FetchPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FetchPolicy::PreferCache => write!(f, "prefer-cache"),
+            FetchPolicy::CacheOnly => write!(f, "cache-only"),
+            FetchPolicy::NetworkOnly => write!(f, "network-only"),
+            FetchPolicy::AlwaysRefresh => write!(f, "always-refresh"),
+        }
+    }
+}
+
+/*-------------------------------------------------------------------------------------------------
+  Data Age Policy
+-------------------------------------------------------------------------------------------------*/
+
+/// What [Client::get_ranges] does when the AWS IP Ranges are older than the client's
+/// [max_data_age](ClientBuilder::max_data_age). Set the policy with
+/// [ClientBuilder::data_age_policy] or the `AWSIPRANGES_DATA_AGE_POLICY` environment variable
+/// (`error` or `warn`).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum DataAgePolicy {
+    /// Return an [Error::StaleData].
+    #[default]
+    Error,
+
+    /// Log a warning and return the AWS IP Ranges.
+    Warn,
+}
+
+/*--------------------------------------------------------------------------------------
+  Data Age Policy Implementation
+--------------------------------------------------------------------------------------*/
+
+impl std::str::FromStr for DataAgePolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "error" => Ok(DataAgePolicy::Error),
+            "warn" => Ok(DataAgePolicy::Warn),
+            _ => Err(format!("unknown data age policy: {value}")),
+        }
+    }
+}
+
+impl std::fmt::Display for DataAgePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DataAgePolicy::Error => write!(f, "error"),
+            DataAgePolicy::Warn => write!(f, "warn"),
+        }
+    }
+}
+
 /*-------------------------------------------------------------------------------------------------
   Helper Functions
 -------------------------------------------------------------------------------------------------*/
 
-/// Get and parse an environment variable value or return a default value.
-fn get_env_var<T: std::str::FromStr>(env_var: &str, default: T) -> T {
-    env::var(env_var)
-        .ok()
-        .and_then(|value| {
-            value
-                .parse::<T>()
-                .inspect(|_| info!("Using {}: {}", env_var, value))
-                .inspect_err(|_| warn!("Invalid {}: {}", env_var, value))
-                .ok()
-        })
-        .unwrap_or(default)
+/// Get and parse an environment variable value or return a default value; an
+/// invalid value is an [Error::InvalidEnvVar] when `strict` and is logged and
+/// ignored otherwise.
+fn get_env_var<T>(env_var: &str, default: T, strict: bool) -> Result<T>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    get_optional_env_var(env_var, None, strict).map(|value| value.unwrap_or(default))
+}
+
+/// Get and parse an optional environment variable value or return a default
+/// value; an invalid value is an [Error::InvalidEnvVar] when `strict` and is
+/// logged and ignored otherwise.
+fn get_optional_env_var<T>(env_var: &str, default: Option<T>, strict: bool) -> Result<Option<T>>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    let (value, reason) = match env::var(env_var) {
+        Ok(value) => match value.parse::<T>() {
+            Ok(parsed) => {
+                info!("Using {}: {}", env_var, value);
+                return Ok(Some(parsed));
+            }
+            Err(error) => (value, error.to_string()),
+        },
+        Err(env::VarError::NotPresent) => return Ok(default),
+        Err(env::VarError::NotUnicode(value)) => (
+            value.to_string_lossy().into_owned(),
+            "not valid unicode".to_string(),
+        ),
+    };
+
+    if strict {
+        return Err(Error::InvalidEnvVar {
+            name: env_var.to_string(),
+            value,
+            reason,
+        });
+    }
+    warn!("Invalid {}: {}", env_var, value);
+    Ok(default)
+}
+
+/// Describe where the AWS IP Ranges came from, for errors.
+fn data_origin(aws_ip_ranges: &AwsIpRanges) -> String {
+    aws_ip_ranges
+        .provenance()
+        .map(|provenance| provenance.location.clone())
+        .unwrap_or_else(|| "AWS IP Ranges".to_string())
+}
+
+/// Parse the AWS IP Ranges JSON, recording where it came from.
+fn with_provenance(json: &str, provenance: Provenance) -> Result<Box<AwsIpRanges>> {
+    let mut aws_ip_ranges = AwsIpRanges::from_json(json, &provenance.location)?;
+    aws_ip_ranges.provenance = Some(provenance);
+    Ok(aws_ip_ranges)
 }
 
 /// Validate a string contains parsable JSON.
-fn validate_json(json: String) -> Result<String> {
-    serde_json::from_str::<serde::de::IgnoredAny>(&json)
-        .and(Ok(json))
-        .or(Err("Invalid JSON".into()))
+fn validate_json(json: String, origin: &str) -> Result<String> {
+    match serde_json::from_str::<serde::de::IgnoredAny>(&json) {
+        Ok(_) => Ok(json),
+        Err(error) => Err(Error::Parse {
+            origin: origin.to_string(),
+            source: error,
+        }),
+    }
 }
 
 /*-------------------------------------------------------------------------------------------------
@@ -591,6 +1167,10 @@ mod tests {
     /// ENV_VAR: AWSIPRANGES_RETRY_INITIAL_DELAY
     /// ENV_VAR: AWSIPRANGES_RETRY_BACKOFF_FACTOR
     /// ENV_VAR: AWSIPRANGES_RETRY_TIMEOUT
+    /// ENV_VAR: AWSIPRANGES_FETCH_POLICY
+    /// ENV_VAR: AWSIPRANGES_MAX_DATA_AGE
+    /// ENV_VAR: AWSIPRANGES_DATA_AGE_POLICY
+    /// ENV_VAR: AWSIPRANGES_EXPECTED_SYNC_TOKEN
     #[test]
     fn test_environment_variable_configuration() {
         let test_env_vars = [
@@ -604,6 +1184,10 @@ mod tests {
             ("AWSIPRANGES_RETRY_INITIAL_DELAY", "100"),
             ("AWSIPRANGES_RETRY_BACKOFF_FACTOR", "3"),
             ("AWSIPRANGES_RETRY_TIMEOUT", "1000"),
+            ("AWSIPRANGES_FETCH_POLICY", "offline"),
+            ("AWSIPRANGES_MAX_DATA_AGE", "604800"),
+            ("AWSIPRANGES_DATA_AGE_POLICY", "warn"),
+            ("AWSIPRANGES_EXPECTED_SYNC_TOKEN", "1712345678"),
         ];
 
         let default = Client::default();
@@ -629,6 +1213,10 @@ mod tests {
         assert_eq!(new.retry_initial_delay(), default.retry_initial_delay());
         assert_eq!(new.retry_backoff_factor(), default.retry_backoff_factor());
         assert_eq!(new.retry_timeout(), default.retry_timeout());
+        assert_eq!(new.fetch_policy(), default.fetch_policy());
+        assert_eq!(new.max_data_age(), default.max_data_age());
+        assert_eq!(new.data_age_policy(), default.data_age_policy());
+        assert_eq!(new.expected_sync_token(), default.expected_sync_token());
 
         // Set all environment variables
         for (env_var, value) in test_env_vars.iter() {
@@ -647,6 +1235,35 @@ mod tests {
         assert_eq!(env_config.retry_initial_delay(), 100);
         assert_eq!(env_config.retry_backoff_factor(), 3);
         assert_eq!(env_config.retry_timeout(), 1000);
+        assert_eq!(env_config.fetch_policy(), FetchPolicy::CacheOnly);
+        assert_eq!(env_config.max_data_age(), Some(604800));
+        assert_eq!(env_config.data_age_policy(), DataAgePolicy::Warn);
+        assert_eq!(env_config.expected_sync_token(), Some("1712345678"));
+        assert!(ClientBuilder::try_new().is_ok());
+
+        // Test invalid environment variable values
+        unsafe { std::env::set_var("AWSIPRANGES_FETCH_POLICY", "ofline") };
+        assert_eq!(Client::new().fetch_policy(), default.fetch_policy());
+        assert!(matches!(
+            ClientBuilder::try_new(),
+            Err(Error::InvalidEnvVar { name, value, .. })
+                if name == "AWSIPRANGES_FETCH_POLICY" && value == "ofline"
+        ));
+        unsafe { std::env::set_var("AWSIPRANGES_FETCH_POLICY", "offline") };
+
+        for (name, value) in [
+            ("AWSIPRANGES_MAX_DATA_AGE", "7d"),
+            ("AWSIPRANGES_DATA_AGE_POLICY", "ignore"),
+        ] {
+            let stored_value = std::env::var(name).unwrap();
+            unsafe { std::env::set_var(name, value) };
+            assert!(matches!(
+                ClientBuilder::try_new(),
+                Err(Error::InvalidEnvVar { name: error_name, .. }) if error_name == name
+            ));
+            unsafe { std::env::set_var(name, stored_value) };
+        }
+        assert_eq!(Client::new().max_data_age(), Some(604800));
 
         // Reset environment variables
         for (env_var, value) in stored_env_vars {
@@ -672,6 +1289,10 @@ mod tests {
             .retry_initial_delay(100)
             .retry_backoff_factor(3)
             .retry_timeout(1000)
+            .fetch_policy(FetchPolicy::NetworkOnly)
+            .max_data_age(3600)
+            .data_age_policy(DataAgePolicy::Warn)
+            .expected_sync_token("1712345678")
             .build();
 
         assert_eq!(client.url(), "https://my-ip-ranges.com/ip-ranges.json");
@@ -684,6 +1305,10 @@ mod tests {
         assert_eq!(client.retry_initial_delay(), 100);
         assert_eq!(client.retry_backoff_factor(), 3);
         assert_eq!(client.retry_timeout(), 1000);
+        assert_eq!(client.fetch_policy(), FetchPolicy::NetworkOnly);
+        assert_eq!(client.max_data_age(), Some(3600));
+        assert_eq!(client.data_age_policy(), DataAgePolicy::Warn);
+        assert_eq!(client.expected_sync_token(), Some("1712345678"));
     }
 
     /*-------------------------------------------------------------------------
@@ -709,7 +1334,7 @@ mod tests {
         let client: Client = ClientBuilder::default()
             .cache_file(&test_cache_file)
             .build();
-        let json = client.get_json_from_url().unwrap();
+        let (json, _) = client.get_json_from_url().unwrap();
         let result = client.cache_json_to_file(&json).inspect_err(log_error);
         assert!(result.is_ok());
     }
@@ -725,7 +1350,7 @@ mod tests {
         let client: Client = ClientBuilder::default()
             .cache_file(&test_cache_file)
             .build();
-        let json_from_url = client.get_json_from_url().unwrap();
+        let (json_from_url, _) = client.get_json_from_url().unwrap();
         client.cache_json_to_file(&json_from_url).unwrap();
 
         // Get JSON from test cache file
@@ -733,6 +1358,348 @@ mod tests {
         assert!(json_from_file.is_ok());
     }
 
+    /// Test comparing the cached AWS IP Ranges with a freshly downloaded copy.
+    /// URL: https://ip-ranges.amazonaws.com/ip-ranges.json
+    /// FILE: ./scratch/test_get_cached_ranges.json
+    #[test]
+    fn test_get_cached_ranges() {
+        let test_cache_file: PathBuf = [".", "scratch", "test_get_cached_ranges.json"]
+            .iter()
+            .collect();
+        let client: Client = ClientBuilder::default()
+            .cache_file(&test_cache_file)
+            .build();
+        let (json_from_url, _) = client.get_json_from_url().unwrap();
+        client.cache_json_to_file(&json_from_url).unwrap();
+
+        let cached_ranges = client.get_cached_ranges().inspect_err(log_error).unwrap();
+        let downloaded_ranges = client.download_ranges().inspect_err(log_error).unwrap();
+        assert!(cached_ranges.diff(&downloaded_ranges).is_empty());
+    }
+
+    /*-------------------------------------------------------------------------
+      Test Cache Management Methods
+    -------------------------------------------------------------------------*/
+
+    /// Test inspecting, verifying, and clearing the cache file.
+    /// FILE: ./scratch/test_cache_management.json
+    #[test]
+    fn test_cache_management() {
+        let test_cache_file: PathBuf = [".", "scratch", "test_cache_management.json"]
+            .iter()
+            .collect();
+        let client: Client = ClientBuilder::default()
+            .cache_file(&test_cache_file)
+            .build();
+        let json = Client::default().get_cached_ranges().unwrap().to_json();
+        client.cache_json_to_file(&json).unwrap();
+
+        let cache_info = client.cache_info().inspect_err(log_error).unwrap();
+        assert_eq!(cache_info.path, test_cache_file);
+        assert_eq!(cache_info.size, json.len() as u64);
+        assert!(cache_info.is_fresh());
+        assert!(cache_info.sync_token.is_some());
+        assert!(cache_info.create_date.is_some());
+        assert!(client.verify_cache().inspect_err(log_error).is_ok());
+
+        // A corrupt cache file is reported by verify, but not by info
+        client.cache_json_to_file("{\"syncToken\": ").unwrap();
+        let cache_info = client.cache_info().unwrap();
+        assert!(cache_info.sync_token.is_none());
+        assert!(matches!(client.verify_cache(), Err(Error::Parse { .. })));
+
+        assert!(client.clear_cache().unwrap());
+        assert!(!client.clear_cache().unwrap());
+        assert!(matches!(client.cache_info(), Err(Error::Io { .. })));
+    }
+
+    /*-------------------------------------------------------------------------
+      Test Fetch Policies
+    -------------------------------------------------------------------------*/
+
+    #[test]
+    fn test_fetch_policy_from_str() {
+        assert_eq!("offline".parse(), Ok(FetchPolicy::CacheOnly));
+        assert_eq!("Cache_Only".parse(), Ok(FetchPolicy::CacheOnly));
+        assert_eq!("network-only".parse(), Ok(FetchPolicy::NetworkOnly));
+        assert_eq!("PREFER-CACHE".parse(), Ok(FetchPolicy::PreferCache));
+        assert_eq!("always-refresh".parse(), Ok(FetchPolicy::AlwaysRefresh));
+        assert!("sometimes".parse::<FetchPolicy>().is_err());
+
+        for fetch_policy in [
+            FetchPolicy::PreferCache,
+            FetchPolicy::CacheOnly,
+            FetchPolicy::NetworkOnly,
+            FetchPolicy::AlwaysRefresh,
+        ] {
+            assert_eq!(fetch_policy.to_string().parse(), Ok(fetch_policy));
+        }
+    }
+
+    /// Test the cache-only policy uses a stale cache file and never requests
+    /// the URL.
+    /// FILE: ./scratch/test_fetch_policy_cache_only.json
+    #[test]
+    fn test_fetch_policy_cache_only() {
+        let test_cache_file: PathBuf = [".", "scratch", "test_fetch_policy_cache_only.json"]
+            .iter()
+            .collect();
+        let client: Client = ClientBuilder::default()
+            .url("http://127.0.0.1:9/ip-ranges.json")
+            .cache_file(&test_cache_file)
+            .cache_time(0)
+            .fetch_policy(FetchPolicy::CacheOnly)
+            .build();
+        let json = Client::default().get_cached_ranges().unwrap().to_json();
+        client.cache_json_to_file(&json).unwrap();
+        std::thread::sleep(time::Duration::from_millis(1100));
+
+        let aws_ip_ranges = client.get_ranges().inspect_err(log_error).unwrap();
+        let provenance = aws_ip_ranges.provenance().unwrap();
+        assert_eq!(provenance.source, DataSource::StaleCache);
+        assert_eq!(provenance.attempts, 0);
+
+        client.clear_cache().unwrap();
+        assert!(matches!(client.get_ranges(), Err(Error::Io { .. })));
+    }
+
+    /// Test the network-only and always-refresh policies never fall back to
+    /// the cache file.
+    /// FILE: ./scratch/test_fetch_policy_network.json
+    #[test]
+    fn test_fetch_policy_network() {
+        let test_cache_file: PathBuf = [".", "scratch", "test_fetch_policy_network.json"]
+            .iter()
+            .collect();
+        let mut client_builder = ClientBuilder::default();
+        client_builder
+            .url("http://127.0.0.1:9/ip-ranges.json")
+            .cache_file(&test_cache_file)
+            .retry_count(1);
+        let json = Client::default().get_cached_ranges().unwrap().to_json();
+        client_builder.build().cache_json_to_file(&json).unwrap();
+
+        for fetch_policy in [FetchPolicy::NetworkOnly, FetchPolicy::AlwaysRefresh] {
+            let client = client_builder.fetch_policy(fetch_policy).build();
+            assert!(matches!(
+                client.get_ranges(),
+                Err(Error::Http { attempts: 1, .. })
+            ));
+        }
+    }
+
+    /*-------------------------------------------------------------------------
+      Test Provenance
+    -------------------------------------------------------------------------*/
+
+    /// Test the provenance of AWS IP Ranges read from a fresh cache file and
+    /// from a stale cache file when the URL is unavailable.
+    /// FILE: ./scratch/test_cache_provenance.json
+    #[test]
+    fn test_cache_provenance() {
+        let test_cache_file: PathBuf = [".", "scratch", "test_cache_provenance.json"]
+            .iter()
+            .collect();
+        let mut client_builder = ClientBuilder::default();
+        client_builder
+            .url("http://127.0.0.1:9/ip-ranges.json")
+            .cache_file(&test_cache_file)
+            .retry_count(2)
+            .retry_initial_delay(10);
+        let json = Client::default().get_cached_ranges().unwrap().to_json();
+        client_builder.build().cache_json_to_file(&json).unwrap();
+
+        let aws_ip_ranges = client_builder.build().get_ranges().unwrap();
+        let provenance = aws_ip_ranges.provenance().unwrap();
+        assert_eq!(provenance.source, DataSource::FreshCache);
+        assert_eq!(provenance.location, test_cache_file.display().to_string());
+        assert!(provenance.cache_age.is_some());
+        assert!(!provenance.is_stale());
+
+        std::thread::sleep(time::Duration::from_millis(1100));
+        let aws_ip_ranges = client_builder
+            .cache_time(0)
+            .build()
+            .get_ranges()
+            .inspect_err(log_error)
+            .unwrap();
+        let provenance = aws_ip_ranges.provenance().unwrap();
+        assert!(provenance.is_stale());
+        assert_eq!(provenance.attempts, 2);
+        assert_eq!(provenance.http_status, None);
+    }
+
+    /*-------------------------------------------------------------------------
+      Test Data Age and Sync Token Checks
+    -------------------------------------------------------------------------*/
+
+    /// Test the maximum data age and expected sync token checks apply to the
+    /// AWS IP Ranges returned by `get_ranges()` and `check_ranges()`.
+    /// FILE: ./scratch/test_data_checks.json
+    #[test]
+    fn test_data_checks() {
+        let test_cache_file: PathBuf = [".", "scratch", "test_data_checks.json"].iter().collect();
+        let mut client_builder = ClientBuilder::default();
+        client_builder
+            .cache_file(&test_cache_file)
+            .fetch_policy(FetchPolicy::CacheOnly);
+        let cached_ranges = Client::default().get_cached_ranges().unwrap();
+        client_builder
+            .build()
+            .cache_json_to_file(&cached_ranges.to_json())
+            .unwrap();
+
+        let data_age = Utc::now()
+            .signed_duration_since(cached_ranges.create_date())
+            .num_seconds() as u64;
+
+        // Maximum data age
+        let client = client_builder.max_data_age(data_age + 3600).build();
+        assert!(client.get_ranges().inspect_err(log_error).is_ok());
+
+        let client = client_builder.max_data_age(data_age / 2).build();
+        assert!(matches!(
+            client.get_ranges(),
+            Err(Error::StaleData { max_data_age, .. }) if max_data_age == data_age / 2
+        ));
+
+        assert!(matches!(
+            client.check_ranges(&cached_ranges),
+            Err(Error::StaleData { .. })
+        ));
+
+        let client = client_builder.data_age_policy(DataAgePolicy::Warn).build();
+        let aws_ip_ranges = client.get_ranges().inspect_err(log_error).unwrap();
+        assert!(client.check_data_age(&aws_ip_ranges).is_err());
+        assert!(client.check_ranges(&aws_ip_ranges).is_ok());
+
+        // Expected sync token
+        let client = client_builder
+            .expected_sync_token(cached_ranges.sync_token())
+            .build();
+        assert!(client.get_ranges().inspect_err(log_error).is_ok());
+
+        let client = client_builder.expected_sync_token("1").build();
+        assert!(matches!(
+            client.get_ranges(),
+            Err(Error::SyncTokenMismatch { expected, .. }) if expected == "1"
+        ));
+        assert!(client.check_ranges(&cached_ranges).is_err());
+    }
+
+    /// Test a fresh cache file holding AWS IP Ranges older than the maximum
+    /// data age is not used without first requesting the URL, and the maximum
+    /// data age still applies when falling back to the cache file.
+    /// FILE: ./scratch/test_prefer_cache_over_age_unreachable_url.json
+    #[test]
+    fn test_prefer_cache_over_age_unreachable_url() {
+        let test_cache_file: PathBuf = [
+            ".",
+            "scratch",
+            "test_prefer_cache_over_age_unreachable_url.json",
+        ]
+        .iter()
+        .collect();
+        let mut client_builder = ClientBuilder::default();
+        client_builder
+            .url("http://127.0.0.1:9/ip-ranges.json")
+            .cache_file(&test_cache_file)
+            .retry_count(2)
+            .retry_initial_delay(10)
+            .max_data_age(3600);
+        let cached_ranges = Client::default().get_cached_ranges().unwrap();
+        client_builder
+            .build()
+            .cache_json_to_file(&cached_ranges.to_json())
+            .unwrap();
+
+        assert!(matches!(
+            client_builder.build().get_ranges(),
+            Err(Error::StaleData { .. })
+        ));
+
+        let aws_ip_ranges = client_builder
+            .data_age_policy(DataAgePolicy::Warn)
+            .build()
+            .get_ranges()
+            .inspect_err(log_error)
+            .unwrap();
+        let provenance = aws_ip_ranges.provenance().unwrap();
+        assert_eq!(provenance.location, test_cache_file.display().to_string());
+        assert_eq!(provenance.attempts, 2);
+    }
+
+    /// Test a fresh cache file holding AWS IP Ranges older than the maximum
+    /// data age is refreshed from the URL.
+    /// FILE: ./scratch/test_prefer_cache_over_age_refresh.json
+    #[test]
+    fn test_prefer_cache_over_age_refresh() {
+        let test_cache_file: PathBuf = [".", "scratch", "test_prefer_cache_over_age_refresh.json"]
+            .iter()
+            .collect();
+        let cached_ranges = Client::default().get_cached_ranges().unwrap();
+        let mut current_ranges = cached_ranges.clone();
+        current_ranges.create_date = Utc::now();
+        let url = serve_json(current_ranges.to_json());
+
+        let client = ClientBuilder::default()
+            .url(&url)
+            .cache_file(&test_cache_file)
+            .max_data_age(3600)
+            .build();
+        client.cache_json_to_file(&cached_ranges.to_json()).unwrap();
+
+        let aws_ip_ranges = client.get_ranges().inspect_err(log_error).unwrap();
+        let provenance = aws_ip_ranges.provenance().unwrap();
+        assert_eq!(provenance.source, DataSource::Network);
+        assert_eq!(provenance.location, url);
+        assert_eq!(provenance.http_status, Some(200));
+
+        let cache_info = client.cache_info().unwrap();
+        assert_eq!(
+            cache_info
+                .create_date
+                .map(|create_date| create_date.timestamp()),
+            Some(current_ranges.create_date().timestamp())
+        );
+    }
+
+    /// Serve the JSON to a single HTTP request from a local TCP listener;
+    /// returns the URL to request.
+    fn serve_json(json: String) -> String {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+
+            // Read the request headers
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{json}",
+                json.len()
+            )
+            .unwrap();
+        });
+
+        format!("http://{address}/ip-ranges.json")
+    }
+
+    #[test]
+    fn test_data_age_policy_from_str() {
+        assert_eq!("error".parse(), Ok(DataAgePolicy::Error));
+        assert_eq!("WARN".parse(), Ok(DataAgePolicy::Warn));
+        assert!("ignore".parse::<DataAgePolicy>().is_err());
+        assert_eq!(DataAgePolicy::Warn.to_string(), "warn");
+    }
+
     /*-------------------------------------------------------------------------
       Test JSON Parsing
     -------------------------------------------------------------------------*/
@@ -742,8 +1709,8 @@ mod tests {
     #[test]
     fn test_parse_json() {
         let client = Client::default();
-        let json = client.get_json_from_url().unwrap();
-        let json_ip_ranges = json::parse(&json).inspect_err(log_error);
+        let (json, _) = client.get_json_from_url().unwrap();
+        let json_ip_ranges = json::parse(&json, client.url()).inspect_err(log_error);
         assert!(json_ip_ranges.is_ok());
     }
 
@@ -752,8 +1719,8 @@ mod tests {
     #[test]
     fn test_serialize_json_ip_ranges() {
         let client = Client::default();
-        let json_from_url = client.get_json_from_url().unwrap();
-        let json_ip_ranges = json::parse(&json_from_url).unwrap();
+        let (json_from_url, _) = client.get_json_from_url().unwrap();
+        let json_ip_ranges = json::parse(&json_from_url, client.url()).unwrap();
         let serialized_json = serde_json::to_string(&json_ip_ranges);
         assert!(serialized_json.is_ok());
     }
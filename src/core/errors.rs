@@ -1,16 +1,233 @@
+use chrono::{DateTime, Utc};
+use std::fmt;
+use std::path::PathBuf;
+
 /*-------------------------------------------------------------------------------------------------
   Errors and Results
 -------------------------------------------------------------------------------------------------*/
 
-// TODO: Add descriptive error messages and handling for the various errors that can occur in the
-//       crate.
+/// Errors returned by the `awsipranges` library.
+///
+/// Each variant carries the structured context needed to handle the error programmatically (the
+/// URL, file path, attempt count, or offending value). The enum is marked `#[non_exhaustive]`, so
+/// include a wildcard arm when matching on it.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Retrieving the AWS IP Ranges JSON from a URL failed.
+    Http {
+        /// URL requested.
+        url: String,
+        /// Number of attempts made before giving up.
+        attempts: u32,
+        /// Error returned by the HTTP client on the last attempt.
+        source: reqwest::Error,
+    },
+
+    /// Reading or writing a file failed.
+    Io {
+        /// Path of the file being accessed.
+        path: PathBuf,
+        /// Underlying I/O error.
+        source: std::io::Error,
+    },
+
+    /// Reading from or writing to a standard stream (stdin or stdout) failed.
+    Stream {
+        /// Stream being accessed (`stdin` or `stdout`).
+        stream: String,
+        /// Underlying I/O error.
+        source: std::io::Error,
+    },
+
+    /// Parsing the AWS IP Ranges JSON failed.
+    Parse {
+        /// Where the JSON came from (a URL, file path, or other description).
+        origin: String,
+        /// Underlying JSON parsing error.
+        source: serde_json::Error,
+    },
+
+    /// A value could not be interpreted as an IP prefix.
+    InvalidPrefix {
+        /// Offending value.
+        value: String,
+        /// Why the value is invalid.
+        reason: String,
+    },
+
+    /// A line of search input could not be interpreted as an IP prefix.
+    InvalidInput {
+        /// Where the input came from (a file path or `<stdin>`).
+        origin: String,
+        /// Line number (starting at 1) of the offending line.
+        line: usize,
+        /// Offending value.
+        value: String,
+        /// Why the value is invalid.
+        reason: String,
+    },
+
+    /// The AWS IP Ranges data violated an expected invariant.
+    Validation {
+        /// Where the data came from (a URL, file path, or other description).
+        origin: String,
+        /// Description of the violated invariant.
+        message: String,
+    },
+
+    /// The AWS IP Ranges were published longer ago than the client's maximum data age.
+    StaleData {
+        /// Where the data came from (a URL, file path, or other description).
+        origin: String,
+        /// Publication time (`createDate`) of the AWS IP Ranges.
+        create_date: DateTime<Utc>,
+        /// Maximum data age (in seconds).
+        max_data_age: u64,
+    },
+
+    /// The AWS IP Ranges do not have the client's expected `syncToken`.
+    SyncTokenMismatch {
+        /// Where the data came from (a URL, file path, or other description).
+        origin: String,
+        /// Expected `syncToken`.
+        expected: String,
+        /// `syncToken` of the AWS IP Ranges.
+        actual: String,
+    },
+
+    /// An environment variable has a value that cannot be parsed.
+    InvalidEnvVar {
+        /// Name of the environment variable.
+        name: String,
+        /// Offending value.
+        value: String,
+        /// Why the value is invalid.
+        reason: String,
+    },
 
-// Error type alias used throughout the crate.
-pub type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
+    /// A filter's minimum prefix length is greater than its maximum prefix length.
+    InvalidPrefixLenRange {
+        /// IP version of the prefix lengths (`IPv4` or `IPv6`).
+        ip_version: String,
+        /// Minimum prefix length.
+        min: u8,
+        /// Maximum prefix length.
+        max: u8,
+    },
 
-// Result type alias used throughout the crate.
+    /// A filter value does not exist in the AWS IP Ranges.
+    UnknownFilterValue {
+        /// Kind of filter value (region, network border group, or service).
+        kind: FilterValueKind,
+        /// Offending value.
+        value: String,
+    },
+}
+
+/// Kinds of values used to filter the AWS IP Ranges.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum FilterValueKind {
+    Region,
+    NetworkBorderGroup,
+    Service,
+}
+
+/// Result type alias used throughout the crate.
 pub type Result<T> = std::result::Result<T, Error>;
 
+/*--------------------------------------------------------------------------------------
+  Error Implementation
+--------------------------------------------------------------------------------------*/
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Http {
+                url,
+                attempts,
+                source,
+            } => write!(
+                f,
+                "failed to retrieve `{url}` after {attempts} attempt(s): {source}"
+            ),
+            Error::Io { path, source } => write!(f, "failed to access {path:?}: {source}"),
+            Error::Stream { stream, source } => write!(f, "failed to access {stream}: {source}"),
+            Error::Parse { origin, source } => {
+                write!(f, "failed to parse JSON from `{origin}`: {source}")
+            }
+            Error::InvalidPrefix { value, reason } => {
+                write!(f, "invalid IP prefix `{value}`: {reason}")
+            }
+            Error::InvalidInput {
+                origin,
+                line,
+                value,
+                reason,
+            } => write!(
+                f,
+                "invalid IP prefix `{value}` on line {line} of `{origin}`: {reason}"
+            ),
+            Error::Validation { origin, message } => {
+                write!(f, "invalid AWS IP Ranges data from `{origin}`: {message}")
+            }
+            Error::StaleData {
+                origin,
+                create_date,
+                max_data_age,
+            } => write!(
+                f,
+                "AWS IP Ranges from `{origin}` were published at {create_date}, more than {max_data_age} seconds ago"
+            ),
+            Error::SyncTokenMismatch {
+                origin,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "AWS IP Ranges from `{origin}` have syncToken {actual}; expected {expected}"
+            ),
+            Error::InvalidEnvVar {
+                name,
+                value,
+                reason,
+            } => write!(f, "invalid {name} value `{value}`: {reason}"),
+            Error::InvalidPrefixLenRange {
+                ip_version,
+                min,
+                max,
+            } => write!(
+                f,
+                "invalid {ip_version} prefix length range: minimum /{min} is greater than maximum /{max}"
+            ),
+            Error::UnknownFilterValue { kind, value } => write!(f, "unknown {kind}: {value}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Http { source, .. } => Some(source),
+            Error::Io { source, .. } => Some(source),
+            Error::Stream { source, .. } => Some(source),
+            Error::Parse { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for FilterValueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterValueKind::Region => write!(f, "region"),
+            FilterValueKind::NetworkBorderGroup => write!(f, "network border group"),
+            FilterValueKind::Service => write!(f, "service"),
+        }
+    }
+}
+
 /*--------------------------------------------------------------------------------------
   Log Error Function
 --------------------------------------------------------------------------------------*/
@@ -19,3 +236,110 @@ pub type Result<T> = std::result::Result<T, Error>;
 pub(crate) fn log_error(error: &Error) {
     log::error!("{}", error);
 }
+
+/*-------------------------------------------------------------------------------------------------
+  Unit Tests
+-------------------------------------------------------------------------------------------------*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_display() {
+        let error = Error::UnknownFilterValue {
+            kind: FilterValueKind::NetworkBorderGroup,
+            value: "us-east-1-nope-1".to_string(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "unknown network border group: us-east-1-nope-1"
+        );
+
+        let error = Error::InvalidPrefix {
+            value: "10.0.0.0/33".to_string(),
+            reason: "invalid prefix".to_string(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "invalid IP prefix `10.0.0.0/33`: invalid prefix"
+        );
+
+        let error = Error::InvalidInput {
+            origin: "addresses.txt".to_string(),
+            line: 3,
+            value: "10.0.0.256".to_string(),
+            reason: "invalid address".to_string(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "invalid IP prefix `10.0.0.256` on line 3 of `addresses.txt`: invalid address"
+        );
+
+        let error = Error::InvalidEnvVar {
+            name: "AWSIPRANGES_FETCH_POLICY".to_string(),
+            value: "ofline".to_string(),
+            reason: "unknown fetch policy: ofline".to_string(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "invalid AWSIPRANGES_FETCH_POLICY value `ofline`: unknown fetch policy: ofline"
+        );
+
+        let error = Error::InvalidPrefixLenRange {
+            ip_version: "IPv4".to_string(),
+            min: 24,
+            max: 16,
+        };
+        assert_eq!(
+            error.to_string(),
+            "invalid IPv4 prefix length range: minimum /24 is greater than maximum /16"
+        );
+    }
+
+    #[test]
+    fn test_data_error_display() {
+        let error = Error::SyncTokenMismatch {
+            origin: "ip-ranges.json".to_string(),
+            expected: "1712345678".to_string(),
+            actual: "1760000000".to_string(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "AWS IP Ranges from `ip-ranges.json` have syncToken 1760000000; expected 1712345678"
+        );
+
+        let error = Error::StaleData {
+            origin: "ip-ranges.json".to_string(),
+            create_date: DateTime::from_timestamp(1712345678, 0).unwrap(),
+            max_data_age: 86400,
+        };
+        assert_eq!(
+            error.to_string(),
+            "AWS IP Ranges from `ip-ranges.json` were published at 2024-04-05 19:34:38 UTC, more than 86400 seconds ago"
+        );
+    }
+
+    #[test]
+    fn test_error_source() {
+        use std::error::Error as _;
+
+        let error = Error::Io {
+            path: PathBuf::from("/nonexistent/ip-ranges.json"),
+            source: std::io::Error::from(std::io::ErrorKind::NotFound),
+        };
+        assert!(error.source().is_some());
+
+        let error = Error::Stream {
+            stream: "stdout".to_string(),
+            source: std::io::Error::from(std::io::ErrorKind::BrokenPipe),
+        };
+        assert!(error.source().is_some());
+
+        let error = Error::UnknownFilterValue {
+            kind: FilterValueKind::Region,
+            value: "nope".to_string(),
+        };
+        assert!(error.source().is_none());
+    }
+}
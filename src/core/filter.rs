@@ -1,24 +1,49 @@
 use crate::core::aws_ip_prefix::AwsIpPrefix;
 use crate::core::aws_ip_ranges::AwsIpRanges;
-use crate::core::errors::Result;
+use crate::core::errors::{Error, FilterValueKind, Result};
 use crate::core::prefix_type::PrefixType;
+use crate::core::utils;
+use crate::core::utils::ipnetwork::{is_supernet_of, network_prefix};
+use ipnetwork::IpNetwork;
 use log::trace;
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeSet;
-use std::rc::Rc;
+use std::sync::Arc;
+
+/// Catch-all service published for (nearly) every AWS IP Prefix.
+pub(crate) const AMAZON_SERVICE: &str = "AMAZON";
 
 /*-------------------------------------------------------------------------------------------------
   FilterBuilder
 -------------------------------------------------------------------------------------------------*/
 
 /// Builder used to construct a [Filter] object with the desired filter parameters.
+///
+/// Region, network border group, and service values are case-insensitive glob patterns (`*`
+/// matches any sequence of characters and `?` matches any single character) expanded against
+/// the values in the AWS IP Ranges; `"us-*"` selects all US regions and `"*-lax-*"` selects the
+/// Los Angeles Local Zones. Setters return an [Error::UnknownFilterValue] error when a value
+/// matches nothing.
 #[derive(Debug)]
 pub struct FilterBuilder<'a> {
     aws_ip_ranges: &'a AwsIpRanges,
 
     prefix_type: Option<PrefixType>,
-    regions: Option<BTreeSet<Rc<str>>>,
-    network_border_groups: Option<BTreeSet<Rc<str>>>,
-    services: Option<BTreeSet<Rc<str>>>,
+    regions: Option<BTreeSet<Arc<str>>>,
+    network_border_groups: Option<BTreeSet<Arc<str>>>,
+    services: Option<BTreeSet<Arc<str>>>,
+
+    exclude_regions: Option<BTreeSet<Arc<str>>>,
+    exclude_network_border_groups: Option<BTreeSet<Arc<str>>>,
+    exclude_services: Option<BTreeSet<Arc<str>>>,
+    exclude_amazon_only: bool,
+
+    min_ipv4_prefix_len: Option<u8>,
+    max_ipv4_prefix_len: Option<u8>,
+    min_ipv6_prefix_len: Option<u8>,
+    max_ipv6_prefix_len: Option<u8>,
+    within: Option<BTreeSet<IpNetwork>>,
+    not_within: Option<BTreeSet<IpNetwork>>,
 }
 
 /*--------------------------------------------------------------------------------------
@@ -54,6 +79,16 @@ impl<'a> FilterBuilder<'a> {
             regions: None,
             network_border_groups: None,
             services: None,
+            exclude_regions: None,
+            exclude_network_border_groups: None,
+            exclude_services: None,
+            exclude_amazon_only: false,
+            min_ipv4_prefix_len: None,
+            max_ipv4_prefix_len: None,
+            min_ipv6_prefix_len: None,
+            max_ipv6_prefix_len: None,
+            within: None,
+            not_within: None,
         }
     }
 
@@ -91,15 +126,7 @@ impl<'a> FilterBuilder<'a> {
         I: IntoIterator<Item = S>,
         S: AsRef<str>,
     {
-        let regions: Result<BTreeSet<Rc<str>>> = regions
-            .into_iter()
-            .map(|region| {
-                self.aws_ip_ranges
-                    .get_region(region.as_ref())
-                    .ok_or(format!("Invalid region: {}", region.as_ref()).into())
-            })
-            .collect();
-        self.regions = Some(regions?);
+        self.regions = Some(self.lookup(FilterValueKind::Region, regions)?);
         Ok(self)
     }
 
@@ -109,21 +136,8 @@ impl<'a> FilterBuilder<'a> {
         I: IntoIterator<Item = S>,
         S: AsRef<str>,
     {
-        let network_border_groups: Result<BTreeSet<Rc<str>>> = network_border_groups
-            .into_iter()
-            .map(|network_border_group| {
-                self.aws_ip_ranges
-                    .get_network_border_group(network_border_group.as_ref())
-                    .ok_or(
-                        format!(
-                            "Invalid network border group: {}",
-                            network_border_group.as_ref()
-                        )
-                        .into(),
-                    )
-            })
-            .collect();
-        self.network_border_groups = Some(network_border_groups?);
+        self.network_border_groups =
+            Some(self.lookup(FilterValueKind::NetworkBorderGroup, network_border_groups)?);
         Ok(self)
     }
 
@@ -133,18 +147,111 @@ impl<'a> FilterBuilder<'a> {
         I: IntoIterator<Item = S>,
         S: AsRef<str>,
     {
-        let services: Result<BTreeSet<Rc<str>>> = services
-            .into_iter()
-            .map(|service| {
-                self.aws_ip_ranges
-                    .get_service(service.as_ref())
-                    .ok_or(format!("Invalid service: {}", service.as_ref()).into())
-            })
-            .collect();
-        self.services = Some(services?);
+        self.services = Some(self.lookup(FilterValueKind::Service, services)?);
+        Ok(self)
+    }
+
+    /// Exclude AWS IP Prefixes from the provided AWS regions. Exclusions take precedence over
+    /// the included regions, network border groups, and services.
+    pub fn exclude_regions<I, S>(mut self, regions: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.exclude_regions = Some(self.lookup(FilterValueKind::Region, regions)?);
+        Ok(self)
+    }
+
+    /// Exclude AWS IP Prefixes from the provided network border groups. Exclusions take
+    /// precedence over the included regions, network border groups, and services.
+    pub fn exclude_network_border_groups<I, S>(mut self, network_border_groups: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.exclude_network_border_groups =
+            Some(self.lookup(FilterValueKind::NetworkBorderGroup, network_border_groups)?);
+        Ok(self)
+    }
+
+    /// Exclude AWS IP Prefixes used by any of the provided services, even when the prefixes are
+    /// also used by an included service. Exclusions take precedence over the included regions,
+    /// network border groups, and services.
+    ///
+    /// Nearly every AWS IP Prefix is also published under the catch-all `AMAZON` service; use
+    /// [FilterBuilder::exclude_amazon_only] to drop only the prefixes not attributed to a
+    /// specific service.
+    pub fn exclude_services<I, S>(mut self, services: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.exclude_services = Some(self.lookup(FilterValueKind::Service, services)?);
         Ok(self)
     }
 
+    /// Exclude AWS IP Prefixes whose only service is the catch-all `AMAZON` service.
+    pub fn exclude_amazon_only(mut self) -> Self {
+        self.exclude_amazon_only = true;
+        self
+    }
+
+    /// Include IPv4 prefixes with a prefix length of at least `len` bits (`/len` or longer).
+    /// Returns an [Error::InvalidPrefixLenRange] error when `len` is greater than the maximum
+    /// IPv4 prefix length.
+    pub fn min_ipv4_prefix_len(mut self, len: u8) -> Result<Self> {
+        check_prefix_len_range("IPv4", Some(len), self.max_ipv4_prefix_len)?;
+        self.min_ipv4_prefix_len = Some(len);
+        Ok(self)
+    }
+
+    /// Include IPv4 prefixes with a prefix length of at most `len` bits (`/len` or shorter).
+    /// Returns an [Error::InvalidPrefixLenRange] error when `len` is less than the minimum IPv4
+    /// prefix length.
+    pub fn max_ipv4_prefix_len(mut self, len: u8) -> Result<Self> {
+        check_prefix_len_range("IPv4", self.min_ipv4_prefix_len, Some(len))?;
+        self.max_ipv4_prefix_len = Some(len);
+        Ok(self)
+    }
+
+    /// Include IPv6 prefixes with a prefix length of at least `len` bits (`/len` or longer).
+    /// Returns an [Error::InvalidPrefixLenRange] error when `len` is greater than the maximum
+    /// IPv6 prefix length.
+    pub fn min_ipv6_prefix_len(mut self, len: u8) -> Result<Self> {
+        check_prefix_len_range("IPv6", Some(len), self.max_ipv6_prefix_len)?;
+        self.min_ipv6_prefix_len = Some(len);
+        Ok(self)
+    }
+
+    /// Include IPv6 prefixes with a prefix length of at most `len` bits (`/len` or shorter).
+    /// Returns an [Error::InvalidPrefixLenRange] error when `len` is less than the minimum IPv6
+    /// prefix length.
+    pub fn max_ipv6_prefix_len(mut self, len: u8) -> Result<Self> {
+        check_prefix_len_range("IPv6", self.min_ipv6_prefix_len, Some(len))?;
+        self.max_ipv6_prefix_len = Some(len);
+        Ok(self)
+    }
+
+    /// Include AWS IP Prefixes within (equal to or a subnet of) the provided network. Call
+    /// repeatedly to include prefixes within any of several networks. Host bits set in the
+    /// provided network are ignored.
+    pub fn within(mut self, network: IpNetwork) -> Self {
+        self.within
+            .get_or_insert_with(BTreeSet::new)
+            .insert(network_prefix(&network));
+        self
+    }
+
+    /// Exclude AWS IP Prefixes within (equal to or a subnet of) the provided network. Call
+    /// repeatedly to exclude several networks. Exclusions take precedence over inclusions. Host
+    /// bits set in the provided network are ignored.
+    pub fn not_within(mut self, network: IpNetwork) -> Self {
+        self.not_within
+            .get_or_insert_with(BTreeSet::new)
+            .insert(network_prefix(&network));
+        self
+    }
+
     /*-------------------------------------------------------------------------
       Build
     -------------------------------------------------------------------------*/
@@ -156,6 +263,16 @@ impl<'a> FilterBuilder<'a> {
             regions: self.regions.clone(),
             network_border_groups: self.network_border_groups.clone(),
             services: self.services.clone(),
+            exclude_regions: self.exclude_regions.clone(),
+            exclude_network_border_groups: self.exclude_network_border_groups.clone(),
+            exclude_services: self.exclude_services.clone(),
+            exclude_amazon_only: self.exclude_amazon_only,
+            min_ipv4_prefix_len: self.min_ipv4_prefix_len,
+            max_ipv4_prefix_len: self.max_ipv4_prefix_len,
+            min_ipv6_prefix_len: self.min_ipv6_prefix_len,
+            max_ipv6_prefix_len: self.max_ipv6_prefix_len,
+            within: self.within.clone(),
+            not_within: self.not_within.clone(),
         }
     }
 
@@ -168,28 +285,111 @@ impl<'a> FilterBuilder<'a> {
         let filter = self.build();
         self.aws_ip_ranges.filter(&filter)
     }
+
+    /*-------------------------------------------------------------------------
+      Private Methods
+    -------------------------------------------------------------------------*/
+
+    /// Expand the provided filter values, which may be case-insensitive glob patterns, into the
+    /// matching reference-counted strings from the AWS IP Ranges. Returns an error if any value
+    /// matches nothing.
+    fn lookup<I, S>(&self, kind: FilterValueKind, values: I) -> Result<BTreeSet<Arc<str>>>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let set = match kind {
+            FilterValueKind::Region => self.aws_ip_ranges.regions(),
+            FilterValueKind::NetworkBorderGroup => self.aws_ip_ranges.network_border_groups(),
+            FilterValueKind::Service => self.aws_ip_ranges.services(),
+        };
+
+        let mut matches: BTreeSet<Arc<str>> = BTreeSet::new();
+        for value in values {
+            let value = value.as_ref();
+            let value_matches = utils::get_arc_strs_matching_pattern(value, set);
+            if value_matches.is_empty() {
+                return Err(Error::UnknownFilterValue {
+                    kind,
+                    value: value.to_string(),
+                });
+            }
+            trace!("Filter {kind} `{value}` matches: {value_matches:?}");
+            matches.extend(value_matches);
+        }
+
+        Ok(matches)
+    }
+}
+
+/// Check that a minimum prefix length is not greater than a maximum prefix length.
+fn check_prefix_len_range(ip_version: &str, min: Option<u8>, max: Option<u8>) -> Result<()> {
+    match (min, max) {
+        (Some(min), Some(max)) if min > max => Err(Error::InvalidPrefixLenRange {
+            ip_version: ip_version.to_string(),
+            min,
+            max,
+        }),
+        _ => Ok(()),
+    }
 }
 
 /*-------------------------------------------------------------------------------------------------
   Filter
 -------------------------------------------------------------------------------------------------*/
 
-/// Filter used to include AWS IP Prefixes based on the prefix type (IPv4/IPv6),
-/// regions, network border groups, and services associated with the prefixes. Use the
-/// [FilterBuilder] to construct a [Filter] object with the desired filter parameters.
-#[derive(Debug, Default)]
+/// Filter used to include AWS IP Prefixes based on the prefix type (IPv4/IPv6), prefix length,
+/// the networks containing the prefixes, and the regions, network border groups, and services
+/// associated with the prefixes. Use the [FilterBuilder] to construct a [Filter] object with
+/// the desired filter parameters.
+///
+/// Exclusions (regions, network border groups, services, `AMAZON`-only prefixes, and networks)
+/// take precedence: a prefix matching any exclusion is dropped, even when it matches the
+/// included regions, network border groups, services, and networks.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
 pub struct Filter {
     /// Only include IPv4 or IPv6 AWS IP Prefixes.
     prefix_type: Option<PrefixType>,
 
     /// Include AWS IP Prefixes from these AWS regions.
-    regions: Option<BTreeSet<Rc<str>>>,
+    regions: Option<BTreeSet<Arc<str>>>,
 
     /// Include AWS IP Prefixes from these network border groups.
-    network_border_groups: Option<BTreeSet<Rc<str>>>,
+    network_border_groups: Option<BTreeSet<Arc<str>>>,
 
     /// Include AWS IP Prefixes used by these services.
-    services: Option<BTreeSet<Rc<str>>>,
+    services: Option<BTreeSet<Arc<str>>>,
+
+    /// Exclude AWS IP Prefixes from these AWS regions.
+    exclude_regions: Option<BTreeSet<Arc<str>>>,
+
+    /// Exclude AWS IP Prefixes from these network border groups.
+    exclude_network_border_groups: Option<BTreeSet<Arc<str>>>,
+
+    /// Exclude AWS IP Prefixes used by any of these services.
+    exclude_services: Option<BTreeSet<Arc<str>>>,
+
+    /// Exclude AWS IP Prefixes whose only service is `AMAZON`.
+    exclude_amazon_only: bool,
+
+    /// Include IPv4 prefixes with at least this prefix length.
+    min_ipv4_prefix_len: Option<u8>,
+
+    /// Include IPv4 prefixes with at most this prefix length.
+    max_ipv4_prefix_len: Option<u8>,
+
+    /// Include IPv6 prefixes with at least this prefix length.
+    min_ipv6_prefix_len: Option<u8>,
+
+    /// Include IPv6 prefixes with at most this prefix length.
+    max_ipv6_prefix_len: Option<u8>,
+
+    /// Include AWS IP Prefixes within any of these networks.
+    within: Option<BTreeSet<IpNetwork>>,
+
+    /// Exclude AWS IP Prefixes within any of these networks.
+    not_within: Option<BTreeSet<IpNetwork>>,
 }
 
 /*--------------------------------------------------------------------------------------
@@ -218,20 +418,70 @@ impl Filter {
     }
 
     /// AWS regions included in the filter.
-    pub fn regions(&self) -> Option<&BTreeSet<Rc<str>>> {
+    pub fn regions(&self) -> Option<&BTreeSet<Arc<str>>> {
         self.regions.as_ref()
     }
 
     /// Network border groups included in the filter.
-    pub fn network_border_groups(&self) -> Option<&BTreeSet<Rc<str>>> {
+    pub fn network_border_groups(&self) -> Option<&BTreeSet<Arc<str>>> {
         self.network_border_groups.as_ref()
     }
 
     /// AWS services included in the filter.
-    pub fn services(&self) -> Option<&BTreeSet<Rc<str>>> {
+    pub fn services(&self) -> Option<&BTreeSet<Arc<str>>> {
         self.services.as_ref()
     }
 
+    /// AWS regions excluded by the filter.
+    pub fn exclude_regions(&self) -> Option<&BTreeSet<Arc<str>>> {
+        self.exclude_regions.as_ref()
+    }
+
+    /// Network border groups excluded by the filter.
+    pub fn exclude_network_border_groups(&self) -> Option<&BTreeSet<Arc<str>>> {
+        self.exclude_network_border_groups.as_ref()
+    }
+
+    /// AWS services excluded by the filter.
+    pub fn exclude_services(&self) -> Option<&BTreeSet<Arc<str>>> {
+        self.exclude_services.as_ref()
+    }
+
+    /// Check if the filter excludes prefixes whose only service is `AMAZON`.
+    pub fn exclude_amazon_only(&self) -> bool {
+        self.exclude_amazon_only
+    }
+
+    /// Minimum IPv4 prefix length included in the filter.
+    pub fn min_ipv4_prefix_len(&self) -> Option<u8> {
+        self.min_ipv4_prefix_len
+    }
+
+    /// Maximum IPv4 prefix length included in the filter.
+    pub fn max_ipv4_prefix_len(&self) -> Option<u8> {
+        self.max_ipv4_prefix_len
+    }
+
+    /// Minimum IPv6 prefix length included in the filter.
+    pub fn min_ipv6_prefix_len(&self) -> Option<u8> {
+        self.min_ipv6_prefix_len
+    }
+
+    /// Maximum IPv6 prefix length included in the filter.
+    pub fn max_ipv6_prefix_len(&self) -> Option<u8> {
+        self.max_ipv6_prefix_len
+    }
+
+    /// Networks that included AWS IP Prefixes must be within.
+    pub fn within(&self) -> Option<&BTreeSet<IpNetwork>> {
+        self.within.as_ref()
+    }
+
+    /// Networks that excluded AWS IP Prefixes are within.
+    pub fn not_within(&self) -> Option<&BTreeSet<IpNetwork>> {
+        self.not_within.as_ref()
+    }
+
     /*-------------------------------------------------------------------------
       Filter Functions
     -------------------------------------------------------------------------*/
@@ -279,12 +529,67 @@ impl Filter {
         }
     }
 
+    pub(crate) fn match_exclusions(&self, aws_ip_prefix: &AwsIpPrefix) -> bool {
+        let excluded_region = self
+            .exclude_regions
+            .as_ref()
+            .is_some_and(|regions| regions.contains(&aws_ip_prefix.region));
+        let excluded_network_border_group = self
+            .exclude_network_border_groups
+            .as_ref()
+            .is_some_and(|groups| groups.contains(&aws_ip_prefix.network_border_group));
+        let excluded_service = self.exclude_services.as_ref().is_some_and(|services| {
+            services
+                .intersection(&aws_ip_prefix.services)
+                .next()
+                .is_some()
+        });
+        let excluded_amazon_only = self.exclude_amazon_only
+            && aws_ip_prefix.services.len() == 1
+            && aws_ip_prefix.services.contains(AMAZON_SERVICE);
+        let excluded_network = self.not_within.as_ref().is_some_and(|networks| {
+            networks
+                .iter()
+                .any(|network| is_supernet_of(*network, aws_ip_prefix.prefix))
+        });
+
+        !(excluded_region
+            || excluded_network_border_group
+            || excluded_service
+            || excluded_amazon_only
+            || excluded_network)
+    }
+
+    pub(crate) fn match_prefix_len(&self, aws_ip_prefix: &AwsIpPrefix) -> bool {
+        let (min, max) = match aws_ip_prefix.prefix {
+            IpNetwork::V4(_) => (self.min_ipv4_prefix_len, self.max_ipv4_prefix_len),
+            IpNetwork::V6(_) => (self.min_ipv6_prefix_len, self.max_ipv6_prefix_len),
+        };
+        let prefix_len = aws_ip_prefix.prefix.prefix();
+
+        min.is_none_or(|min| prefix_len >= min) && max.is_none_or(|max| prefix_len <= max)
+    }
+
+    pub(crate) fn match_within(&self, aws_ip_prefix: &AwsIpPrefix) -> bool {
+        if let Some(networks) = &self.within {
+            networks
+                .iter()
+                .any(|network| is_supernet_of(*network, aws_ip_prefix.prefix))
+        } else {
+            trace!("No `within` filter");
+            true
+        }
+    }
+
     pub(crate) fn include_prefix(&self, prefix: &AwsIpPrefix) -> bool {
         let filters = [
+            Filter::match_exclusions,
             Filter::match_prefix_type,
             Filter::match_regions,
             Filter::match_network_border_groups,
             Filter::match_services,
+            Filter::match_prefix_len,
+            Filter::match_within,
         ];
         filters.iter().all(|filter| filter(self, prefix))
     }
@@ -308,6 +613,13 @@ mod tests {
       Test Getter and Setter Methods
     -------------------------------------------------------------------------*/
 
+    #[test]
+    fn test_filter_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Filter>();
+        assert_send_sync::<FilterBuilder>();
+    }
+
     #[test]
     fn test_getter_and_setter_methods() {
         let aws_ip_ranges = test_aws_ip_ranges();
@@ -356,6 +668,105 @@ mod tests {
         assert!(filter3.services().is_none());
     }
 
+    #[test]
+    fn test_filter_serde_round_trip() {
+        let aws_ip_ranges = test_aws_ip_ranges();
+        let filter = FilterBuilder::new(&aws_ip_ranges)
+            .ipv6()
+            .services(["S3"])
+            .unwrap()
+            .build();
+
+        let value = serde_json::to_value(&filter).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "prefix_type": "ipv6",
+                "regions": null,
+                "network_border_groups": null,
+                "services": ["S3"],
+                "exclude_regions": null,
+                "exclude_network_border_groups": null,
+                "exclude_services": null,
+                "exclude_amazon_only": false,
+                "min_ipv4_prefix_len": null,
+                "max_ipv4_prefix_len": null,
+                "min_ipv6_prefix_len": null,
+                "max_ipv6_prefix_len": null,
+                "within": null,
+                "not_within": null,
+            })
+        );
+
+        let deserialized: Filter = serde_json::from_value(value).unwrap();
+        assert_eq!(
+            aws_ip_ranges.filter(&deserialized).prefixes(),
+            aws_ip_ranges.filter(&filter).prefixes()
+        );
+    }
+
+    #[test]
+    fn test_filter_value_patterns() {
+        let aws_ip_ranges = test_aws_ip_ranges();
+
+        let filter = FilterBuilder::new(&aws_ip_ranges)
+            .regions(["US-*"])
+            .unwrap()
+            .network_border_groups(["*-east-?"])
+            .unwrap()
+            .services(["s3", "ec2"])
+            .unwrap()
+            .exclude_services(["S?"])
+            .unwrap()
+            .build();
+
+        assert_eq!(filter.regions().unwrap().len(), 2);
+        assert_eq!(filter.network_border_groups().unwrap().len(), 1);
+        assert_eq!(filter.services().unwrap().len(), 2);
+        assert_eq!(filter.exclude_services().unwrap().len(), 1);
+
+        let result = FilterBuilder::new(&aws_ip_ranges).regions(["us-*", "eu-*"]);
+        assert!(matches!(
+            result,
+            Err(Error::UnknownFilterValue {
+                kind: FilterValueKind::Region,
+                value,
+            }) if value == "eu-*"
+        ));
+    }
+
+    #[test]
+    fn test_unknown_filter_values() {
+        let aws_ip_ranges = test_aws_ip_ranges();
+
+        let result = FilterBuilder::new(&aws_ip_ranges).regions(["eu-west-1"]);
+        assert!(matches!(
+            result,
+            Err(Error::UnknownFilterValue {
+                kind: FilterValueKind::Region,
+                value,
+            }) if value == "eu-west-1"
+        ));
+
+        let result = FilterBuilder::new(&aws_ip_ranges).network_border_groups(["us-west-2-lax-1"]);
+        assert!(matches!(
+            result,
+            Err(Error::UnknownFilterValue {
+                kind: FilterValueKind::NetworkBorderGroup,
+                ..
+            })
+        ));
+
+        let result = FilterBuilder::new(&aws_ip_ranges).services(["ROUTE53"]);
+        assert!(matches!(
+            result,
+            Err(Error::UnknownFilterValue {
+                kind: FilterValueKind::Service,
+                ..
+            })
+        ));
+    }
+
     /*-------------------------------------------------------------------------
       Test Filter Functions
     -------------------------------------------------------------------------*/
@@ -392,7 +803,7 @@ mod tests {
 
         let prefix1 = test_aws_ipv4_prefix();
         let prefix2 = AwsIpPrefix {
-            region: Rc::from("us-west-1"),
+            region: Arc::from("us-west-1"),
             ..test_aws_ipv4_prefix()
         };
 
@@ -415,7 +826,7 @@ mod tests {
 
         let prefix1 = test_aws_ipv4_prefix();
         let prefix2 = AwsIpPrefix {
-            network_border_group: Rc::from("us-west-1"),
+            network_border_group: Arc::from("us-west-1"),
             ..test_aws_ipv4_prefix()
         };
 
@@ -438,11 +849,11 @@ mod tests {
         let no_service_filter = FilterBuilder::new(&aws_ip_ranges).build();
 
         let prefix1 = AwsIpPrefix {
-            services: [Rc::from("EC2"), Rc::from("S3")].into_iter().collect(),
+            services: [Arc::from("EC2"), Arc::from("S3")].into_iter().collect(),
             ..test_aws_ipv4_prefix()
         };
         let prefix2 = AwsIpPrefix {
-            services: [Rc::from("S3")].into_iter().collect(),
+            services: [Arc::from("S3")].into_iter().collect(),
             ..test_aws_ipv4_prefix()
         };
 
@@ -452,4 +863,201 @@ mod tests {
         assert!(no_service_filter.match_services(&prefix1)); // No service filter matches any prefix
         assert!(no_service_filter.match_services(&prefix2)); // No service filter matches any prefix
     }
+
+    #[test]
+    fn test_filter_match_exclusions() {
+        let aws_ip_ranges = test_aws_ip_ranges();
+
+        let exclusion_filter = FilterBuilder::new(&aws_ip_ranges)
+            .exclude_regions(["us-west-1"])
+            .unwrap()
+            .exclude_network_border_groups(["us-east-1"])
+            .unwrap()
+            .exclude_services(["S3"])
+            .unwrap()
+            .build();
+        let no_exclusion_filter = Filter::default();
+
+        let prefix1 = AwsIpPrefix {
+            network_border_group: Arc::from("us-east-1-bos-1"),
+            ..test_aws_ipv4_prefix()
+        };
+        let prefix2 = AwsIpPrefix {
+            region: Arc::from("us-west-1"),
+            ..prefix1.clone()
+        };
+        let prefix3 = test_aws_ipv4_prefix();
+        let prefix4 = AwsIpPrefix {
+            services: [Arc::from("EC2"), Arc::from("S3")].into_iter().collect(),
+            ..prefix1.clone()
+        };
+
+        assert!(exclusion_filter.match_exclusions(&prefix1)); // Prefix matches no exclusions
+        assert!(!exclusion_filter.match_exclusions(&prefix2)); // Excluded region
+        assert!(!exclusion_filter.match_exclusions(&prefix3)); // Excluded network border group
+        assert!(!exclusion_filter.match_exclusions(&prefix4)); // Any excluded service excludes the prefix
+
+        assert!(no_exclusion_filter.match_exclusions(&prefix2)); // No exclusion filter matches any prefix
+        assert!(no_exclusion_filter.match_exclusions(&prefix4)); // No exclusion filter matches any prefix
+    }
+
+    #[test]
+    fn test_filter_exclusions_take_precedence() {
+        let aws_ip_ranges = test_aws_ip_ranges();
+
+        let filter = FilterBuilder::new(&aws_ip_ranges)
+            .regions(["us-west-1"])
+            .unwrap()
+            .exclude_regions(["us-west-1"])
+            .unwrap()
+            .build();
+        assert!(aws_ip_ranges.filter(&filter).prefixes().is_empty());
+
+        let filtered_ranges = FilterBuilder::new(&aws_ip_ranges)
+            .services(["EC2"])
+            .unwrap()
+            .exclude_services(["S3"])
+            .unwrap()
+            .filter();
+        assert_eq!(filtered_ranges.prefixes().len(), 4);
+        assert!(filtered_ranges
+            .regions()
+            .iter()
+            .all(|region| &**region == "us-east-1"));
+    }
+
+    #[test]
+    fn test_filter_exclude_amazon_only() {
+        let aws_ip_ranges = test_aws_ip_ranges();
+
+        let amazon_only_filter = FilterBuilder::new(&aws_ip_ranges)
+            .exclude_amazon_only()
+            .build();
+        assert!(amazon_only_filter.exclude_amazon_only());
+
+        let prefix1 = AwsIpPrefix {
+            services: [Arc::from("AMAZON")].into_iter().collect(),
+            ..test_aws_ipv4_prefix()
+        };
+        let prefix2 = AwsIpPrefix {
+            services: [Arc::from("AMAZON"), Arc::from("EC2")]
+                .into_iter()
+                .collect(),
+            ..test_aws_ipv4_prefix()
+        };
+
+        assert!(!amazon_only_filter.include_prefix(&prefix1)); // Only service is AMAZON
+        assert!(amazon_only_filter.include_prefix(&prefix2)); // Also used by a specific service
+        assert!(Filter::default().include_prefix(&prefix1));
+    }
+
+    #[test]
+    fn test_filter_match_prefix_len() {
+        let aws_ip_ranges = test_aws_ip_ranges();
+
+        let prefix_len_filter = FilterBuilder::new(&aws_ip_ranges)
+            .min_ipv4_prefix_len(12)
+            .unwrap()
+            .max_ipv4_prefix_len(24)
+            .unwrap()
+            .max_ipv6_prefix_len(40)
+            .unwrap()
+            .build();
+        let no_prefix_len_filter = Filter::default();
+
+        let prefix1 = test_aws_ipv4_prefix(); // 10.0.0.0/8
+        let prefix2 = AwsIpPrefix {
+            prefix: "10.0.0.0/24".parse().unwrap(),
+            ..test_aws_ipv4_prefix()
+        };
+        let prefix3 = AwsIpPrefix {
+            prefix: "10.0.0.0/25".parse().unwrap(),
+            ..test_aws_ipv4_prefix()
+        };
+        let prefix4 = test_aws_ipv6_prefix(); // 2001:db8::/32
+        let prefix5 = AwsIpPrefix {
+            prefix: "2001:db8::/48".parse().unwrap(),
+            ..test_aws_ipv6_prefix()
+        };
+
+        assert!(!prefix_len_filter.match_prefix_len(&prefix1)); // Shorter than the IPv4 minimum
+        assert!(prefix_len_filter.match_prefix_len(&prefix2)); // Equal to the IPv4 maximum
+        assert!(!prefix_len_filter.match_prefix_len(&prefix3)); // Longer than the IPv4 maximum
+        assert!(prefix_len_filter.match_prefix_len(&prefix4)); // No IPv6 minimum
+        assert!(!prefix_len_filter.match_prefix_len(&prefix5)); // Longer than the IPv6 maximum
+
+        assert!(no_prefix_len_filter.match_prefix_len(&prefix1)); // No prefix length filter matches any prefix
+        assert!(no_prefix_len_filter.match_prefix_len(&prefix5)); // No prefix length filter matches any prefix
+    }
+
+    #[test]
+    fn test_filter_invalid_prefix_len_range() {
+        let aws_ip_ranges = test_aws_ip_ranges();
+
+        let result = FilterBuilder::new(&aws_ip_ranges)
+            .min_ipv4_prefix_len(24)
+            .unwrap()
+            .max_ipv4_prefix_len(16);
+        assert!(matches!(
+            result,
+            Err(Error::InvalidPrefixLenRange {
+                min: 24,
+                max: 16,
+                ..
+            })
+        ));
+
+        let result = FilterBuilder::new(&aws_ip_ranges)
+            .max_ipv6_prefix_len(32)
+            .unwrap()
+            .min_ipv6_prefix_len(48);
+        assert!(matches!(
+            result,
+            Err(Error::InvalidPrefixLenRange {
+                min: 48,
+                max: 32,
+                ..
+            })
+        ));
+
+        // The IPv4 and IPv6 ranges are independent
+        assert!(FilterBuilder::new(&aws_ip_ranges)
+            .min_ipv4_prefix_len(24)
+            .unwrap()
+            .max_ipv6_prefix_len(16)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_filter_match_within() {
+        let aws_ip_ranges = test_aws_ip_ranges();
+
+        let filtered_ranges = FilterBuilder::new(&aws_ip_ranges)
+            .within("10.0.0.0/8".parse().unwrap())
+            .within("2001:db8::1/33".parse().unwrap()) // Host bits are ignored
+            .not_within("10.1.0.0/16".parse().unwrap())
+            .filter();
+
+        let prefixes: Vec<String> = filtered_ranges
+            .prefixes()
+            .keys()
+            .map(|prefix| prefix.to_string())
+            .collect();
+        assert_eq!(
+            prefixes,
+            [
+                "10.0.0.0/8",
+                "10.0.0.0/16",
+                "2001:db8::/48",
+                "2001:db8:1::/48"
+            ]
+        );
+
+        let filter = FilterBuilder::new(&aws_ip_ranges)
+            .within("10.0.0.0/16".parse().unwrap())
+            .build();
+        assert!(!filter.match_within(&test_aws_ipv4_prefix())); // Supernet is not within the network
+        assert!(!filter.match_within(&test_aws_ipv6_prefix())); // Other address family
+        assert!(Filter::default().match_within(&test_aws_ipv4_prefix()));
+    }
 }
@@ -0,0 +1,288 @@
+use crate::core::errors::{Error, Result};
+use crate::core::utils::ipnetwork::network_prefix;
+use ipnetwork::{IpNetwork, Ipv4Network, Ipv6Network};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/*-------------------------------------------------------------------------------------------------
+  Parse Search Input
+-------------------------------------------------------------------------------------------------*/
+
+/// Parse an IP address or network written in one of the forms commonly produced by network tools
+/// into one or more [IpNetwork] values suitable for searching the AWS IP Ranges.
+///
+/// Supported forms:
+///
+/// - Addresses and CIDRs: `10.0.0.1`, `10.0.0.0/16`, `2600:1f18::/33`
+/// - Address and netmask: `10.0.0.0 255.255.0.0`
+/// - Address ranges: `10.0.0.1-10.0.3.255`; ranges become the minimal set of CIDRs covering
+///   exactly the range
+/// - Trailing IPv4 wildcards: `10.0.*.*`
+/// - IPv4-mapped (`::ffff:3.5.140.2`) and NAT64 (`64:ff9b::352:8c02`) IPv6 addresses become the
+///   embedded IPv4 address
+///
+/// ```rust
+/// # fn main() -> awsipranges::Result<()> {
+/// use ipnetwork::IpNetwork;
+///
+/// let networks = awsipranges::parse_input("10.0.0.1-10.0.0.6")?;
+/// assert_eq!(
+///     networks,
+///     ["10.0.0.1/32", "10.0.0.2/31", "10.0.0.4/31", "10.0.0.6/32"]
+///         .iter()
+///         .map(|network| network.parse::<IpNetwork>().unwrap())
+///         .collect::<Vec<IpNetwork>>()
+/// );
+///
+/// let networks = awsipranges::parse_input("::ffff:3.5.140.2")?;
+/// assert_eq!(networks, vec!["3.5.140.2/32".parse::<IpNetwork>().unwrap()]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn parse_input(value: &str) -> Result<Vec<IpNetwork>> {
+    let value = value.trim();
+    let invalid = |reason: String| Error::InvalidPrefix {
+        value: value.to_string(),
+        reason,
+    };
+
+    let networks = if let Some((start, end)) = value.split_once('-') {
+        let start: IpAddr = start
+            .trim()
+            .parse()
+            .map_err(|error| invalid(format!("invalid range start: {error}")))?;
+        let end: IpAddr = end
+            .trim()
+            .parse()
+            .map_err(|error| invalid(format!("invalid range end: {error}")))?;
+        parse_range(start, end).map_err(invalid)?
+    } else if value.contains('*') {
+        vec![parse_wildcard(value).map_err(invalid)?]
+    } else if let Some((address, netmask)) = value.split_once(char::is_whitespace) {
+        let address: Ipv4Addr = address
+            .parse()
+            .map_err(|error| invalid(format!("invalid address: {error}")))?;
+        let netmask: Ipv4Addr = netmask
+            .trim()
+            .parse()
+            .map_err(|error| invalid(format!("invalid netmask: {error}")))?;
+        let network = Ipv4Network::with_netmask(address, netmask)
+            .map_err(|error| invalid(error.to_string()))?;
+        vec![network_prefix(&IpNetwork::V4(network))]
+    } else {
+        vec![value
+            .parse::<IpNetwork>()
+            .map_err(|error| invalid(error.to_string()))?]
+    };
+
+    Ok(networks.into_iter().map(embedded_ipv4).collect())
+}
+
+/*--------------------------------------------------------------------------------------
+  Helper Functions
+--------------------------------------------------------------------------------------*/
+
+/// Get the minimal set of CIDRs covering exactly the addresses from `start` to `end`.
+fn parse_range(start: IpAddr, end: IpAddr) -> std::result::Result<Vec<IpNetwork>, String> {
+    match (start, end) {
+        (IpAddr::V4(start), IpAddr::V4(end)) if start <= end => {
+            Ok(
+                range_cidrs(u32::from(start).into(), u32::from(end).into(), 32)
+                    .into_iter()
+                    .map(|(network, prefix)| {
+                        let network = Ipv4Addr::from(network as u32);
+                        IpNetwork::V4(Ipv4Network::new(network, prefix).unwrap())
+                    })
+                    .collect(),
+            )
+        }
+        (IpAddr::V6(start), IpAddr::V6(end)) if start <= end => {
+            Ok(range_cidrs(start.into(), end.into(), 128)
+                .into_iter()
+                .map(|(network, prefix)| {
+                    let network = Ipv6Addr::from(network);
+                    IpNetwork::V6(Ipv6Network::new(network, prefix).unwrap())
+                })
+                .collect())
+        }
+        (IpAddr::V4(_), IpAddr::V4(_)) | (IpAddr::V6(_), IpAddr::V6(_)) => {
+            Err("the range start is after the range end".to_string())
+        }
+        _ => Err("the range start and end are different IP versions".to_string()),
+    }
+}
+
+/// Split the range from `start` to `end` into the largest aligned blocks; returns the network
+/// address and prefix length of each block.
+fn range_cidrs(start: u128, end: u128, bits: u32) -> Vec<(u128, u8)> {
+    let mut cidrs: Vec<(u128, u8)> = Vec::new();
+    let mut current = start;
+    loop {
+        let mut host_bits = current.trailing_zeros().min(bits);
+        let mask = |host_bits: u32| {
+            1u128
+                .checked_shl(host_bits)
+                .map_or(u128::MAX, |size| size - 1)
+        };
+        while current | mask(host_bits) > end {
+            host_bits -= 1;
+        }
+
+        cidrs.push((current, (bits - host_bits) as u8));
+
+        let last = current | mask(host_bits);
+        if last >= end {
+            break;
+        }
+        current = last + 1;
+    }
+
+    cidrs
+}
+
+/// Parse an IPv4 address with trailing `*` wildcard octets (`10.0.*.*`).
+fn parse_wildcard(value: &str) -> std::result::Result<IpNetwork, String> {
+    let octets: Vec<&str> = value.split('.').collect();
+    if octets.len() != 4 {
+        return Err("wildcard addresses must have four octets".to_string());
+    }
+
+    let fixed_octets = octets.iter().take_while(|octet| **octet != "*").count();
+    if octets[fixed_octets..].iter().any(|octet| *octet != "*") {
+        return Err("only trailing octets may be `*` wildcards".to_string());
+    }
+
+    let mut address = [0u8; 4];
+    for (index, octet) in octets[..fixed_octets].iter().enumerate() {
+        address[index] = octet
+            .parse()
+            .map_err(|_| format!("invalid octet `{octet}`"))?;
+    }
+
+    let prefix = (fixed_octets * 8) as u8;
+    Ok(IpNetwork::V4(
+        Ipv4Network::new(Ipv4Addr::from(address), prefix).unwrap(),
+    ))
+}
+
+/// Replace IPv4-mapped (`::ffff:0:0/96`) and NAT64 (`64:ff9b::/96`) IPv6 networks with the
+/// embedded IPv4 network.
+fn embedded_ipv4(network: IpNetwork) -> IpNetwork {
+    let IpNetwork::V6(ipv6_network) = network else {
+        return network;
+    };
+    if ipv6_network.prefix() < 96 {
+        return network;
+    }
+
+    let ip = ipv6_network.ip();
+    let ipv4 = match ip.segments() {
+        [0x64, 0xff9b, 0, 0, 0, 0, _, _] => Some(Ipv4Addr::from(u128::from(ip) as u32)),
+        _ => ip.to_ipv4_mapped(),
+    };
+
+    ipv4.map_or(network, |ipv4| {
+        IpNetwork::V4(Ipv4Network::new(ipv4, ipv6_network.prefix() - 96).unwrap())
+    })
+}
+
+/*-------------------------------------------------------------------------------------------------
+  Unit Tests
+-------------------------------------------------------------------------------------------------*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn networks(values: &[&str]) -> Vec<IpNetwork> {
+        values.iter().map(|value| value.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_parse_input_cidr() {
+        assert_eq!(parse_input("10.0.0.1").unwrap(), networks(&["10.0.0.1/32"]));
+        assert_eq!(
+            parse_input(" 2600:1f18::/33 ").unwrap(),
+            networks(&["2600:1f18::/33"])
+        );
+        assert!(matches!(
+            parse_input("10.0.0.0/33"),
+            Err(Error::InvalidPrefix { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_input_netmask() {
+        assert_eq!(
+            parse_input("10.0.1.2 255.255.0.0").unwrap(),
+            networks(&["10.0.0.0/16"])
+        );
+        assert!(parse_input("10.0.0.0 255.0.255.0").is_err()); // Non-contiguous netmask
+        assert!(parse_input("10.0.0.0 nope").is_err());
+    }
+
+    #[test]
+    fn test_parse_input_range() {
+        assert_eq!(
+            parse_input("10.0.0.1-10.0.3.255").unwrap(),
+            networks(&[
+                "10.0.0.1/32",
+                "10.0.0.2/31",
+                "10.0.0.4/30",
+                "10.0.0.8/29",
+                "10.0.0.16/28",
+                "10.0.0.32/27",
+                "10.0.0.64/26",
+                "10.0.0.128/25",
+                "10.0.1.0/24",
+                "10.0.2.0/23",
+            ])
+        );
+        assert_eq!(
+            parse_input("10.0.0.0 - 10.0.255.255").unwrap(),
+            networks(&["10.0.0.0/16"])
+        );
+        assert_eq!(
+            parse_input("0.0.0.0-255.255.255.255").unwrap(),
+            networks(&["0.0.0.0/0"])
+        );
+        assert_eq!(
+            parse_input("::-ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff").unwrap(),
+            networks(&["::/0"])
+        );
+        assert_eq!(
+            parse_input("2001:db8::-2001:db8::1:0").unwrap(),
+            networks(&["2001:db8::/112", "2001:db8::1:0/128"])
+        );
+        assert!(parse_input("10.0.0.2-10.0.0.1").is_err()); // Start after end
+        assert!(parse_input("10.0.0.1-2001:db8::").is_err()); // Mixed IP versions
+    }
+
+    #[test]
+    fn test_parse_input_wildcard() {
+        assert_eq!(parse_input("10.0.*.*").unwrap(), networks(&["10.0.0.0/16"]));
+        assert_eq!(parse_input("*.*.*.*").unwrap(), networks(&["0.0.0.0/0"]));
+        assert!(parse_input("10.*.0.*").is_err()); // Wildcards must be trailing
+        assert!(parse_input("10.0.*").is_err());
+        assert!(parse_input("10.256.*.*").is_err());
+    }
+
+    #[test]
+    fn test_parse_input_embedded_ipv4() {
+        assert_eq!(
+            parse_input("::ffff:3.5.140.2").unwrap(),
+            networks(&["3.5.140.2/32"])
+        );
+        assert_eq!(
+            parse_input("::ffff:3.5.140.0/120").unwrap(),
+            networks(&["3.5.140.0/24"])
+        );
+        assert_eq!(
+            parse_input("64:ff9b::352:8c02").unwrap(),
+            networks(&["3.82.140.2/32"])
+        );
+        assert_eq!(
+            parse_input("64:ff9b::/64").unwrap(), // Shorter than /96; not an embedded address
+            networks(&["64:ff9b::/64"])
+        );
+    }
+}
@@ -1,14 +1,20 @@
-use crate::core::errors::Result;
+use crate::core::aws_ip_ranges::AwsIpRanges;
+use crate::core::errors::{Error, Result};
 use chrono::{DateTime, Utc};
-use ipnetwork::{Ipv4Network, Ipv6Network};
+use ipnetwork::{IpNetwork, Ipv4Network, Ipv6Network};
 use serde::{Deserialize, Serialize};
 
 /*-------------------------------------------------------------------------------------------------
   Parse JSON
 -------------------------------------------------------------------------------------------------*/
 
-pub fn parse(json: &str) -> Result<JsonIpRanges<'_>> {
-    Ok(serde_json::from_str(json)?)
+/// Parse the AWS IP Ranges JSON; `origin` describes where the JSON came from
+/// (a URL, file path, etc.) and is included in any returned error.
+pub fn parse<'j>(json: &'j str, origin: &str) -> Result<JsonIpRanges<'j>> {
+    serde_json::from_str(json).map_err(|error| Error::Parse {
+        origin: origin.to_string(),
+        source: error,
+    })
 }
 
 /*-------------------------------------------------------------------------------------------------
@@ -56,6 +62,46 @@ pub struct JsonIpv6Prefix<'j> {
     pub service: &'j str,
 }
 
+/*-------------------------------------------------------------------------------------------------
+  JSON IP Ranges from AWS IP Ranges
+-------------------------------------------------------------------------------------------------*/
+
+/// Convert [AwsIpRanges] into the native AWS IP Ranges JSON data structures; a record is created
+/// for each service that uses a prefix.
+impl<'j> From<&'j AwsIpRanges> for JsonIpRanges<'j> {
+    fn from(aws_ip_ranges: &'j AwsIpRanges) -> Self {
+        let mut json_ip_ranges = JsonIpRanges {
+            sync_token: &aws_ip_ranges.sync_token,
+            create_date: aws_ip_ranges.create_date,
+            prefixes: Vec::new(),
+            ipv6_prefixes: Vec::new(),
+        };
+
+        for aws_ip_prefix in aws_ip_ranges.prefixes.values() {
+            for service in aws_ip_prefix.services.iter() {
+                match aws_ip_prefix.prefix {
+                    IpNetwork::V4(ip_prefix) => json_ip_ranges.prefixes.push(JsonIpPrefix {
+                        ip_prefix,
+                        region: &aws_ip_prefix.region,
+                        network_border_group: &aws_ip_prefix.network_border_group,
+                        service,
+                    }),
+                    IpNetwork::V6(ipv6_prefix) => {
+                        json_ip_ranges.ipv6_prefixes.push(JsonIpv6Prefix {
+                            ipv6_prefix,
+                            region: &aws_ip_prefix.region,
+                            network_border_group: &aws_ip_prefix.network_border_group,
+                            service,
+                        })
+                    }
+                }
+            }
+        }
+
+        json_ip_ranges
+    }
+}
+
 /*-------------------------------------------------------------------------------------------------
   Unit Tests
 -------------------------------------------------------------------------------------------------*/
@@ -118,6 +164,37 @@ mod tests {
         assert_eq!(deserialized_value, expected_value);
     }
 
+    #[test]
+    fn test_json_ip_ranges_from_aws_ip_ranges() {
+        use crate::core::aws_ip_ranges::tests::test_aws_ip_ranges;
+
+        let aws_ip_ranges = test_aws_ip_ranges();
+        let json_ip_ranges = JsonIpRanges::from(&*aws_ip_ranges);
+
+        // One record per service; 10.1.0.0/16 and 2001:db8:1::/48 are used by EC2 and S3
+        assert_eq!(json_ip_ranges.sync_token, aws_ip_ranges.sync_token());
+        assert_eq!(json_ip_ranges.prefixes.len(), 4);
+        assert_eq!(json_ip_ranges.ipv6_prefixes.len(), 4);
+        assert_eq!(
+            json_ip_ranges
+                .prefixes
+                .iter()
+                .filter(|prefix| prefix.ip_prefix.to_string() == "10.1.0.0/16")
+                .map(|prefix| prefix.service)
+                .collect::<Vec<&str>>(),
+            vec!["EC2", "S3"]
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_json() {
+        let result = parse(r#"{"syncToken": "1640995200"}"#, "test.json");
+        assert!(matches!(
+            result,
+            Err(Error::Parse { origin, .. }) if origin == "test.json"
+        ));
+    }
+
     #[test]
     fn test_json_ip_prefix() {
         let json_str = r#"{
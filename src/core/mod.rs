@@ -2,13 +2,20 @@
   Core Modules
 -------------------------------------------------------------------------------------------------*/
 
+pub mod attribution;
 pub mod aws_ip_prefix;
 pub mod aws_ip_ranges;
+pub mod cache_info;
 pub mod client;
 pub mod datetime;
 pub mod errors;
 pub mod filter;
+pub mod input;
 pub mod json;
+pub mod prefix_trie;
 pub mod prefix_type;
+pub mod provenance;
+pub mod ranges_diff;
+pub mod ranges_stats;
 pub mod search_results;
 pub mod utils;
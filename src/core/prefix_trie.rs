@@ -0,0 +1,440 @@
+use ipnetwork::IpNetwork;
+use std::fmt;
+
+/*-------------------------------------------------------------------------------------------------
+  Prefix Trie
+-------------------------------------------------------------------------------------------------*/
+
+/// Path-compressed binary (Patricia) trie indexing IPv4 and IPv6 prefixes.
+///
+/// The trie answers longest-match, supernet, and subnet queries in time proportional to the
+/// prefix length (plus the number of results returned), independent of the number of indexed
+/// prefixes. Each indexed prefix is stored as the [IpNetwork] value it was inserted with, so query
+/// results can be used as keys into the map the index was built from.
+#[derive(Clone, Default)]
+pub(crate) struct PrefixTrie {
+    ipv4: Trie,
+    ipv6: Trie,
+}
+
+/*--------------------------------------------------------------------------------------
+  Prefix Trie Implementation
+--------------------------------------------------------------------------------------*/
+
+impl PrefixTrie {
+    /// Index a prefix.
+    pub(crate) fn insert(&mut self, prefix: IpNetwork) {
+        let (bits, len) = key(&prefix);
+        self.trie_mut(&prefix).insert(bits, len, prefix);
+    }
+
+    /// Get the most specific indexed prefix containing the provided prefix.
+    pub(crate) fn longest_match(&self, prefix: &IpNetwork) -> Option<IpNetwork> {
+        let (bits, len) = key(prefix);
+        self.trie(prefix)
+            .supernets(bits, len)
+            .pop()
+            .map(|(_, value)| value)
+    }
+
+    /// Get all indexed prefixes containing (or equal to) the provided prefix,
+    /// ordered from the least to the most specific.
+    pub(crate) fn supernets(&self, prefix: &IpNetwork) -> Vec<IpNetwork> {
+        let (bits, len) = key(prefix);
+        self.trie(prefix)
+            .supernets(bits, len)
+            .into_iter()
+            .map(|(_, value)| value)
+            .collect()
+    }
+
+    /// Get all indexed prefixes contained in (or equal to) the provided prefix.
+    pub(crate) fn subnets(&self, prefix: &IpNetwork) -> Vec<IpNetwork> {
+        let (bits, len) = key(prefix);
+        self.trie(prefix).subnets(bits, len)
+    }
+
+    /// Number of indexed prefixes.
+    pub(crate) fn len(&self) -> usize {
+        self.ipv4.len() + self.ipv6.len()
+    }
+
+    fn trie(&self, prefix: &IpNetwork) -> &Trie {
+        match prefix {
+            IpNetwork::V4(_) => &self.ipv4,
+            IpNetwork::V6(_) => &self.ipv6,
+        }
+    }
+
+    fn trie_mut(&mut self, prefix: &IpNetwork) -> &mut Trie {
+        match prefix {
+            IpNetwork::V4(_) => &mut self.ipv4,
+            IpNetwork::V6(_) => &mut self.ipv6,
+        }
+    }
+}
+
+impl FromIterator<IpNetwork> for PrefixTrie {
+    fn from_iter<I: IntoIterator<Item = IpNetwork>>(iter: I) -> Self {
+        let mut prefix_trie = PrefixTrie::default();
+        iter.into_iter()
+            .for_each(|prefix| prefix_trie.insert(prefix));
+        prefix_trie
+    }
+}
+
+impl fmt::Debug for PrefixTrie {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrefixTrie")
+            .field("prefixes", &self.len())
+            .finish()
+    }
+}
+
+/*--------------------------------------------------------------------------------------
+  Trie (Single Address Family)
+--------------------------------------------------------------------------------------*/
+
+/*
+    Prefixes are stored as left-aligned 128-bit keys (IPv4 addresses occupy the
+    most significant 32 bits) with the host bits set to `0`. Node `0` is the
+    root and always represents the zero-length prefix.
+*/
+
+#[derive(Clone)]
+struct Trie {
+    nodes: Vec<Node>,
+}
+
+#[derive(Clone)]
+struct Node {
+    bits: u128,
+    len: u8,
+    children: [Option<usize>; 2],
+    value: Option<IpNetwork>,
+}
+
+impl Default for Trie {
+    fn default() -> Self {
+        Self {
+            nodes: vec![Node::new(0, 0, None)],
+        }
+    }
+}
+
+impl Node {
+    fn new(bits: u128, len: u8, value: Option<IpNetwork>) -> Self {
+        Self {
+            bits,
+            len,
+            children: [None, None],
+            value,
+        }
+    }
+}
+
+impl Trie {
+    fn len(&self) -> usize {
+        self.nodes
+            .iter()
+            .filter(|node| node.value.is_some())
+            .count()
+    }
+
+    fn push(&mut self, node: Node) -> usize {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    fn insert(&mut self, bits: u128, len: u8, value: IpNetwork) {
+        let mut index = 0;
+        loop {
+            // Invariant: the node at `index` is a prefix of the key
+            let node = &self.nodes[index];
+            if node.len == len {
+                self.nodes[index].value = Some(value);
+                return;
+            }
+
+            let branch = bit(bits, node.len);
+            let Some(child_index) = node.children[branch] else {
+                let leaf_index = self.push(Node::new(bits, len, Some(value)));
+                self.nodes[index].children[branch] = Some(leaf_index);
+                return;
+            };
+
+            let child = &self.nodes[child_index];
+            let common = common_len(bits, child.bits, len.min(child.len));
+
+            if common == child.len {
+                // The child is a prefix of the key; descend
+                index = child_index;
+            } else if common == len {
+                // The key is a prefix of the child; insert the key between the node and child
+                let mut new_node = Node::new(bits, len, Some(value));
+                new_node.children[bit(child.bits, len)] = Some(child_index);
+                let new_index = self.push(new_node);
+                self.nodes[index].children[branch] = Some(new_index);
+                return;
+            } else {
+                // The key and child diverge; insert a fork node at the divergence point
+                let mut fork = Node::new(mask(bits, common), common, None);
+                fork.children[bit(child.bits, common)] = Some(child_index);
+                let leaf_index = self.push(Node::new(bits, len, Some(value)));
+                fork.children[bit(bits, common)] = Some(leaf_index);
+                let fork_index = self.push(fork);
+                self.nodes[index].children[branch] = Some(fork_index);
+                return;
+            }
+        }
+    }
+
+    /// Values (and their prefix lengths) of the nodes on the path to the key,
+    /// ordered from the least to the most specific.
+    fn supernets(&self, bits: u128, len: u8) -> Vec<(u8, IpNetwork)> {
+        let mut supernets = Vec::new();
+        let mut index = 0;
+        loop {
+            let node = &self.nodes[index];
+            if let Some(value) = node.value {
+                supernets.push((node.len, value));
+            }
+
+            if node.len >= len {
+                break;
+            }
+
+            let Some(child_index) = node.children[bit(bits, node.len)] else {
+                break;
+            };
+
+            let child = &self.nodes[child_index];
+            if child.len > len || common_len(bits, child.bits, child.len) < child.len {
+                break;
+            }
+
+            index = child_index;
+        }
+        supernets
+    }
+
+    /// Values of all nodes at or below the key.
+    fn subnets(&self, bits: u128, len: u8) -> Vec<IpNetwork> {
+        // Find the first node at or below the key
+        let mut index = 0;
+        while self.nodes[index].len < len {
+            let node = &self.nodes[index];
+            let Some(child_index) = node.children[bit(bits, node.len)] else {
+                return Vec::new();
+            };
+
+            let child = &self.nodes[child_index];
+            let compare_len = len.min(child.len);
+            if common_len(bits, child.bits, compare_len) < compare_len {
+                return Vec::new();
+            }
+
+            index = child_index;
+        }
+
+        // Collect the values in the subtree (depth first, lower addresses first)
+        let mut subnets = Vec::new();
+        let mut stack = vec![index];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if let Some(value) = node.value {
+                subnets.push(value);
+            }
+            stack.extend(node.children.iter().rev().flatten());
+        }
+        subnets
+    }
+}
+
+/*--------------------------------------------------------------------------------------
+  Key Helper Functions
+--------------------------------------------------------------------------------------*/
+
+/// Left-aligned network bits and prefix length of an [IpNetwork].
+fn key(prefix: &IpNetwork) -> (u128, u8) {
+    match prefix {
+        IpNetwork::V4(ipv4_network) => (
+            (u32::from(ipv4_network.network()) as u128) << 96,
+            ipv4_network.prefix(),
+        ),
+        IpNetwork::V6(ipv6_network) => (u128::from(ipv6_network.network()), ipv6_network.prefix()),
+    }
+}
+
+/// Keep the first `len` bits.
+fn mask(bits: u128, len: u8) -> u128 {
+    match len {
+        0 => 0,
+        _ => bits & (u128::MAX << (128 - len as u32)),
+    }
+}
+
+/// Value of the bit at `position` (counted from the most significant bit).
+fn bit(bits: u128, position: u8) -> usize {
+    ((bits >> (127 - position as u32)) & 1) as usize
+}
+
+/// Number of leading bits shared by `a` and `b`, up to `max_len`.
+fn common_len(a: u128, b: u128, max_len: u8) -> u8 {
+    ((a ^ b).leading_zeros() as u8).min(max_len)
+}
+
+/*-------------------------------------------------------------------------------------------------
+  Unit Tests
+-------------------------------------------------------------------------------------------------*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::utils::ipnetwork::{is_supernet_of, network_prefix};
+
+    fn test_prefix_trie() -> PrefixTrie {
+        [
+            "0.0.0.0/0",
+            "10.0.0.0/8",
+            "10.0.0.0/16",
+            "10.1.0.0/16",
+            "10.1.2.0/24",
+            "192.168.0.0/24",
+            "2001:db8::/32",
+            "2001:db8::/48",
+            "2001:db8:1::/48",
+        ]
+        .into_iter()
+        .map(|prefix| prefix.parse().unwrap())
+        .collect()
+    }
+
+    fn parse(prefixes: &[&str]) -> Vec<IpNetwork> {
+        prefixes
+            .iter()
+            .map(|prefix| prefix.parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_prefix_trie_len() {
+        assert_eq!(test_prefix_trie().len(), 9);
+        assert_eq!(PrefixTrie::default().len(), 0);
+    }
+
+    #[test]
+    fn test_prefix_trie_longest_match() {
+        let prefix_trie = test_prefix_trie();
+
+        assert_eq!(
+            prefix_trie.longest_match(&"10.1.2.3/32".parse().unwrap()),
+            Some("10.1.2.0/24".parse().unwrap())
+        );
+        assert_eq!(
+            prefix_trie.longest_match(&"10.2.0.1/32".parse().unwrap()),
+            Some("10.0.0.0/8".parse().unwrap())
+        );
+        assert_eq!(
+            prefix_trie.longest_match(&"172.16.0.1/32".parse().unwrap()),
+            Some("0.0.0.0/0".parse().unwrap()) // Prefixes shorter than /8 are found
+        );
+        assert_eq!(
+            prefix_trie.longest_match(&"2001:db8:1::1/128".parse().unwrap()),
+            Some("2001:db8:1::/48".parse().unwrap())
+        );
+        assert_eq!(
+            prefix_trie.longest_match(&"2001:face::1/128".parse().unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_prefix_trie_supernets() {
+        let prefix_trie = test_prefix_trie();
+
+        assert_eq!(
+            prefix_trie.supernets(&"10.1.2.0/24".parse().unwrap()),
+            parse(&["0.0.0.0/0", "10.0.0.0/8", "10.1.0.0/16", "10.1.2.0/24"])
+        );
+        assert_eq!(
+            prefix_trie.supernets(&"2001:db8::1/128".parse().unwrap()),
+            parse(&["2001:db8::/32", "2001:db8::/48"])
+        );
+        assert!(prefix_trie
+            .supernets(&"2001:face::/32".parse().unwrap())
+            .is_empty());
+    }
+
+    #[test]
+    fn test_prefix_trie_subnets() {
+        let prefix_trie = test_prefix_trie();
+
+        assert_eq!(
+            prefix_trie.subnets(&"10.0.0.0/8".parse().unwrap()),
+            parse(&["10.0.0.0/8", "10.0.0.0/16", "10.1.0.0/16", "10.1.2.0/24"])
+        );
+        assert_eq!(
+            prefix_trie.subnets(&"10.1.0.0/16".parse().unwrap()),
+            parse(&["10.1.0.0/16", "10.1.2.0/24"])
+        );
+        assert_eq!(
+            prefix_trie.subnets(&"2001:db8::/31".parse().unwrap()),
+            parse(&["2001:db8::/32", "2001:db8::/48", "2001:db8:1::/48"])
+        );
+        assert!(prefix_trie
+            .subnets(&"10.2.0.0/16".parse().unwrap())
+            .is_empty());
+    }
+
+    #[test]
+    fn test_prefix_trie_matches_linear_scan() {
+        // Deterministic pseudo-random prefixes (linear congruential generator)
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = move || {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            state
+        };
+
+        let prefixes: Vec<IpNetwork> = (0..2000)
+            .map(|_| {
+                let address = std::net::Ipv4Addr::from((next() >> 32) as u32 & 0x0fff_ffff);
+                let len = (next() % 29) as u8;
+                IpNetwork::V4(ipnetwork::Ipv4Network::new(address, len).unwrap())
+            })
+            .map(|prefix| network_prefix(&prefix))
+            .collect();
+        let prefix_trie: PrefixTrie = prefixes.iter().copied().collect();
+
+        for _ in 0..500 {
+            let address = std::net::Ipv4Addr::from((next() >> 32) as u32 & 0x0fff_ffff);
+            let query = network_prefix(&IpNetwork::V4(
+                ipnetwork::Ipv4Network::new(address, (next() % 33) as u8).unwrap(),
+            ));
+
+            let mut expected_supernets: Vec<IpNetwork> = prefixes
+                .iter()
+                .copied()
+                .filter(|prefix| is_supernet_of(*prefix, query))
+                .collect();
+            expected_supernets.sort_by_key(|prefix| prefix.prefix());
+            expected_supernets.dedup();
+
+            let mut expected_subnets: Vec<IpNetwork> = prefixes
+                .iter()
+                .copied()
+                .filter(|prefix| is_supernet_of(query, *prefix))
+                .collect();
+            expected_subnets.sort();
+            expected_subnets.dedup();
+
+            let mut actual_subnets = prefix_trie.subnets(&query);
+            actual_subnets.sort();
+
+            assert_eq!(prefix_trie.supernets(&query), expected_supernets);
+            assert_eq!(actual_subnets, expected_subnets);
+        }
+    }
+}
@@ -1,9 +1,12 @@
+use serde::{Deserialize, Serialize};
+
 /*-------------------------------------------------------------------------------------------------
   Prefix Type
 -------------------------------------------------------------------------------------------------*/
 
 /// IP prefix type (IPv4 or IPv6) used to filter the AWS IP Prefixes.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub(crate) enum PrefixType {
     IPv4,
     IPv6,
@@ -0,0 +1,75 @@
+use chrono::{DateTime, Utc};
+use std::fmt;
+use std::time::Duration;
+
+/*-------------------------------------------------------------------------------------------------
+  Provenance
+-------------------------------------------------------------------------------------------------*/
+
+/// Where and when loaded AWS IP Ranges came from; see [AwsIpRanges::provenance].
+///
+/// [AwsIpRanges::provenance]: crate::AwsIpRanges::provenance
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Provenance {
+    /// Source of the AWS IP Ranges JSON.
+    pub source: DataSource,
+
+    /// URL or file path the AWS IP Ranges JSON was read from.
+    pub location: String,
+
+    /// Time the AWS IP Ranges JSON was retrieved or read.
+    pub fetched_at: DateTime<Utc>,
+
+    /// Elapsed time since the cache file was last modified; `None` unless the AWS IP Ranges came
+    /// from the cache file.
+    pub cache_age: Option<Duration>,
+
+    /// HTTP status code of the last response from the URL; `None` when the URL was not requested
+    /// or no response was received.
+    pub http_status: Option<u16>,
+
+    /// Number of attempts made to request the AWS IP Ranges from the URL; `0` when the URL was not
+    /// requested.
+    pub attempts: u32,
+}
+
+/// Sources of AWS IP Ranges JSON.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum DataSource {
+    /// Requested from the URL.
+    Network,
+
+    /// Read from a cache file within its cache time.
+    FreshCache,
+
+    /// Read from a cache file older than its cache time; the AWS IP Ranges may be out of date.
+    StaleCache,
+
+    /// Read from an `ip-ranges.json` file with [AwsIpRanges::from_file].
+    ///
+    /// [AwsIpRanges::from_file]: crate::AwsIpRanges::from_file
+    File,
+}
+
+/*--------------------------------------------------------------------------------------
+  Provenance Implementation
+--------------------------------------------------------------------------------------*/
+
+impl Provenance {
+    /// Whether the AWS IP Ranges came from a stale cache file and may be out of date.
+    pub fn is_stale(&self) -> bool {
+        self.source == DataSource::StaleCache
+    }
+}
+
+impl fmt::Display for DataSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataSource::Network => write!(f, "network"),
+            DataSource::FreshCache => write!(f, "fresh cache"),
+            DataSource::StaleCache => write!(f, "stale cache"),
+            DataSource::File => write!(f, "file"),
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use crate::core::aws_ip_prefix::AwsIpPrefix;
+use chrono::{DateTime, Utc};
+use ipnetwork::IpNetwork;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+
+/*-------------------------------------------------------------------------------------------------
+  Ranges Diff
+-------------------------------------------------------------------------------------------------*/
+
+/// Differences between two AWS IP Ranges datasets, created with [AwsIpRanges::diff]; maps of
+/// added, removed, and changed prefixes keyed by [IpNetwork] along with the old and new sync
+/// tokens and create dates.
+///
+/// [AwsIpRanges::diff]: crate::AwsIpRanges::diff
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RangesDiff {
+    /// Sync token of the old AWS IP Ranges.
+    pub old_sync_token: String,
+
+    /// Sync token of the new AWS IP Ranges.
+    pub new_sync_token: String,
+
+    /// Create date of the old AWS IP Ranges.
+    pub old_create_date: DateTime<Utc>,
+
+    /// Create date of the new AWS IP Ranges.
+    pub new_create_date: DateTime<Utc>,
+
+    /// Prefixes present in the new AWS IP Ranges but not in the old.
+    pub added: BTreeMap<IpNetwork, AwsIpPrefix>,
+
+    /// Prefixes present in the old AWS IP Ranges but not in the new.
+    pub removed: BTreeMap<IpNetwork, AwsIpPrefix>,
+
+    /// Prefixes present in both AWS IP Ranges whose region, network border group, or services
+    /// changed.
+    pub changed: BTreeMap<IpNetwork, PrefixChange>,
+}
+
+/*--------------------------------------------------------------------------------------
+  Ranges Diff Implementation
+--------------------------------------------------------------------------------------*/
+
+impl RangesDiff {
+    /// Returns `true` when no prefixes were added, removed, or changed.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/*-------------------------------------------------------------------------------------------------
+  Prefix Change
+-------------------------------------------------------------------------------------------------*/
+
+/// The old and new [AwsIpPrefix] records for a prefix whose metadata changed between two AWS IP
+/// Ranges datasets.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PrefixChange {
+    /// Record from the old AWS IP Ranges.
+    pub old: AwsIpPrefix,
+
+    /// Record from the new AWS IP Ranges.
+    pub new: AwsIpPrefix,
+}
+
+/*--------------------------------------------------------------------------------------
+  Prefix Change Implementation
+--------------------------------------------------------------------------------------*/
+
+impl PrefixChange {
+    /// Returns `true` when the prefix moved to a different region.
+    pub fn region_changed(&self) -> bool {
+        self.old.region != self.new.region
+    }
+
+    /// Returns `true` when the prefix moved to a different network border group.
+    pub fn network_border_group_changed(&self) -> bool {
+        self.old.network_border_group != self.new.network_border_group
+    }
+
+    /// Services that started using the prefix.
+    pub fn services_added(&self) -> BTreeSet<Arc<str>> {
+        self.new
+            .services
+            .difference(&self.old.services)
+            .cloned()
+            .collect()
+    }
+
+    /// Services that stopped using the prefix.
+    pub fn services_removed(&self) -> BTreeSet<Arc<str>> {
+        self.old
+            .services
+            .difference(&self.new.services)
+            .cloned()
+            .collect()
+    }
+}
+
+/*-------------------------------------------------------------------------------------------------
+  Unit Tests
+-------------------------------------------------------------------------------------------------*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::aws_ip_prefix::tests::test_aws_ipv4_prefix;
+
+    #[test]
+    fn test_ranges_diff_is_empty() {
+        let mut ranges_diff = RangesDiff::default();
+        assert!(ranges_diff.is_empty());
+
+        let aws_ip_prefix = test_aws_ipv4_prefix();
+        ranges_diff
+            .removed
+            .insert(aws_ip_prefix.prefix, aws_ip_prefix);
+        assert!(!ranges_diff.is_empty());
+    }
+
+    #[test]
+    fn test_prefix_change() {
+        let prefix_change = PrefixChange {
+            old: test_aws_ipv4_prefix(),
+            new: AwsIpPrefix {
+                network_border_group: Arc::from("us-east-1-bos-1"),
+                services: [Arc::from("AMAZON"), Arc::from("EC2")]
+                    .into_iter()
+                    .collect(),
+                ..test_aws_ipv4_prefix()
+            },
+        };
+
+        assert!(!prefix_change.region_changed());
+        assert!(prefix_change.network_border_group_changed());
+        assert_eq!(
+            prefix_change.services_added(),
+            [Arc::from("AMAZON")].into_iter().collect()
+        );
+        assert!(prefix_change.services_removed().is_empty());
+    }
+}
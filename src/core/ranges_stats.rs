@@ -0,0 +1,209 @@
+use crate::core::aws_ip_prefix::AwsIpPrefix;
+use crate::core::utils::ipnetwork::{address_count, aggregate};
+use ipnetwork::IpNetwork;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/*-------------------------------------------------------------------------------------------------
+  Ranges Stats
+-------------------------------------------------------------------------------------------------*/
+
+/// Address-space statistics for AWS IP Ranges, created with [AwsIpRanges::stats]; statistics for
+/// all prefixes along with breakdowns by region, network border group, and service.
+///
+/// [AwsIpRanges::stats]: crate::AwsIpRanges::stats
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RangesStats {
+    /// Statistics for all prefixes.
+    pub all: FamilyStats,
+
+    /// Statistics for the prefixes in each AWS region.
+    pub by_region: BTreeMap<Arc<str>, FamilyStats>,
+
+    /// Statistics for the prefixes in each network border group.
+    pub by_network_border_group: BTreeMap<Arc<str>, FamilyStats>,
+
+    /// Statistics for the prefixes used by each AWS service. Prefixes used by multiple services
+    /// are counted for each service.
+    pub by_service: BTreeMap<Arc<str>, FamilyStats>,
+}
+
+/*--------------------------------------------------------------------------------------
+  Ranges Stats Implementation
+--------------------------------------------------------------------------------------*/
+
+impl RangesStats {
+    pub(crate) fn new<'p, I>(aws_ip_prefixes: I) -> Self
+    where
+        I: IntoIterator<Item = &'p AwsIpPrefix>,
+    {
+        let mut all: Vec<IpNetwork> = Vec::new();
+        let mut by_region: BTreeMap<Arc<str>, Vec<IpNetwork>> = BTreeMap::new();
+        let mut by_network_border_group: BTreeMap<Arc<str>, Vec<IpNetwork>> = BTreeMap::new();
+        let mut by_service: BTreeMap<Arc<str>, Vec<IpNetwork>> = BTreeMap::new();
+
+        for aws_ip_prefix in aws_ip_prefixes {
+            all.push(aws_ip_prefix.prefix);
+            by_region
+                .entry(aws_ip_prefix.region.clone())
+                .or_default()
+                .push(aws_ip_prefix.prefix);
+            by_network_border_group
+                .entry(aws_ip_prefix.network_border_group.clone())
+                .or_default()
+                .push(aws_ip_prefix.prefix);
+            for service in aws_ip_prefix.services.iter() {
+                by_service
+                    .entry(service.clone())
+                    .or_default()
+                    .push(aws_ip_prefix.prefix);
+            }
+        }
+
+        fn group_stats(
+            groups: BTreeMap<Arc<str>, Vec<IpNetwork>>,
+        ) -> BTreeMap<Arc<str>, FamilyStats> {
+            groups
+                .into_iter()
+                .map(|(key, prefixes)| (key, FamilyStats::new(&prefixes)))
+                .collect()
+        }
+
+        Self {
+            all: FamilyStats::new(&all),
+            by_region: group_stats(by_region),
+            by_network_border_group: group_stats(by_network_border_group),
+            by_service: group_stats(by_service),
+        }
+    }
+}
+
+/*-------------------------------------------------------------------------------------------------
+  Family Stats
+-------------------------------------------------------------------------------------------------*/
+
+/// Address-space statistics for a group of prefixes, split by address family.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct FamilyStats {
+    /// Statistics for the IPv4 prefixes.
+    pub ipv4: PrefixStats,
+
+    /// Statistics for the IPv6 prefixes.
+    pub ipv6: PrefixStats,
+}
+
+/*--------------------------------------------------------------------------------------
+  Family Stats Implementation
+--------------------------------------------------------------------------------------*/
+
+impl FamilyStats {
+    fn new(prefixes: &[IpNetwork]) -> Self {
+        let (ipv4_prefixes, ipv6_prefixes): (Vec<IpNetwork>, Vec<IpNetwork>) =
+            prefixes.iter().partition(|prefix| prefix.is_ipv4());
+
+        Self {
+            ipv4: PrefixStats::new(&ipv4_prefixes),
+            ipv6: PrefixStats::new(&ipv6_prefixes),
+        }
+    }
+}
+
+/*-------------------------------------------------------------------------------------------------
+  Prefix Stats
+-------------------------------------------------------------------------------------------------*/
+
+/// Address-space statistics for a group of prefixes in a single address family.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PrefixStats {
+    /// Number of prefixes.
+    pub prefix_count: usize,
+
+    /// Sum of the addresses in each prefix; addresses in overlapping prefixes are counted once
+    /// per prefix.
+    pub total_addresses: u128,
+
+    /// Number of distinct addresses covered by the prefixes; addresses in overlapping prefixes
+    /// are counted once.
+    pub unique_addresses: u128,
+
+    /// Number of prefixes with each prefix length.
+    pub prefix_lengths: BTreeMap<u8, usize>,
+}
+
+/*--------------------------------------------------------------------------------------
+  Prefix Stats Implementation
+--------------------------------------------------------------------------------------*/
+
+impl PrefixStats {
+    fn new(prefixes: &[IpNetwork]) -> Self {
+        let mut prefix_lengths: BTreeMap<u8, usize> = BTreeMap::new();
+        for prefix in prefixes {
+            *prefix_lengths.entry(prefix.prefix()).or_default() += 1;
+        }
+
+        Self {
+            prefix_count: prefixes.len(),
+            total_addresses: prefixes
+                .iter()
+                .fold(0, |sum, prefix| sum.saturating_add(address_count(prefix))),
+            unique_addresses: aggregate(prefixes.iter().copied())
+                .iter()
+                .fold(0, |sum, prefix| sum.saturating_add(address_count(prefix))),
+            prefix_lengths,
+        }
+    }
+}
+
+/*-------------------------------------------------------------------------------------------------
+  Unit Tests
+-------------------------------------------------------------------------------------------------*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::aws_ip_prefix::tests::{test_aws_ipv4_prefix, test_aws_ipv6_prefix};
+
+    #[test]
+    fn test_prefix_stats() {
+        let prefixes: Vec<IpNetwork> = ["10.0.0.0/24", "10.0.0.0/25", "10.0.1.0/24"]
+            .iter()
+            .map(|prefix| prefix.parse().unwrap())
+            .collect();
+
+        let prefix_stats = PrefixStats::new(&prefixes);
+        assert_eq!(prefix_stats.prefix_count, 3);
+        assert_eq!(prefix_stats.total_addresses, 640);
+        assert_eq!(prefix_stats.unique_addresses, 512); // Nested /25 counted once
+        assert_eq!(prefix_stats.prefix_lengths, [(24, 2), (25, 1)].into());
+
+        assert_eq!(PrefixStats::new(&[]), PrefixStats::default());
+    }
+
+    #[test]
+    fn test_ranges_stats() {
+        let aws_ip_prefixes = [
+            test_aws_ipv4_prefix(),
+            AwsIpPrefix {
+                prefix: "10.0.0.0/16".parse().unwrap(),
+                region: Arc::from("us-west-1"),
+                network_border_group: Arc::from("us-west-1"),
+                services: [Arc::from("EC2"), Arc::from("S3")].into_iter().collect(),
+            },
+            test_aws_ipv6_prefix(),
+        ];
+
+        let ranges_stats = RangesStats::new(&aws_ip_prefixes);
+        assert_eq!(ranges_stats.all.ipv4.prefix_count, 2);
+        assert_eq!(ranges_stats.all.ipv4.unique_addresses, 1 << 24);
+        assert_eq!(ranges_stats.all.ipv6.unique_addresses, 1 << 96);
+        assert_eq!(
+            ranges_stats.by_region.keys().collect::<Vec<_>>(),
+            vec![&Arc::from("us-east-1"), &Arc::from("us-west-1")]
+        );
+        assert_eq!(ranges_stats.by_region["us-east-1"].ipv6.prefix_count, 1);
+        assert_eq!(ranges_stats.by_service["EC2"].ipv4.prefix_count, 2);
+        assert_eq!(ranges_stats.by_service["S3"].ipv4.total_addresses, 1 << 16);
+        assert_eq!(ranges_stats.by_service["S3"].ipv6, PrefixStats::default());
+    }
+}
@@ -1,6 +1,10 @@
+use crate::core::attribution::{AmazonServicePolicy, Attribution};
 use crate::core::aws_ip_prefix::AwsIpPrefix;
 use crate::core::aws_ip_ranges::AwsIpRanges;
+use crate::core::filter::Filter;
+use crate::core::utils::ipnetwork::{is_supernet_of, network_prefix};
 use ipnetwork::IpNetwork;
+use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet};
 
 /*-------------------------------------------------------------------------------------------------
@@ -9,7 +13,7 @@ use std::collections::{BTreeMap, BTreeSet};
 
 /// Search results containing the matching [AwsIpRanges], a map of found
 /// prefixes, and the set of prefixes not found in the AWS IP Ranges.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct SearchResults {
     /// [AwsIpRanges] object containing the matching AWS IP Prefixes.
     pub aws_ip_ranges: Box<AwsIpRanges>,
@@ -21,3 +25,362 @@ pub struct SearchResults {
     /// Set of [IpNetwork] prefixes not found in the AWS IP Ranges.
     pub prefixes_not_found: BTreeSet<IpNetwork>,
 }
+
+/*--------------------------------------------------------------------------------------
+  Search Results Implementation
+--------------------------------------------------------------------------------------*/
+
+impl SearchResults {
+    /// Get the [PrefixRelation] of a matching [AwsIpPrefix] to a search prefix; returns `None`
+    /// when the AWS IP Prefix is not one of the search prefix's matches.
+    pub fn relation(
+        &self,
+        search_prefix: &IpNetwork,
+        aws_ip_prefix: &AwsIpPrefix,
+    ) -> Option<PrefixRelation> {
+        self.prefix_matches
+            .get(search_prefix)
+            .filter(|aws_ip_prefixes| aws_ip_prefixes.contains(aws_ip_prefix))
+            .and_then(|_| PrefixRelation::between(search_prefix, &aws_ip_prefix.prefix))
+    }
+
+    /// Filter the search results with the provided [Filter]; the matches for each search prefix
+    /// are filtered, and search prefixes left without matches are moved to the prefixes not
+    /// found.
+    ///
+    /// ```rust
+    /// # fn main() -> awsipranges::Result<()> {
+    /// use ipnetwork::IpNetwork;
+    ///
+    /// let aws_ip_ranges = awsipranges::get_ranges()?;
+    ///
+    /// let search_prefixes: Vec<IpNetwork> = vec!["3.141.102.225".parse().unwrap()];
+    /// let filter = aws_ip_ranges.filter_builder().services(["EC2"])?.build();
+    ///
+    /// let search_results = aws_ip_ranges.search(&search_prefixes).filter(&filter);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn filter(&self, filter: &Filter) -> Box<SearchResults> {
+        let mut prefixes_not_found = self.prefixes_not_found.clone();
+        let mut prefix_matches: BTreeMap<IpNetwork, BTreeSet<AwsIpPrefix>> = BTreeMap::new();
+        for (search_prefix, aws_ip_prefixes) in self.prefix_matches.iter() {
+            let aws_ip_prefixes: BTreeSet<AwsIpPrefix> = aws_ip_prefixes
+                .iter()
+                .filter(|aws_ip_prefix| filter.include_prefix(aws_ip_prefix))
+                .cloned()
+                .collect();
+
+            if aws_ip_prefixes.is_empty() {
+                prefixes_not_found.insert(*search_prefix);
+            } else {
+                prefix_matches.insert(*search_prefix, aws_ip_prefixes);
+            }
+        }
+
+        Box::new(SearchResults {
+            aws_ip_ranges: self.aws_ip_ranges.filter(filter),
+            prefix_matches,
+            prefixes_not_found,
+        })
+    }
+
+    /// Resolve each found search prefix to the most specific AWS IP Prefix containing it and its
+    /// effective services; search prefixes only matching subnets (see [SearchMode]) are omitted.
+    ///
+    /// ```rust
+    /// # fn main() -> awsipranges::Result<()> {
+    /// use awsipranges::AmazonServicePolicy;
+    /// use ipnetwork::IpNetwork;
+    ///
+    /// let aws_ip_ranges = awsipranges::get_ranges()?;
+    ///
+    /// let search_prefixes: Vec<IpNetwork> = vec!["3.141.102.225".parse().unwrap()];
+    /// let search_results = aws_ip_ranges.search(&search_prefixes);
+    ///
+    /// for (search_prefix, attribution) in search_results.attributions(AmazonServicePolicy::Fallback) {
+    ///     println!("{search_prefix}: {} {:?}", attribution.prefix.prefix, attribution.services);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn attributions(&self, policy: AmazonServicePolicy) -> BTreeMap<IpNetwork, Attribution> {
+        self.prefix_matches
+            .iter()
+            .filter_map(|(search_prefix, aws_ip_prefixes)| {
+                Attribution::resolve(search_prefix, aws_ip_prefixes, policy)
+                    .map(|attribution| (*search_prefix, attribution))
+            })
+            .collect()
+    }
+
+    /// Narrow the search results to the most specific AWS IP Prefix matching each search prefix
+    /// (see [SearchResults::attributions]). Search prefixes only matching subnets keep all of
+    /// their matches.
+    pub fn most_specific(&self, policy: AmazonServicePolicy) -> Box<SearchResults> {
+        let attributions = self.attributions(policy);
+
+        let prefix_matches: BTreeMap<IpNetwork, BTreeSet<AwsIpPrefix>> = self
+            .prefix_matches
+            .iter()
+            .map(
+                |(search_prefix, aws_ip_prefixes)| match attributions.get(search_prefix) {
+                    Some(attribution) => (*search_prefix, [attribution.prefix.clone()].into()),
+                    None => (*search_prefix, aws_ip_prefixes.clone()),
+                },
+            )
+            .collect();
+
+        let aws_ip_prefixes: BTreeSet<AwsIpPrefix> =
+            prefix_matches.values().flatten().cloned().collect();
+        let mut aws_ip_ranges = Box::new(AwsIpRanges::from(aws_ip_prefixes));
+        aws_ip_ranges
+            .sync_token
+            .clone_from(&self.aws_ip_ranges.sync_token);
+        aws_ip_ranges.create_date = self.aws_ip_ranges.create_date;
+        aws_ip_ranges
+            .provenance
+            .clone_from(&self.aws_ip_ranges.provenance);
+
+        Box::new(SearchResults {
+            aws_ip_ranges,
+            prefix_matches,
+            prefixes_not_found: self.prefixes_not_found.clone(),
+        })
+    }
+}
+
+/*-------------------------------------------------------------------------------------------------
+  Search Mode
+-------------------------------------------------------------------------------------------------*/
+
+/// Selects which AWS IP Prefixes [AwsIpRanges::search_with_mode] matches for each search prefix.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SearchMode {
+    /// Match AWS IP Prefixes containing (or equal to) the search prefix.
+    #[default]
+    Supernets,
+
+    /// Match AWS IP Prefixes contained in (or equal to) the search prefix.
+    Subnets,
+
+    /// Match AWS IP Prefixes overlapping the search prefix in either direction.
+    Overlapping,
+}
+
+/*-------------------------------------------------------------------------------------------------
+  Prefix Relation
+-------------------------------------------------------------------------------------------------*/
+
+/// Relationship of a matching AWS IP Prefix to a search prefix.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PrefixRelation {
+    /// The AWS IP Prefix contains the search prefix.
+    Supernet,
+
+    /// The AWS IP Prefix is the search prefix.
+    Exact,
+
+    /// The AWS IP Prefix is contained in the search prefix.
+    Subnet,
+}
+
+impl PrefixRelation {
+    /// Get the relationship of an AWS IP Prefix to a search prefix; returns `None` when the
+    /// prefixes do not overlap. Host bits set in the search prefix are ignored.
+    pub fn between(search_prefix: &IpNetwork, aws_prefix: &IpNetwork) -> Option<PrefixRelation> {
+        let search_prefix = network_prefix(search_prefix);
+        let aws_prefix = network_prefix(aws_prefix);
+
+        if search_prefix == aws_prefix {
+            Some(PrefixRelation::Exact)
+        } else if is_supernet_of(aws_prefix, search_prefix) {
+            Some(PrefixRelation::Supernet)
+        } else if is_supernet_of(search_prefix, aws_prefix) {
+            Some(PrefixRelation::Subnet)
+        } else {
+            None
+        }
+    }
+}
+
+/*-------------------------------------------------------------------------------------------------
+  Unit Tests
+-------------------------------------------------------------------------------------------------*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_relation_between() {
+        let prefix = |value: &str| value.parse::<IpNetwork>().unwrap();
+
+        assert_eq!(
+            PrefixRelation::between(&prefix("10.1.2.3"), &prefix("10.0.0.0/8")),
+            Some(PrefixRelation::Supernet)
+        );
+        assert_eq!(
+            PrefixRelation::between(&prefix("10.1.2.3/8"), &prefix("10.0.0.0/8")),
+            Some(PrefixRelation::Exact)
+        );
+        assert_eq!(
+            PrefixRelation::between(&prefix("10.0.0.0/8"), &prefix("10.1.0.0/16")),
+            Some(PrefixRelation::Subnet)
+        );
+        assert_eq!(
+            PrefixRelation::between(&prefix("10.0.0.0/16"), &prefix("10.1.0.0/16")),
+            None
+        );
+        assert_eq!(
+            PrefixRelation::between(&prefix("10.0.0.0/8"), &prefix("2001:db8::/32")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_search_results_relation() {
+        use crate::core::aws_ip_prefix::tests::test_aws_ipv4_prefix;
+        use crate::core::aws_ip_ranges::tests::test_aws_ip_ranges;
+
+        let aws_ip_ranges = test_aws_ip_ranges();
+        let search_prefixes: Vec<IpNetwork> = vec![
+            "10.0.0.0/15".parse().unwrap(),
+            "2001:db8::/32".parse().unwrap(),
+        ];
+        let search_results =
+            aws_ip_ranges.search_with_mode(&search_prefixes, SearchMode::Overlapping);
+
+        let relation = |search_prefix: &IpNetwork, prefix: &str| {
+            let aws_ip_prefix = aws_ip_ranges.get_prefix(&prefix.parse().unwrap()).unwrap();
+            search_results.relation(search_prefix, aws_ip_prefix)
+        };
+
+        let ipv4 = &search_prefixes[0];
+        assert_eq!(relation(ipv4, "10.0.0.0/8"), Some(PrefixRelation::Supernet));
+        assert_eq!(relation(ipv4, "10.0.0.0/16"), Some(PrefixRelation::Subnet));
+        assert_eq!(relation(ipv4, "10.1.0.0/16"), Some(PrefixRelation::Subnet));
+        assert_eq!(relation(ipv4, "2001:db8::/32"), None);
+
+        let ipv6 = &search_prefixes[1];
+        assert_eq!(relation(ipv6, "2001:db8::/32"), Some(PrefixRelation::Exact));
+        assert_eq!(
+            relation(ipv6, "2001:db8:1::/48"),
+            Some(PrefixRelation::Subnet)
+        );
+        assert_eq!(search_results.relation(ipv6, &test_aws_ipv4_prefix()), None);
+    }
+
+    #[test]
+    fn test_search_results_filter() {
+        use crate::core::aws_ip_ranges::tests::test_aws_ip_ranges;
+
+        let aws_ip_ranges = test_aws_ip_ranges();
+        let search_prefixes: Vec<IpNetwork> = vec![
+            "10.0.0.1".parse().unwrap(),
+            "10.1.0.1".parse().unwrap(),
+            "192.168.0.1".parse().unwrap(),
+        ];
+        let search_results = aws_ip_ranges.search(&search_prefixes);
+
+        let filter = aws_ip_ranges
+            .filter_builder()
+            .regions(["us-east-1"])
+            .unwrap()
+            .build();
+        let filtered = search_results.filter(&filter);
+
+        assert_eq!(filtered.prefix_matches.len(), 2);
+        assert_eq!(filtered.prefix_matches[&search_prefixes[0]].len(), 2); // 10.0.0.0/8 and /16
+        assert_eq!(filtered.prefix_matches[&search_prefixes[1]].len(), 1); // 10.0.0.0/8
+        assert_eq!(filtered.prefixes_not_found, [search_prefixes[2]].into());
+        assert_eq!(filtered.aws_ip_ranges.regions().len(), 1);
+
+        let filter = aws_ip_ranges
+            .filter_builder()
+            .services(["S3"])
+            .unwrap()
+            .build();
+        let filtered = search_results.filter(&filter);
+
+        assert_eq!(
+            filtered.prefix_matches.keys().collect::<Vec<_>>(),
+            vec![&search_prefixes[1]]
+        );
+        assert_eq!(
+            filtered.prefixes_not_found,
+            [search_prefixes[0], search_prefixes[2]].into()
+        );
+    }
+
+    #[test]
+    fn test_search_results_attributions() {
+        use crate::core::aws_ip_ranges::tests::test_aws_ip_ranges;
+
+        let aws_ip_ranges = test_aws_ip_ranges();
+        let search_prefixes: Vec<IpNetwork> = vec![
+            "10.1.0.1".parse().unwrap(),
+            "10.0.0.0/7".parse().unwrap(),
+            "192.168.0.1".parse().unwrap(),
+        ];
+        let search_results =
+            aws_ip_ranges.search_with_mode(&search_prefixes, SearchMode::Overlapping);
+
+        let attributions = search_results.attributions(AmazonServicePolicy::Fallback);
+        assert_eq!(
+            attributions.keys().collect::<Vec<_>>(),
+            vec![&search_prefixes[0]] // 10.0.0.0/7 only matches subnets
+        );
+        let attribution = &attributions[&search_prefixes[0]];
+        assert_eq!(attribution.prefix.prefix, "10.1.0.0/16".parse().unwrap());
+        assert_eq!(
+            attribution.overridden[0].prefix,
+            "10.0.0.0/8".parse().unwrap()
+        );
+
+        let most_specific = search_results.most_specific(AmazonServicePolicy::Fallback);
+        assert_eq!(most_specific.prefix_matches[&search_prefixes[0]].len(), 1);
+        assert_eq!(
+            most_specific.prefix_matches[&search_prefixes[1]],
+            search_results.prefix_matches[&search_prefixes[1]]
+        );
+        assert_eq!(
+            most_specific.prefixes_not_found,
+            search_results.prefixes_not_found
+        );
+        assert_eq!(
+            most_specific.aws_ip_ranges.sync_token(),
+            aws_ip_ranges.sync_token()
+        );
+    }
+
+    #[test]
+    fn test_search_results_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SearchResults>();
+    }
+
+    #[test]
+    fn test_search_results_serde_round_trip() {
+        use crate::core::aws_ip_ranges::tests::test_aws_ip_ranges;
+
+        let aws_ip_ranges = test_aws_ip_ranges();
+        let search_prefixes: Vec<IpNetwork> =
+            vec!["10.0.0.1".parse().unwrap(), "192.168.0.1".parse().unwrap()];
+        let search_results = aws_ip_ranges.search(&search_prefixes);
+
+        let json = serde_json::to_string(&search_results).unwrap();
+        let deserialized: SearchResults = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(deserialized.prefix_matches, search_results.prefix_matches);
+        assert_eq!(
+            deserialized.prefixes_not_found,
+            search_results.prefixes_not_found
+        );
+        assert_eq!(
+            deserialized.aws_ip_ranges.prefixes(),
+            search_results.aws_ip_ranges.prefixes()
+        );
+    }
+}
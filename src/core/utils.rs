@@ -1,5 +1,5 @@
 use std::collections::BTreeSet;
-use std::rc::Rc;
+use std::sync::Arc;
 
 /*-------------------------------------------------------------------------------------------------
   Utilities
@@ -9,8 +9,58 @@ use std::rc::Rc;
   Work with Reference Counted String Slices
 --------------------------------------------------------------------------------------*/
 
-pub fn get_rc_str_from_set(value: &str, set: &BTreeSet<Rc<str>>) -> Option<Rc<str>> {
-    set.get(value).map(Rc::clone)
+pub fn get_arc_str_from_set(value: &str, set: &BTreeSet<Arc<str>>) -> Option<Arc<str>> {
+    set.get(value).map(Arc::clone)
+}
+
+/// Get the strings in the set matching a case-insensitive glob pattern; `*` matches any sequence
+/// of characters and `?` matches any single character.
+pub fn get_arc_strs_matching_pattern(
+    pattern: &str,
+    set: &BTreeSet<Arc<str>>,
+) -> BTreeSet<Arc<str>> {
+    set.iter()
+        .filter(|value| glob_match(pattern, value))
+        .map(Arc::clone)
+        .collect()
+}
+
+/*--------------------------------------------------------------------------------------
+  Case-Insensitive Glob Pattern Matching
+--------------------------------------------------------------------------------------*/
+
+/// Check if a value matches a case-insensitive glob pattern; `*` matches any sequence of
+/// characters and `?` matches any single character.
+pub fn glob_match(pattern: &str, value: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().flat_map(char::to_lowercase).collect();
+    let value: Vec<char> = value.chars().flat_map(char::to_lowercase).collect();
+
+    let (mut p, mut v) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None; // (pattern index after `*`, value index)
+
+    while v < value.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                p += 1;
+                backtrack = Some((p, v));
+            }
+            Some(&c) if c == '?' || c == value[v] => {
+                p += 1;
+                v += 1;
+            }
+            _ => match backtrack {
+                // Let the last `*` consume one more character
+                Some((star_p, star_v)) => {
+                    p = star_p;
+                    v = star_v + 1;
+                    backtrack = Some((star_p, star_v + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
 }
 
 /*--------------------------------------------------------------------------------------
@@ -18,7 +68,7 @@ pub fn get_rc_str_from_set(value: &str, set: &BTreeSet<Rc<str>>) -> Option<Rc<st
 --------------------------------------------------------------------------------------*/
 
 pub mod ipnetwork {
-    use crate::core::errors::Result;
+    use crate::core::errors::{Error, Result};
     use ipnetwork::{IpNetwork, Ipv4Network, Ipv6Network};
 
     /*
@@ -45,12 +95,16 @@ pub mod ipnetwork {
     pub fn new_network_prefix(ip_network: &IpNetwork, mask_bits: u8) -> Result<IpNetwork> {
         let new_prefix = match ip_network {
             IpNetwork::V4(ipv4_network) => {
-                IpNetwork::V4(Ipv4Network::new(ipv4_network.ip(), mask_bits)?)
+                Ipv4Network::new(ipv4_network.ip(), mask_bits).map(IpNetwork::V4)
             }
             IpNetwork::V6(ipv6_network) => {
-                IpNetwork::V6(Ipv6Network::new(ipv6_network.ip(), mask_bits)?)
+                Ipv6Network::new(ipv6_network.ip(), mask_bits).map(IpNetwork::V6)
             }
-        };
+        }
+        .map_err(|error| Error::InvalidPrefix {
+            value: format!("{}/{}", ip_network.ip(), mask_bits),
+            reason: error.to_string(),
+        })?;
 
         Ok(network_prefix(&new_prefix))
     }
@@ -74,6 +128,132 @@ pub mod ipnetwork {
             _ => false,
         }
     }
+
+    /*
+        The number of addresses in an IPv6 prefix exceeds u64; address counts for
+        both address families are returned as u128 values (saturating for ::/0).
+    */
+
+    pub fn address_count(ip_network: &IpNetwork) -> u128 {
+        match ip_network {
+            IpNetwork::V4(ipv4_network) => 1 << (32 - ipv4_network.prefix()),
+            IpNetwork::V6(ipv6_network) => 1u128
+                .checked_shl(128 - u32::from(ipv6_network.prefix()))
+                .unwrap_or(u128::MAX),
+        }
+    }
+
+    /*
+        Aggregation collapses a set of prefixes into the minimal set of prefixes
+        covering exactly the same address space: nested prefixes are dropped and
+        adjacent sibling prefixes are merged into their parent prefix.
+    */
+
+    pub fn aggregate<I>(prefixes: I) -> Vec<IpNetwork>
+    where
+        I: IntoIterator<Item = IpNetwork>,
+    {
+        // Sorting network prefixes orders them by address family, network
+        // address, and prefix length; supernets sort before their subnets
+        let mut prefixes: Vec<IpNetwork> = prefixes
+            .into_iter()
+            .map(|prefix| network_prefix(&prefix))
+            .collect();
+        prefixes.sort();
+
+        let mut aggregated: Vec<IpNetwork> = Vec::with_capacity(prefixes.len());
+        for prefix in prefixes {
+            // Drop prefixes nested in the last aggregated prefix
+            if let Some(last) = aggregated.last() {
+                if is_supernet_of(*last, prefix) {
+                    continue;
+                }
+            }
+
+            aggregated.push(prefix);
+
+            // Merge sibling prefixes into their parent prefix
+            while aggregated.len() >= 2 {
+                let lower = aggregated[aggregated.len() - 2];
+                let upper = aggregated[aggregated.len() - 1];
+                match parent_of_siblings(&lower, &upper) {
+                    Some(parent) => {
+                        aggregated.truncate(aggregated.len() - 2);
+                        aggregated.push(parent);
+                    }
+                    None => break,
+                }
+            }
+        }
+
+        aggregated
+    }
+
+    /*
+        Subtraction removes the address space covered by a set of prefixes from a
+        prefix; the remaining address space is returned as the sub-CIDRs left after
+        splitting the prefix around the removed prefixes.
+    */
+
+    pub fn subtract(prefix: IpNetwork, removed: &[IpNetwork]) -> Vec<IpNetwork> {
+        let removed: Vec<IpNetwork> = removed
+            .iter()
+            .copied()
+            .filter(|other| is_supernet_of(*other, prefix) || is_supernet_of(prefix, *other))
+            .collect();
+
+        if removed.is_empty() {
+            return vec![prefix];
+        }
+
+        if removed.iter().any(|other| is_supernet_of(*other, prefix)) {
+            return vec![];
+        }
+
+        // The remaining removed prefixes are strict subnets of the prefix, so it can be split
+        let (lower, upper) = halves(&prefix);
+        let mut remaining = subtract(lower, &removed);
+        remaining.extend(subtract(upper, &removed));
+        remaining
+    }
+
+    /// Split a prefix into its two child prefixes.
+    fn halves(prefix: &IpNetwork) -> (IpNetwork, IpNetwork) {
+        match prefix {
+            IpNetwork::V4(ipv4_network) => {
+                let prefix_len = ipv4_network.prefix() + 1;
+                let network = u32::from(ipv4_network.network());
+                let upper = network | (1 << (32 - prefix_len));
+                (
+                    IpNetwork::V4(Ipv4Network::new(network.into(), prefix_len).unwrap()),
+                    IpNetwork::V4(Ipv4Network::new(upper.into(), prefix_len).unwrap()),
+                )
+            }
+            IpNetwork::V6(ipv6_network) => {
+                let prefix_len = ipv6_network.prefix() + 1;
+                let network = u128::from(ipv6_network.network());
+                let upper = network | (1 << (128 - prefix_len));
+                (
+                    IpNetwork::V6(Ipv6Network::new(network.into(), prefix_len).unwrap()),
+                    IpNetwork::V6(Ipv6Network::new(upper.into(), prefix_len).unwrap()),
+                )
+            }
+        }
+    }
+
+    /// Get the parent prefix of two distinct sibling prefixes.
+    fn parent_of_siblings(lower: &IpNetwork, upper: &IpNetwork) -> Option<IpNetwork> {
+        if lower == upper
+            || lower.is_ipv4() != upper.is_ipv4()
+            || lower.prefix() != upper.prefix()
+            || lower.prefix() == 0
+        {
+            return None;
+        }
+
+        let parent = new_network_prefix(lower, lower.prefix() - 1).ok()?;
+        (parent == new_network_prefix(upper, upper.prefix() - 1).ok()?).then_some(parent)
+    }
 }
 
 /*-------------------------------------------------------------------------------------------------
@@ -82,33 +262,88 @@ pub mod ipnetwork {
 
 #[cfg(test)]
 mod tests {
-    use super::get_rc_str_from_set;
-    use super::ipnetwork::{is_supernet_of, network_prefix, new_network_prefix};
+    use super::ipnetwork::{
+        address_count, aggregate, is_supernet_of, network_prefix, new_network_prefix, subtract,
+    };
+    use super::{get_arc_str_from_set, get_arc_strs_matching_pattern, glob_match};
+    use crate::core::errors::Error;
     use ipnetwork::IpNetwork;
     use std::collections::BTreeSet;
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     /*-----------------------------------------------------------------------------
       Work with Reference Counted String Slices
     -----------------------------------------------------------------------------*/
 
     #[test]
-    fn test_get_rc_str_from_set() {
-        let set: BTreeSet<Rc<str>> = [Rc::from("foo"), Rc::from("bar"), Rc::from("baz")]
+    fn test_get_arc_str_from_set() {
+        let set: BTreeSet<Arc<str>> = [Arc::from("foo"), Arc::from("bar"), Arc::from("baz")]
             .into_iter()
             .collect();
 
-        let foo = get_rc_str_from_set("foo", &set).unwrap();
-        let bar = get_rc_str_from_set("bar", &set).unwrap();
-        let baz = get_rc_str_from_set("baz", &set).unwrap();
-        let nope = get_rc_str_from_set("nope", &set);
+        let foo = get_arc_str_from_set("foo", &set).unwrap();
+        let bar = get_arc_str_from_set("bar", &set).unwrap();
+        let baz = get_arc_str_from_set("baz", &set).unwrap();
+        let nope = get_arc_str_from_set("nope", &set);
 
-        assert_eq!(Rc::strong_count(&foo), 2);
-        assert_eq!(Rc::strong_count(&bar), 2);
-        assert_eq!(Rc::strong_count(&baz), 2);
+        assert_eq!(Arc::strong_count(&foo), 2);
+        assert_eq!(Arc::strong_count(&bar), 2);
+        assert_eq!(Arc::strong_count(&baz), 2);
         assert!(nope.is_none());
     }
 
+    #[test]
+    fn test_get_arc_strs_matching_pattern() {
+        let set: BTreeSet<Arc<str>> = [
+            Arc::from("us-east-1"),
+            Arc::from("us-west-2"),
+            Arc::from("us-west-2-lax-1"),
+            Arc::from("eu-west-1"),
+            Arc::from("GLOBAL"),
+        ]
+        .into_iter()
+        .collect();
+
+        let matches = |pattern| {
+            get_arc_strs_matching_pattern(pattern, &set)
+                .iter()
+                .map(|value| value.to_string())
+                .collect::<Vec<String>>()
+        };
+
+        assert_eq!(
+            matches("us-*"),
+            ["us-east-1", "us-west-2", "us-west-2-lax-1"]
+        );
+        assert_eq!(matches("*-lax-*"), ["us-west-2-lax-1"]);
+        assert_eq!(matches("US-EAST-1"), ["us-east-1"]);
+        assert_eq!(matches("global"), ["GLOBAL"]);
+        assert!(matches("ap-*").is_empty());
+    }
+
+    /*-----------------------------------------------------------------------------
+      Case-Insensitive Glob Pattern Matching
+    -----------------------------------------------------------------------------*/
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("us-east-1", "us-east-1"));
+        assert!(glob_match("US-East-1", "us-east-1"));
+        assert!(!glob_match("us-east-1", "us-east-12"));
+        assert!(!glob_match("us-east-12", "us-east-1"));
+
+        assert!(glob_match("*", ""));
+        assert!(glob_match("*", "anything"));
+        assert!(glob_match("us-*", "us-"));
+        assert!(glob_match("us-*-1", "us-west-2-lax-1"));
+        assert!(glob_match("*-lax-*", "us-west-2-lax-1"));
+        assert!(!glob_match("*-lax-*", "us-west-2"));
+        assert!(glob_match("us-?ast-1", "us-east-1"));
+        assert!(!glob_match("us-?-1", "us-east-1"));
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(!glob_match("a*b*c", "aXbYbZ"));
+    }
+
     /*-----------------------------------------------------------------------------
       IP Network Supplemental Functions
     -----------------------------------------------------------------------------*/
@@ -141,6 +376,12 @@ mod tests {
 
         assert_eq!(actual_ipv4_prefix, expected_ipv4_prefix);
         assert_eq!(actual_ipv6_prefix, expected_ipv6_prefix);
+
+        // Invalid mask bits
+        assert!(matches!(
+            new_network_prefix(&original_ipv4_network, 33),
+            Err(Error::InvalidPrefix { value, .. }) if value == "10.0.1.0/33"
+        ));
     }
 
     #[test]
@@ -160,4 +401,74 @@ mod tests {
         assert!(!is_supernet_of(ipv4_supernet, ipv6_supernet)); // Comparing IPv4 and IPv6 prefixes returns false
         assert!(!is_supernet_of(ipv6_supernet, ipv4_supernet)); // Comparing IPv6 and IPv4 prefixes returns false
     }
+
+    #[test]
+    fn test_address_count() {
+        let count = |prefix: &str| address_count(&prefix.parse().unwrap());
+
+        assert_eq!(count("10.0.0.0/24"), 256);
+        assert_eq!(count("10.0.0.1/32"), 1);
+        assert_eq!(count("0.0.0.0/0"), 1 << 32);
+        assert_eq!(count("2001:db8::/64"), 1 << 64);
+        assert_eq!(count("::/0"), u128::MAX);
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let prefixes: Vec<IpNetwork> = [
+            "10.0.0.0/24",
+            "10.0.1.0/24",   // Sibling of 10.0.0.0/24
+            "10.0.2.0/23",   // Sibling of the merged 10.0.0.0/23
+            "10.0.3.128/25", // Nested in 10.0.2.0/23
+            "10.1.0.0/24",
+            "192.168.0.1/24", // Host bits are ignored
+            "2001:db8::/33",
+            "2001:db8:8000::/33",
+            "2001:db9::/32",
+        ]
+        .iter()
+        .map(|prefix| prefix.parse().unwrap())
+        .collect();
+
+        let expected: Vec<IpNetwork> = [
+            "10.0.0.0/22",
+            "10.1.0.0/24",
+            "192.168.0.0/24",
+            "2001:db8::/31",
+        ]
+        .iter()
+        .map(|prefix| prefix.parse().unwrap())
+        .collect();
+
+        assert_eq!(aggregate(prefixes), expected);
+        assert!(aggregate(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn test_subtract() {
+        let parse = |prefixes: &[&str]| -> Vec<IpNetwork> {
+            prefixes
+                .iter()
+                .map(|prefix| prefix.parse().unwrap())
+                .collect()
+        };
+        let prefix: IpNetwork = "10.0.0.0/22".parse().unwrap();
+
+        assert_eq!(subtract(prefix, &[]), vec![prefix]);
+        assert_eq!(subtract(prefix, &parse(&["10.1.0.0/16"])), vec![prefix]); // Disjoint
+        assert!(subtract(prefix, &parse(&["10.0.0.0/8"])).is_empty()); // Supernet
+        assert!(subtract(prefix, &[prefix]).is_empty()); // Exact match
+        assert_eq!(
+            subtract(prefix, &parse(&["10.0.1.0/24"])),
+            parse(&["10.0.0.0/24", "10.0.2.0/23"])
+        );
+        assert_eq!(
+            subtract(prefix, &parse(&["10.0.0.0/24", "10.0.3.128/25"])),
+            parse(&["10.0.1.0/24", "10.0.2.0/24", "10.0.3.0/25"])
+        );
+        assert_eq!(
+            subtract("2001:db8::/32".parse().unwrap(), &parse(&["2001:db8::/33"])),
+            parse(&["2001:db8:8000::/33"])
+        );
+    }
 }
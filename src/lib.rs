@@ -32,12 +32,32 @@
 //! ## Features
 //!
 //! - **Retrieve & Cache**: [`ip-ranges.json`](https://ip-ranges.amazonaws.com/ip-ranges.json) to
-//!   `${HOME}/.aws/ip-ranges.json`; refreshing the cache after 24 hours (by default).
+//!   `${HOME}/.aws/ip-ranges.json`; refreshing the cache after 24 hours (by default). Inspect,
+//!   refresh, clear, and verify the cache with [Client::cache_info], [Client::refresh_cache],
+//!   [Client::clear_cache], and [Client::verify_cache]. Loaded AWS IP Ranges record their
+//!   [Provenance], so you can tell when a stale cache was used because AWS was unreachable.
+//!   Reject old data with [ClientBuilder::max_data_age] and pin a specific version of the AWS IP
+//!   Ranges with [ClientBuilder::expected_sync_token].
 //!
 //! - **Search**: IP ranges for an _**IPv4/IPv6 address**_ or _**CIDR**_ (any prefix length) to
-//!   view the AWS IP ranges that contain the provided address or CIDR.
+//!   view the AWS IP ranges that contain the provided address or CIDR; or the AWS IP ranges
+//!   contained in a wider CIDR ([SearchMode]). Resolve addresses to their most specific AWS IP
+//!   range and effective services with [AwsIpRanges::attribute]. Parse address ranges,
+//!   netmasks, wildcards, and IPv4-mapped IPv6 addresses into CIDRs with [parse_input].
 //!
-//! - **Filter**: IP ranges by region, service, network border group, and IP version (IPv4/IPv6).
+//! - **Filter**: IP ranges by region, service, network border group, and IP version (IPv4/IPv6)
+//!   using case-insensitive names or `*`/`?` wildcard patterns; exclude regions, services,
+//!   network border groups, or `AMAZON`-only prefixes; and constrain prefix lengths or address
+//!   ranges.
+//!
+//! - **Stats**: Address-space statistics ([AwsIpRanges::stats]); prefix counts, total and
+//!   unique address counts, and prefix-length histograms by region, network border group,
+//!   service, and address family.
+//!
+//! - **Set Operations**: Combine AWS IP Ranges with address-space-aware
+//!   [union](AwsIpRanges::union), [intersection](AwsIpRanges::intersection), and
+//!   [difference](AwsIpRanges::difference) operations (or the `|`, `&`, and `-` operators);
+//!   prefixes are split into sub-CIDRs where needed, keeping their metadata.
 //!
 //! ## Example
 //!
@@ -51,9 +71,22 @@
 //! and default values to configure the client that retrieves the AWS IP Ranges. You can use the
 //! [Client::default] and [ClientBuilder::default] methods to create a client with the default
 //! configurations, ignoring environment variables. Use the [ClientBuilder] struct to build a
-//! client with a custom configuration.
+//! client with a custom configuration. [get_ranges] and [ClientBuilder::try_new] return an
+//! [Error::InvalidEnvVar] when an environment variable is set to an invalid value, while
+//! [Client::new] and [ClientBuilder::new] log a warning and use the default value.
 //!
 #![doc = include_str!("../docs/lib_configuration_table.md")]
+//!
+//! ## Serde
+//!
+//! [AwsIpPrefix], [AwsIpRanges], [SearchResults], [Filter], [RangesStats], and [Attribution]
+//! implement serde's `Serialize` and `Deserialize` traits. The serialized schema is stable:
+//!
+#![doc = include_str!("../docs/lib_serde_schema.md")]
+//!
+//! Use [AwsIpRanges::to_json] or [AwsIpRanges::write_json] to serialize AWS IP Ranges into
+//! AWS's native `ip-ranges.json` format; use them to feed a filtered copy of the AWS IP Ranges
+//! to tools that consume `ip-ranges.json`.
 
 /*-------------------------------------------------------------------------------------------------
   Library Modules
@@ -65,12 +98,18 @@ mod core;
   Library Public Interface
 -------------------------------------------------------------------------------------------------*/
 
+pub use crate::core::attribution::{AmazonServicePolicy, Attribution};
 pub use crate::core::aws_ip_prefix::AwsIpPrefix;
 pub use crate::core::aws_ip_ranges::AwsIpRanges;
-pub use crate::core::client::{get_ranges, Client, ClientBuilder};
-pub use crate::core::errors::{Error, Result};
+pub use crate::core::cache_info::CacheInfo;
+pub use crate::core::client::{get_ranges, Client, ClientBuilder, DataAgePolicy, FetchPolicy};
+pub use crate::core::errors::{Error, FilterValueKind, Result};
 pub use crate::core::filter::{Filter, FilterBuilder};
-pub use crate::core::search_results::SearchResults;
+pub use crate::core::input::parse_input;
+pub use crate::core::provenance::{DataSource, Provenance};
+pub use crate::core::ranges_diff::{PrefixChange, RangesDiff};
+pub use crate::core::ranges_stats::{FamilyStats, PrefixStats, RangesStats};
+pub use crate::core::search_results::{PrefixRelation, SearchMode, SearchResults};
 
 /*--------------------------------------------------------------------------------------
   Vendored Modules
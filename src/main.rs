@@ -8,47 +8,156 @@ mod cli;
 Main CLI Function
 -------------------------------------------------------------------------------------------------*/
 
-use clap::Parser;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    // Answer shell completion requests
+    cli::completions::complete();
 
-fn main() -> awsipranges::Result<()> {
     // Parse CLI arguments
-    let args = cli::Args::parse();
+    let args = cli::Args::parse_args();
 
     // Configure logging
     stderrlog::new()
         .module(module_path!())
-        .verbosity(args.verbose.log_level_filter())
+        .verbosity(usize::from(args.verbose))
+        .quiet(args.quiet && args.verbose == 0)
         .init()
         .unwrap();
 
+    match run(&args) {
+        Ok(exit_status) => exit_status.into(),
+        Err(failure) => {
+            if !args.quiet {
+                eprintln!("{failure}");
+            }
+            failure.exit_status.into()
+        }
+    }
+}
+
+fn run(args: &cli::Args) -> Result<cli::ExitStatus, cli::Failure> {
+    // Print nothing in quiet mode; report the result with the exit status
+    let quiet = args.quiet;
+    let client = cli::build_client(&args.client)?;
+
+    match &args.command {
+        None => legacy(&args.legacy, &client, quiet),
+        Some(cli::Command::Search(command)) => {
+            let search_cidrs = cli::parse_prefixes(&command.search_cidrs, &command.input_files)
+                .map_err(cli::Failure::invalid_input)?;
+            query(
+                search_cidrs,
+                &command.search,
+                &command.filter,
+                &command.output,
+                &client,
+                quiet,
+            )
+        }
+        Some(cli::Command::Filter(command)) => query(
+            None,
+            &cli::SearchArgs::default(),
+            &command.filter,
+            &command.output,
+            &client,
+            quiet,
+        ),
+        Some(cli::Command::List(command)) => list(command, &client, quiet),
+        Some(cli::Command::Stats(command)) => stats(command, &client, quiet),
+        Some(cli::Command::Diff(command)) => diff(&command.files, &command.format, &client, quiet),
+        Some(cli::Command::Export(command)) => export(command, &client, quiet),
+        Some(cli::Command::Cache(command)) => cache(command, &client, quiet),
+        Some(cli::Command::Completions(command)) => {
+            cli::completions::script(&command.shell)?;
+            Ok(cli::ExitStatus::Success)
+        }
+        Some(cli::Command::Man(command)) => {
+            cli::completions::man(command.directory.as_deref())?;
+            Ok(cli::ExitStatus::Success)
+        }
+    }
+}
+
+/*--------------------------------------------------------------------------------------
+  Legacy Invocation
+--------------------------------------------------------------------------------------*/
+
+/// Run the flat (pre-command) CLI: `--diff` compares AWS IP Ranges; otherwise, search for the
+/// positional CIDRs and apply the filter flags.
+fn legacy(
+    args: &cli::LegacyArgs,
+    client: &awsipranges::Client,
+    quiet: bool,
+) -> Result<cli::ExitStatus, cli::Failure> {
+    if let Some(diff_files) = &args.diff {
+        return diff(diff_files, &args.diff_format, client, quiet);
+    };
+
+    let search_cidrs = cli::parse_prefixes(&args.search_cidrs, &args.input_files)
+        .map_err(cli::Failure::invalid_input)?;
+    query(
+        search_cidrs,
+        &args.search,
+        &args.filter,
+        &args.output,
+        client,
+        quiet,
+    )
+}
+
+/*--------------------------------------------------------------------------------------
+  Search and Filter
+--------------------------------------------------------------------------------------*/
+
+fn query(
+    search_cidrs: Option<Vec<ipnetwork::IpNetwork>>,
+    search_args: &cli::SearchArgs,
+    filter_args: &cli::FilterArgs,
+    output_args: &cli::OutputArgs,
+    client: &awsipranges::Client,
+    quiet: bool,
+) -> Result<cli::ExitStatus, cli::Failure> {
     // Get AWS IP Ranges
-    let aws_ip_ranges = awsipranges::get_ranges()?;
+    let aws_ip_ranges = client.get_ranges()?;
+    warn_if_stale(&aws_ip_ranges, client, quiet);
 
     // Search for CIDRs
-    let search_cidrs = cli::parse_prefixes(&args);
+    let search_mode = if search_args.overlapping {
+        awsipranges::SearchMode::Overlapping
+    } else {
+        awsipranges::SearchMode::Supernets
+    };
     let search_results = search_cidrs
         .as_ref()
-        .map(|search_prefixes| aws_ip_ranges.search(search_prefixes));
+        .map(|search_prefixes| aws_ip_ranges.search_with_mode(search_prefixes, search_mode));
 
     // Apply Filters
-    let filters_enabled = [
-        args.ipv4,
-        args.ipv6,
-        args.include_regions.is_some(),
-        args.include_network_border_groups.is_some(),
-        args.include_services.is_some(),
-    ]
-    .iter()
-    .any(|v| *v);
-
-    let filter = if filters_enabled {
-        Some(cli::build_filter(&args, &aws_ip_ranges)?)
-    } else {
-        None
+    let filter = cli::build_filter(filter_args, &aws_ip_ranges)?;
+
+    let search_results = match (search_results, &filter) {
+        (Some(search_results), Some(filter)) => Some(search_results.filter(filter)),
+        (search_results, _) => search_results,
+    };
+
+    // Resolve each search CIDR to its most specific (filtered) AWS IP Prefix
+    let amazon_service_policy = match search_args.amazon_service {
+        cli::AmazonService::Fallback => awsipranges::AmazonServicePolicy::Fallback,
+        cli::AmazonService::Service => awsipranges::AmazonServicePolicy::Service,
+    };
+    let attributions = search_results
+        .as_ref()
+        .filter(|_| search_args.most_specific)
+        .map(|search_results| search_results.attributions(amazon_service_policy));
+    let search_results = match (search_results, &attributions) {
+        (Some(search_results), Some(attributions)) => {
+            cli::log::attributions(attributions);
+            Some(search_results.most_specific(amazon_service_policy))
+        }
+        (search_results, _) => search_results,
     };
 
     let filtered_results = match (&search_results, &filter) {
-        (Some(search_results), Some(filter)) => Some(search_results.aws_ip_ranges.filter(filter)),
         (None, Some(filter)) => Some(aws_ip_ranges.filter(filter)),
         _ => None,
     };
@@ -64,29 +173,380 @@ fn main() -> awsipranges::Result<()> {
     // Log CIDR search results
     cli::log::search_results(&search_cidrs, &search_results);
 
+    // Group the search results by search CIDR
+    let search_view = match (&search_cidrs, &search_results) {
+        (Some(search_cidrs), Some(search_results))
+            if search_args.search_view == cli::SearchView::ByInput =>
+        {
+            Some((search_cidrs.as_slice(), &**search_results))
+        }
+        _ => None,
+    };
+
+    // Aggregate the selected AWS IP Prefixes
+    let aggregated_prefixes = output_args
+        .aggregate
+        .as_ref()
+        .map(|aggregate| cli::aggregate_prefixes(aggregate, display_aws_ip_ranges));
+
     // Display selected CLI output
     if display_aws_ip_ranges.prefixes().is_empty() {
-        eprintln!("\nNo AWS IP Prefixes match the provided criteria.\n");
-        std::process::exit(1);
-    } else {
-        match args.output {
-            cli::OutputFormat::Table => cli::output::prefix_table(display_aws_ip_ranges),
-            cli::OutputFormat::Cidr => cli::output::prefixes_in_cidr_format(display_aws_ip_ranges),
-            cli::OutputFormat::Netmask => {
-                cli::output::prefixes_in_netmask_format(display_aws_ip_ranges)
+        return Ok(not_found(quiet));
+    } else if !quiet {
+        if aggregated_prefixes.is_some()
+            && output_args.csv_file.is_none()
+            && !matches!(
+                output_args.output,
+                cli::OutputFormat::Cidr | cli::OutputFormat::Netmask
+            )
+        {
+            eprintln!(
+                "Warning: --aggregate does not apply to the {} output format.",
+                output_args.output
+            );
+        };
+
+        match (&output_args.output, &aggregated_prefixes, search_view) {
+            (cli::OutputFormat::Cidr, Some(aggregated_prefixes), _) => {
+                cli::output::aggregated_prefixes_in_cidr_format(aggregated_prefixes)
+            }
+            (cli::OutputFormat::Netmask, Some(aggregated_prefixes), _) => {
+                cli::output::aggregated_prefixes_in_netmask_format(aggregated_prefixes)
             }
-            cli::OutputFormat::Regions => cli::output::regions(display_aws_ip_ranges),
-            cli::OutputFormat::NetworkBorderGroups => {
-                cli::output::network_border_groups(display_aws_ip_ranges)
+            (cli::OutputFormat::Table, _, Some((search_cidrs, search_results))) => {
+                cli::output::search_results_table(
+                    search_cidrs,
+                    search_results,
+                    attributions.as_ref(),
+                )
             }
-            cli::OutputFormat::Services => cli::output::services(display_aws_ip_ranges),
+            (output, _, _) => display_output(
+                output,
+                &output_args.stats_format,
+                display_aws_ip_ranges,
+                search_cidrs.as_deref().zip(search_results.as_deref()),
+                attributions.as_ref(),
+            )?,
         };
     };
 
     // Save results to CSV file
-    if let Some(csv_file_path) = args.csv_file {
-        cli::csv::save(display_aws_ip_ranges, &csv_file_path)?;
+    if let Some(csv_file_path) = &output_args.csv_file {
+        match (&aggregated_prefixes, search_view) {
+            (Some(aggregated_prefixes), _) => {
+                cli::csv::save_aggregated(aggregated_prefixes, csv_file_path)?
+            }
+            (None, Some((search_cidrs, search_results))) => cli::csv::save_search_results(
+                search_cidrs,
+                search_results,
+                attributions.as_ref(),
+                csv_file_path,
+            )?,
+            (None, None) => cli::csv::save(display_aws_ip_ranges, csv_file_path)?,
+        };
+    };
+
+    // Report whether every search CIDR was found
+    match &search_results {
+        Some(search_results) if !search_results.prefixes_not_found.is_empty() => {
+            Ok(cli::ExitStatus::PartiallyFound)
+        }
+        _ => Ok(cli::ExitStatus::Success),
+    }
+}
+
+/// Warn that the AWS IP Ranges came from a stale cache file (the URL was unavailable, or the
+/// fetch policy is `cache-only`) or, with the `warn` data age policy, are older than the
+/// maximum data age; they may be out of date.
+fn warn_if_stale(
+    aws_ip_ranges: &awsipranges::AwsIpRanges,
+    client: &awsipranges::Client,
+    quiet: bool,
+) {
+    if quiet {
+        return;
+    }
+
+    if client.data_age_policy() == awsipranges::DataAgePolicy::Warn {
+        if let Err(error) = client.check_data_age(aws_ip_ranges) {
+            eprintln!("Warning: {error}.");
+        }
+    }
+
+    let Some(provenance) = aws_ip_ranges.provenance() else {
+        return;
+    };
+    if !provenance.is_stale() {
+        return;
+    }
+
+    eprintln!(
+        "Warning: Using stale AWS IP Ranges from {} (cache age {}, createDate {}); they may be out of date.",
+        provenance.location,
+        format_age(provenance.cache_age.unwrap_or_default()),
+        aws_ip_ranges.create_date()
+    );
+    if provenance.attempts > 0 {
+        eprintln!(
+            "Warning: {} attempt(s) to download the AWS IP Ranges failed.",
+            provenance.attempts
+        );
+    }
+}
+
+fn format_age(age: std::time::Duration) -> String {
+    let age = age.as_secs();
+    format!("{}h {}m {}s", age / 3600, age % 3600 / 60, age % 60)
+}
+
+/// Report that no AWS IP Prefixes match the provided criteria.
+fn not_found(quiet: bool) -> cli::ExitStatus {
+    if !quiet {
+        eprintln!("\nNo AWS IP Prefixes match the provided criteria.\n");
+    }
+    cli::ExitStatus::NotFound
+}
+
+fn display_output(
+    output: &cli::OutputFormat,
+    stats_format: &cli::StatsFormat,
+    display_aws_ip_ranges: &awsipranges::AwsIpRanges,
+    search: Option<(&[ipnetwork::IpNetwork], &awsipranges::SearchResults)>,
+    attributions: Option<
+        &std::collections::BTreeMap<ipnetwork::IpNetwork, awsipranges::Attribution>,
+    >,
+) -> awsipranges::Result<()> {
+    match output {
+        cli::OutputFormat::Table => cli::output::prefix_table(display_aws_ip_ranges),
+        cli::OutputFormat::Cidr => cli::output::prefixes_in_cidr_format(display_aws_ip_ranges),
+        cli::OutputFormat::Netmask => {
+            cli::output::prefixes_in_netmask_format(display_aws_ip_ranges)
+        }
+        cli::OutputFormat::Regions => cli::output::regions(display_aws_ip_ranges),
+        cli::OutputFormat::NetworkBorderGroups => {
+            cli::output::network_border_groups(display_aws_ip_ranges)
+        }
+        cli::OutputFormat::Services => cli::output::services(display_aws_ip_ranges),
+        cli::OutputFormat::Stats => {
+            let ranges_stats = display_aws_ip_ranges.stats();
+            match stats_format {
+                cli::StatsFormat::Table => cli::stats::table(&ranges_stats),
+                cli::StatsFormat::Json => cli::stats::json(&ranges_stats),
+                cli::StatsFormat::Csv => cli::stats::csv(&ranges_stats)?,
+            }
+        }
+        cli::OutputFormat::Json => {
+            cli::structured::json(display_aws_ip_ranges, search, attributions)
+        }
+        cli::OutputFormat::Ndjson => {
+            cli::structured::ndjson(display_aws_ip_ranges, search, attributions)
+        }
+        cli::OutputFormat::Yaml => {
+            cli::structured::yaml(display_aws_ip_ranges, search, attributions)
+        }
+        cli::OutputFormat::IpRangesJson => println!("{}", display_aws_ip_ranges.to_json()),
     };
 
     Ok(())
 }
+
+/*--------------------------------------------------------------------------------------
+  List
+--------------------------------------------------------------------------------------*/
+
+fn list(
+    command: &cli::ListCommand,
+    client: &awsipranges::Client,
+    quiet: bool,
+) -> Result<cli::ExitStatus, cli::Failure> {
+    let aws_ip_ranges = cli::filter_ranges(&command.filter, client.get_ranges()?)?;
+    warn_if_stale(&aws_ip_ranges, client, quiet);
+    if aws_ip_ranges.prefixes().is_empty() {
+        return Ok(not_found(quiet));
+    }
+
+    if !quiet {
+        match command.values {
+            cli::ListValues::Regions => cli::output::regions(&aws_ip_ranges),
+            cli::ListValues::NetworkBorderGroups => {
+                cli::output::network_border_groups(&aws_ip_ranges)
+            }
+            cli::ListValues::Services => cli::output::services(&aws_ip_ranges),
+        };
+    }
+
+    Ok(cli::ExitStatus::Success)
+}
+
+/*--------------------------------------------------------------------------------------
+  Stats
+--------------------------------------------------------------------------------------*/
+
+fn stats(
+    command: &cli::StatsCommand,
+    client: &awsipranges::Client,
+    quiet: bool,
+) -> Result<cli::ExitStatus, cli::Failure> {
+    let aws_ip_ranges = cli::filter_ranges(&command.filter, client.get_ranges()?)?;
+    warn_if_stale(&aws_ip_ranges, client, quiet);
+    if aws_ip_ranges.prefixes().is_empty() {
+        return Ok(not_found(quiet));
+    }
+
+    if !quiet {
+        let ranges_stats = aws_ip_ranges.stats();
+        match command.format {
+            cli::StatsFormat::Table => cli::stats::table(&ranges_stats),
+            cli::StatsFormat::Json => cli::stats::json(&ranges_stats),
+            cli::StatsFormat::Csv => cli::stats::csv(&ranges_stats)?,
+        };
+    }
+
+    Ok(cli::ExitStatus::Success)
+}
+
+/*--------------------------------------------------------------------------------------
+  Diff
+--------------------------------------------------------------------------------------*/
+
+fn diff(
+    files: &[std::path::PathBuf],
+    format: &cli::DiffFormat,
+    client: &awsipranges::Client,
+    quiet: bool,
+) -> Result<cli::ExitStatus, cli::Failure> {
+    let (old, new) = cli::get_ranges_to_diff(files, client)?;
+    warn_if_stale(&new, client, quiet);
+    let ranges_diff = old.diff(&new);
+    if !quiet {
+        match format {
+            cli::DiffFormat::Table => cli::diff::table(&ranges_diff),
+            cli::DiffFormat::Json => cli::diff::json(&ranges_diff),
+            cli::DiffFormat::Text => cli::diff::text(&ranges_diff),
+        };
+    }
+
+    Ok(cli::ExitStatus::Success)
+}
+
+/*--------------------------------------------------------------------------------------
+  Export
+--------------------------------------------------------------------------------------*/
+
+fn export(
+    command: &cli::ExportCommand,
+    client: &awsipranges::Client,
+    quiet: bool,
+) -> Result<cli::ExitStatus, cli::Failure> {
+    let aws_ip_ranges = cli::filter_ranges(&command.filter, client.get_ranges()?)?;
+    warn_if_stale(&aws_ip_ranges, client, quiet);
+
+    // Write nothing, not even an empty file, when no AWS IP Prefixes match
+    if aws_ip_ranges.prefixes().is_empty() {
+        return Ok(not_found(quiet));
+    }
+
+    let contents = match command.format {
+        cli::ExportFormat::IpRangesJson => aws_ip_ranges.to_json() + "\n",
+        cli::ExportFormat::Json => cli::structured::to_json(&aws_ip_ranges, None, None) + "\n",
+        cli::ExportFormat::Ndjson => cli::structured::to_ndjson(&aws_ip_ranges, None, None),
+        cli::ExportFormat::Yaml => cli::structured::to_yaml(&aws_ip_ranges, None, None),
+        cli::ExportFormat::Csv => cli::csv::to_csv(&aws_ip_ranges),
+    };
+
+    match &command.path {
+        Some(path) => std::fs::write(path, contents).map_err(|error| awsipranges::Error::Io {
+            path: path.clone(),
+            source: error,
+        })?,
+        None if !quiet => print!("{contents}"),
+        None => (),
+    };
+
+    Ok(cli::ExitStatus::Success)
+}
+
+/*--------------------------------------------------------------------------------------
+  Cache
+--------------------------------------------------------------------------------------*/
+
+fn cache(
+    command: &cli::CacheCommand,
+    client: &awsipranges::Client,
+    quiet: bool,
+) -> Result<cli::ExitStatus, cli::Failure> {
+    match command.action {
+        cli::CacheAction::Path => {
+            if !quiet {
+                println!("{}", client.cache_file().display());
+            }
+        }
+        cli::CacheAction::Info => {
+            let cache_info = client.cache_info()?;
+            if !quiet {
+                cache_info_table(&cache_info);
+            }
+        }
+        cli::CacheAction::Refresh => {
+            let aws_ip_ranges = client.refresh_cache()?;
+            if !quiet {
+                println!(
+                    "Refreshed {} (syncToken {}, createDate {})",
+                    client.cache_file().display(),
+                    aws_ip_ranges.sync_token(),
+                    aws_ip_ranges.create_date()
+                );
+            }
+        }
+        cli::CacheAction::Clear => {
+            let deleted = client.clear_cache()?;
+            if !quiet {
+                match deleted {
+                    true => println!("Deleted {}", client.cache_file().display()),
+                    false => println!("No cache file at {}", client.cache_file().display()),
+                }
+            }
+        }
+        cli::CacheAction::Verify => {
+            let aws_ip_ranges = client.verify_cache()?;
+            if !quiet {
+                println!(
+                    "Verified {} ({} prefixes, syncToken {}, createDate {})",
+                    client.cache_file().display(),
+                    aws_ip_ranges.prefixes().len(),
+                    aws_ip_ranges.sync_token(),
+                    aws_ip_ranges.create_date()
+                );
+            }
+        }
+    };
+
+    Ok(cli::ExitStatus::Success)
+}
+
+fn cache_info_table(cache_info: &awsipranges::CacheInfo) {
+    let unknown = || "unknown (run `awsipranges cache verify`)".to_string();
+    let freshness = match cache_info.is_fresh() {
+        true => "fresh",
+        false => "stale",
+    };
+
+    println!("Path:        {}", cache_info.path.display());
+    println!("Size:        {} bytes", cache_info.size);
+    println!("Age:         {}", format_age(cache_info.age));
+    println!(
+        "Freshness:   {freshness} (cache time {} seconds)",
+        cache_info.cache_time
+    );
+    println!(
+        "syncToken:   {}",
+        cache_info.sync_token.clone().unwrap_or_else(unknown)
+    );
+    println!(
+        "createDate:  {}",
+        cache_info
+            .create_date
+            .map(|create_date| create_date.to_string())
+            .unwrap_or_else(unknown)
+    );
+}
@@ -117,6 +117,118 @@ fn command_output_services() {
         .success();
 }
 
+/*-----------------------------------------------------------------------------
+  Output: Stats
+-----------------------------------------------------------------------------*/
+
+#[test]
+fn command_output_stats() {
+    for stats_format in ["table", "json", "csv"] {
+        let output = Command::cargo_bin("awsipranges")
+            .unwrap()
+            .arg("--ipv4")
+            .arg("--output")
+            .arg("stats")
+            .arg("--stats-format")
+            .arg(stats_format)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert!(String::from_utf8_lossy(&output.stdout).contains("IPv4"));
+    }
+}
+
+/*-----------------------------------------------------------------------------
+  Output: JSON, NDJSON, and YAML
+-----------------------------------------------------------------------------*/
+
+#[test]
+fn command_output_json() {
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("3.141.102.225")
+        .arg("1.1.1.1")
+        .arg("--output")
+        .arg("json")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3)); // 1.1.1.1 not found
+
+    let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(document["schema_version"], 1);
+    assert!(document["sync_token"].is_string());
+    assert_eq!(
+        document["search"]["results"][0]["search_cidr"],
+        "3.141.102.225/32"
+    );
+    assert_eq!(document["search"]["not_found"][0], "1.1.1.1/32");
+}
+
+#[test]
+fn command_output_ndjson() {
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("--ipv6")
+        .arg("--output")
+        .arg("ndjson")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let records: Vec<serde_json::Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(records[0]["record"], "metadata");
+    assert!(records[1..]
+        .iter()
+        .all(|record| record["record"] == "prefix"));
+}
+
+#[test]
+fn command_output_yaml() {
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("--ipv4")
+        .arg("--output")
+        .arg("yaml")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("schema_version: 1\n"));
+    assert!(stdout.contains("prefixes:"));
+}
+
+/*-----------------------------------------------------------------------------
+  Output: ip-ranges.json
+-----------------------------------------------------------------------------*/
+
+#[test]
+fn command_output_ip_ranges_json() {
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("--region")
+        .arg("us-east-2")
+        .arg("--output")
+        .arg("ip-ranges-json")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let json_file = "./scratch/command_output_ip_ranges_json.json";
+    std::fs::write(json_file, &output.stdout).unwrap();
+    let aws_ip_ranges = awsipranges::AwsIpRanges::from_file(json_file).unwrap();
+    assert_eq!(
+        aws_ip_ranges.regions().iter().collect::<Vec<_>>(),
+        vec![&std::sync::Arc::from("us-east-2")]
+    );
+    assert_eq!(
+        aws_ip_ranges.sync_token(),
+        awsipranges::get_ranges().unwrap().sync_token()
+    );
+}
+
 /*--------------------------------------------------------------------------------------
   Search
 --------------------------------------------------------------------------------------*/
@@ -148,6 +260,256 @@ fn command_search_ip_address_not_found() {
         .code(1);
 }
 
+/*-----------------------------------------------------------------------------
+  Search: Overlapping
+-----------------------------------------------------------------------------*/
+
+#[test]
+fn command_search_overlapping() {
+    // 3.0.0.0/8 is wider than any AWS IP Prefix; only found when searching for subnets
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("3.0.0.0/8")
+        .assert()
+        .failure()
+        .code(1);
+
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("--overlapping")
+        .arg("--output=cidr")
+        .arg("3.0.0.0/8")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .all(|line| line.starts_with("3.")));
+}
+
+/*-----------------------------------------------------------------------------
+  Search: Most Specific
+-----------------------------------------------------------------------------*/
+
+#[test]
+fn command_search_most_specific() {
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("--most-specific")
+        .arg("--output=cidr")
+        .arg("3.141.102.225")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout).lines().count(), 1);
+}
+
+#[test]
+fn command_search_most_specific_filtered() {
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .args([
+            "search",
+            "--most-specific",
+            "--service",
+            "EC2",
+            "--output=cidr",
+        ])
+        .arg("3.141.102.225")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim(),
+        "3.128.0.0/9"
+    );
+}
+
+#[test]
+fn command_search_most_specific_effective_services() {
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("--most-specific")
+        .arg("3.141.102.225")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Effective Services"));
+
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("--most-specific")
+        .arg("--output=json")
+        .arg("3.141.102.225")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        document["search"]["results"][0]["effective_services"],
+        serde_json::json!(["ROUTE53_HEALTHCHECKS"])
+    );
+
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("--most-specific")
+        .arg("--amazon-service=service")
+        .arg("--output=json")
+        .arg("3.141.102.225")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        document["search"]["results"][0]["effective_services"],
+        serde_json::json!(["AMAZON", "ROUTE53_HEALTHCHECKS"])
+    );
+}
+
+#[test]
+fn command_search_amazon_service_requires_most_specific() {
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("--amazon-service=service")
+        .arg("3.141.102.225")
+        .assert()
+        .code(2);
+}
+
+/*-----------------------------------------------------------------------------
+  Search: Results by Search CIDR
+-----------------------------------------------------------------------------*/
+
+#[test]
+fn command_search_view_by_input() {
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("3.141.102.225")
+        .arg("1.1.1.1")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Search CIDR"));
+    assert!(stdout.contains("1.1.1.1/32"));
+    assert!(stdout.contains("Not found"));
+
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("--search-view")
+        .arg("merged")
+        .arg("3.141.102.225")
+        .arg("1.1.1.1")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("Not found"));
+}
+
+#[test]
+fn command_search_view_by_input_csv() {
+    let csv_file = "./scratch/command_search_view_by_input_csv.csv";
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("--csv")
+        .arg(csv_file)
+        .arg("3.141.102.225")
+        .arg("1.1.1.1")
+        .assert()
+        .code(3);
+
+    let csv = std::fs::read_to_string(csv_file).unwrap();
+    assert!(csv.starts_with("Search CIDR,"));
+    assert!(csv.contains("1.1.1.1/32,,,,"));
+}
+
+#[test]
+fn command_search_view_by_input_duplicates() {
+    let csv_file = "./scratch/command_search_view_by_input_duplicates.csv";
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("--most-specific")
+        .arg("--csv")
+        .arg(csv_file)
+        .arg("3.141.102.225")
+        .arg("1.1.1.1")
+        .arg("3.141.102.225/32")
+        .arg("1.1.1.1")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.matches("3.141.102.225/32").count(), 1);
+    assert_eq!(stdout.matches("1.1.1.1/32").count(), 1);
+
+    let csv = std::fs::read_to_string(csv_file).unwrap();
+    assert_eq!(csv.lines().count(), 3);
+}
+
+/*-----------------------------------------------------------------------------
+  Search: Input Files and Stdin
+-----------------------------------------------------------------------------*/
+
+#[test]
+fn command_search_input_file() {
+    let input_file = "./scratch/command_search_input_file.txt";
+    std::fs::write(
+        input_file,
+        "# Incident addresses\n44.192.140.65\n\n1.1.1.1  # Not AWS\n",
+    )
+    .unwrap();
+
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("--input-file")
+        .arg(input_file)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("1.1.1.1/32"));
+}
+
+#[test]
+fn command_search_stdin() {
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("-")
+        .write_stdin("44.192.140.65\n")
+        .assert()
+        .success();
+}
+
+#[test]
+fn command_search_flexible_input() {
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("::ffff:3.141.102.225")
+        .arg("3.141.102.224-3.141.102.230")
+        .arg("-o")
+        .arg("cidr")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("3.141.102.224/27"));
+}
+
+#[test]
+fn command_search_input_file_invalid_line() {
+    let input_file = "./scratch/command_search_input_file_invalid_line.txt";
+    std::fs::write(input_file, "44.192.140.65\nnot-an-address\n").unwrap();
+
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("--input-file")
+        .arg(input_file)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("command_search_input_file_invalid_line.txt"));
+    assert!(stderr.contains("on line 2 of"));
+}
+
 /*--------------------------------------------------------------------------------------
   Filter
 --------------------------------------------------------------------------------------*/
@@ -239,6 +601,111 @@ fn command_filter_ipv4_region_network_border_group_service() {
         .success();
 }
 
+/*-----------------------------------------------------------------------------
+  Filter - Patterns
+-----------------------------------------------------------------------------*/
+
+#[test]
+fn command_filter_region_service_patterns() {
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("--region")
+        .arg("US-*")
+        .arg("--service")
+        .arg("route53*")
+        .assert()
+        .success();
+}
+
+#[test]
+fn command_filter_region_pattern_matches_nothing() {
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("--region")
+        .arg("xx-*")
+        .assert()
+        .failure();
+}
+
+/*-----------------------------------------------------------------------------
+  Filter - Exclusions
+-----------------------------------------------------------------------------*/
+
+#[test]
+fn command_filter_exclude_region_service() {
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("--exclude-region")
+        .arg("GLOBAL")
+        .arg("--exclude-service")
+        .arg("CLOUDFRONT")
+        .assert()
+        .success();
+}
+
+#[test]
+fn command_filter_region_exclude_amazon_only() {
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("--region")
+        .arg("us-east-1")
+        .arg("--exclude-network-border-group")
+        .arg("us-east-1-atl-1")
+        .arg("--exclude-amazon-only")
+        .assert()
+        .success();
+}
+
+/*-----------------------------------------------------------------------------
+  Filter - Prefix Lengths and Address Ranges
+-----------------------------------------------------------------------------*/
+
+#[test]
+fn command_filter_prefix_lengths() {
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("--max-ipv4-prefix-len")
+        .arg("24")
+        .arg("--min-ipv6-prefix-len")
+        .arg("40")
+        .assert()
+        .success();
+}
+
+#[test]
+fn command_filter_within_not_within() {
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("--within")
+        .arg("3.0.0.0/8")
+        .arg("--not-within")
+        .arg("3.5.0.0/16")
+        .assert()
+        .success();
+}
+
+#[test]
+fn command_filter_invalid_prefix_length() {
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("--max-ipv4-prefix-len")
+        .arg("33")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn command_filter_min_prefix_length_greater_than_max() {
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .args(["filter", "--ipv4", "--min-ipv4-prefix-len", "24"])
+        .args(["--max-ipv4-prefix-len", "16"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("invalid IPv4 prefix length range"));
+}
+
 /*--------------------------------------------------------------------------------------
   Save to CSV
 --------------------------------------------------------------------------------------*/
@@ -252,3 +719,642 @@ fn command_save_to_csv() {
         .assert()
         .success();
 }
+
+/*--------------------------------------------------------------------------------------
+  Aggregate
+--------------------------------------------------------------------------------------*/
+
+#[test]
+fn command_aggregate_cidr() {
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("--output")
+        .arg("cidr")
+        .arg("--aggregate")
+        .assert()
+        .success();
+}
+
+#[test]
+fn command_aggregate_by_region_netmask() {
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("--output")
+        .arg("netmask")
+        .arg("--aggregate=region")
+        .assert()
+        .success();
+}
+
+#[test]
+fn command_aggregate_by_service_save_to_csv() {
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("--aggregate=service")
+        .arg("--csv")
+        .arg("./scratch/command_aggregate_by_service_save_to_csv.csv")
+        .assert()
+        .success();
+}
+
+#[test]
+fn command_aggregate_not_applicable_warning() {
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .args(["filter", "--aggregate", "--output=regions"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("Warning: --aggregate does not apply to the regions output format."));
+
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .args(["-q", "filter", "--aggregate", "--output=regions"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(output.stderr.is_empty());
+}
+
+/*--------------------------------------------------------------------------------------
+  Diff
+--------------------------------------------------------------------------------------*/
+
+#[test]
+fn command_diff_files() {
+    // Ensure the cache file exists
+    awsipranges::get_ranges().unwrap();
+    let cache_file = awsipranges::Client::new().cache_file().to_path_buf();
+
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("--diff")
+        .arg(&cache_file)
+        .arg(&cache_file)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("0  Added"));
+}
+
+#[test]
+fn command_diff_file_against_current_json() {
+    // Ensure the cache file exists
+    awsipranges::get_ranges().unwrap();
+    let cache_file = awsipranges::Client::new().cache_file().to_path_buf();
+
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("--diff")
+        .arg(&cache_file)
+        .arg("--diff-format")
+        .arg("json")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("\"added\": []"));
+}
+
+#[test]
+fn command_diff_invalid_file() {
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("--diff")
+        .arg("./scratch/command_diff_invalid_file_does_not_exist.json")
+        .arg("--diff-format")
+        .arg("text")
+        .assert()
+        .failure()
+        .code(4);
+}
+
+/*--------------------------------------------------------------------------------------
+  Commands
+--------------------------------------------------------------------------------------*/
+
+#[test]
+fn command_search() {
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("search")
+        .arg("3.141.102.225")
+        .arg("--service")
+        .arg("ROUTE53_HEALTHCHECKS")
+        .arg("--output")
+        .arg("cidr")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "3.141.102.224/27\n"
+    );
+
+    // Search CIDRs (or an input file) are required
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("search")
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn command_filter() {
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("filter")
+        .arg("--ipv6")
+        .arg("--output")
+        .arg("cidr")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .all(|line| line.contains(':')));
+}
+
+#[test]
+fn command_list() {
+    for (values, expected) in [
+        ("regions", "us-east-2"),
+        ("services", "ROUTE53_HEALTHCHECKS"),
+        ("border-groups", "us-east-2"),
+    ] {
+        let output = Command::cargo_bin("awsipranges")
+            .unwrap()
+            .arg("list")
+            .arg(values)
+            .arg("--region")
+            .arg("us-east-2")
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert!(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .any(|line| line == expected));
+    }
+}
+
+#[test]
+fn command_stats() {
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("stats")
+        .arg("--ipv6")
+        .arg("--format")
+        .arg("csv")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("Group,"));
+    assert!(!stdout.contains("IPv4"));
+}
+
+#[test]
+fn command_diff() {
+    // Ensure the cache file exists
+    awsipranges::get_ranges().unwrap();
+    let cache_file = awsipranges::Client::new().cache_file().to_path_buf();
+
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("diff")
+        .arg(&cache_file)
+        .arg(&cache_file)
+        .arg("--format")
+        .arg("text")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("0 added, 0 removed, 0 changed"));
+}
+
+#[test]
+fn command_diff_max_data_age() {
+    // Ensure the cache file exists
+    awsipranges::get_ranges().unwrap();
+    let cache_file = awsipranges::Client::new().cache_file().to_path_buf();
+
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("diff")
+        .arg(&cache_file)
+        .arg(&cache_file)
+        .args(["--max-data-age", "1", "--format", "text"])
+        .assert()
+        .code(4);
+
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("diff")
+        .arg(&cache_file)
+        .arg(&cache_file)
+        .args(["--max-data-age", "1", "--data-age-policy", "warn"])
+        .args(["--format", "text"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Warning: AWS IP Ranges"));
+
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("diff")
+        .arg(&cache_file)
+        .arg(&cache_file)
+        .args(["--expected-sync-token", "1", "--format", "text"])
+        .assert()
+        .code(4);
+}
+
+#[test]
+fn command_export() {
+    let export_file = "./scratch/command_export.json";
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("export")
+        .arg(export_file)
+        .arg("--region")
+        .arg("us-east-2")
+        .assert()
+        .success();
+    let aws_ip_ranges = awsipranges::AwsIpRanges::from_file(export_file).unwrap();
+    assert_eq!(aws_ip_ranges.regions().len(), 1);
+
+    for (format, expected) in [
+        ("json", "\"schema_version\""),
+        ("ndjson", "\"record\":\"metadata\""),
+        ("yaml", "schema_version: 1"),
+        ("csv", "AWS IP Prefix,Region"),
+    ] {
+        let output = Command::cargo_bin("awsipranges")
+            .unwrap()
+            .arg("export")
+            .arg("--format")
+            .arg(format)
+            .arg("--ipv4")
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert!(String::from_utf8_lossy(&output.stdout).contains(expected));
+    }
+}
+
+#[test]
+fn command_export_not_found() {
+    let export_file = "./scratch/command_export_not_found.json";
+    std::fs::create_dir_all("./scratch").unwrap();
+    let _ = std::fs::remove_file(export_file);
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("export")
+        .arg(export_file)
+        .arg("--within")
+        .arg("192.168.0.0/16")
+        .assert()
+        .code(1);
+    assert!(!std::path::Path::new(export_file).exists());
+}
+
+#[test]
+fn command_cache_path() {
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("cache")
+        .arg("path")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim(),
+        awsipranges::Client::new()
+            .cache_file()
+            .display()
+            .to_string()
+    );
+}
+
+#[test]
+fn command_cache_info_verify_clear() {
+    let cache_file = "./scratch/command_cache_info_verify_clear.json";
+    std::fs::create_dir_all("./scratch").unwrap();
+    awsipranges::get_ranges()
+        .unwrap()
+        .write_json(cache_file)
+        .unwrap();
+
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .env("AWSIPRANGES_CACHE_FILE", cache_file)
+        .args(["cache", "info"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("syncToken"));
+    assert!(stdout.contains("fresh"));
+
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .env("AWSIPRANGES_CACHE_FILE", cache_file)
+        .args(["cache", "verify"])
+        .assert()
+        .success();
+
+    // A corrupt cache file fails verification
+    std::fs::write(cache_file, "{\"syncToken\": ").unwrap();
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .env("AWSIPRANGES_CACHE_FILE", cache_file)
+        .args(["cache", "verify"])
+        .assert()
+        .code(4);
+
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .env("AWSIPRANGES_CACHE_FILE", cache_file)
+        .args(["cache", "clear"])
+        .assert()
+        .success();
+    assert!(!std::path::Path::new(cache_file).exists());
+
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .env("AWSIPRANGES_CACHE_FILE", cache_file)
+        .args(["cache", "info"])
+        .assert()
+        .code(4);
+}
+
+#[test]
+fn command_offline() {
+    let cache_file = "./scratch/command_offline.json";
+    std::fs::create_dir_all("./scratch").unwrap();
+    awsipranges::get_ranges()
+        .unwrap()
+        .write_json(cache_file)
+        .unwrap();
+
+    // A stale cache is used without requesting the (unreachable) URL
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .env("AWSIPRANGES_CACHE_FILE", cache_file)
+        .env("AWSIPRANGES_CACHE_TIME", "0")
+        .env("AWSIPRANGES_URL", "http://127.0.0.1:9/ip-ranges.json")
+        .args(["search", "--offline", "3.141.102.225"])
+        .assert()
+        .success();
+
+    std::fs::remove_file(cache_file).unwrap();
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .env("AWSIPRANGES_CACHE_FILE", cache_file)
+        .args(["search", "--fetch-policy", "cache-only", "3.141.102.225"])
+        .assert()
+        .code(4);
+
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .env("AWSIPRANGES_CACHE_FILE", cache_file)
+        .env("AWSIPRANGES_FETCH_POLICY", "offline")
+        .arg("stats")
+        .assert()
+        .code(4);
+
+    // An invalid fetch policy is an error, not a silent fallback to prefer-cache
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .env("AWSIPRANGES_FETCH_POLICY", "ofline")
+        .arg("stats")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("AWSIPRANGES_FETCH_POLICY"));
+}
+
+#[test]
+fn command_stale_data_warning() {
+    let cache_file = "./scratch/command_stale_data_warning.json";
+    std::fs::create_dir_all("./scratch").unwrap();
+    awsipranges::get_ranges()
+        .unwrap()
+        .write_json(cache_file)
+        .unwrap();
+    std::fs::File::options()
+        .write(true)
+        .open(cache_file)
+        .unwrap()
+        .set_modified(std::time::SystemTime::now() - std::time::Duration::from_secs(3 * 86400))
+        .unwrap();
+
+    // The URL is unreachable, so the stale cache is used
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .env("AWSIPRANGES_CACHE_FILE", cache_file)
+        .env("AWSIPRANGES_URL", "http://127.0.0.1:9/ip-ranges.json")
+        .env("AWSIPRANGES_RETRY_COUNT", "1")
+        .args(["search", "--output", "json", "3.141.102.225"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Warning: Using stale AWS IP Ranges"));
+
+    let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(document["stale"], true);
+    assert_eq!(document["provenance"]["source"], "stale_cache");
+    assert_eq!(document["provenance"]["attempts"], 1);
+}
+
+#[test]
+fn command_max_data_age_and_expected_sync_token() {
+    let aws_ip_ranges = awsipranges::get_ranges().unwrap();
+
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .args(["search", "--max-data-age", "0", "3.141.102.225"])
+        .assert()
+        .code(4);
+
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .args(["search", "--max-data-age", "0", "--data-age-policy", "warn"])
+        .arg("3.141.102.225")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Warning: AWS IP Ranges"));
+
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .args([
+            "search",
+            "--expected-sync-token",
+            aws_ip_ranges.sync_token(),
+        ])
+        .arg("3.141.102.225")
+        .assert()
+        .success();
+
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .env("AWSIPRANGES_EXPECTED_SYNC_TOKEN", "1")
+        .args(["search", "3.141.102.225"])
+        .assert()
+        .code(4);
+
+    // Invalid data age environment variables are errors, not ignored
+    for (name, value) in [
+        ("AWSIPRANGES_MAX_DATA_AGE", "7d"),
+        ("AWSIPRANGES_DATA_AGE_POLICY", "ignore"),
+    ] {
+        let output = Command::cargo_bin("awsipranges")
+            .unwrap()
+            .env(name, value)
+            .args(["search", "--offline", "3.141.102.225"])
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(2));
+        assert!(String::from_utf8_lossy(&output.stderr).contains(name));
+    }
+}
+
+#[test]
+fn command_completions() {
+    for shell in ["bash", "zsh", "fish", "elvish"] {
+        let output = Command::cargo_bin("awsipranges")
+            .unwrap()
+            .arg("completions")
+            .arg(shell)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert!(String::from_utf8_lossy(&output.stdout).contains("COMPLETE="));
+    }
+}
+
+#[test]
+fn command_completions_dynamic_values() {
+    // Ensure the cache file exists; candidates come from the cache
+    awsipranges::get_ranges().unwrap();
+
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .env("COMPLETE", "fish")
+        .args(["--", "awsipranges", "filter", "--region", "us-east-"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .any(|line| line == "us-east-2"));
+}
+
+#[test]
+fn command_man() {
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("man")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains(".TH awsipranges 1"));
+
+    let man_directory = "./scratch/command_man";
+    std::fs::create_dir_all(man_directory).unwrap();
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("man")
+        .arg(man_directory)
+        .assert()
+        .success();
+    assert!(std::path::Path::new(man_directory)
+        .join("awsipranges-search.1")
+        .exists());
+}
+
+/*--------------------------------------------------------------------------------------
+  Exit Status and Quiet Mode
+--------------------------------------------------------------------------------------*/
+
+#[test]
+fn command_exit_status() {
+    let exit_code = |args: &[&str]| {
+        Command::cargo_bin("awsipranges")
+            .unwrap()
+            .args(args)
+            .output()
+            .unwrap()
+            .status
+            .code()
+    };
+
+    assert_eq!(exit_code(&["3.141.102.225"]), Some(0)); // All found
+    assert_eq!(exit_code(&["1.1.1.1"]), Some(1)); // None found
+    assert_eq!(exit_code(&["--region", "us-east-2"]), Some(0));
+    assert_eq!(exit_code(&["not-an-address"]), Some(2)); // Invalid input
+    assert_eq!(exit_code(&["--region", "not-a-region"]), Some(2));
+    assert_eq!(
+        exit_code(&["--input-file", "./scratch/does_not_exist.txt"]),
+        Some(2)
+    );
+    assert_eq!(exit_code(&["--not-an-option"]), Some(2)); // Usage error
+    assert_eq!(exit_code(&["3.141.102.225", "1.1.1.1"]), Some(3)); // Some not found
+}
+
+#[test]
+fn command_quiet() {
+    for (address, exit_code) in [("3.141.102.225", 0), ("1.1.1.1", 1), ("not-an-address", 2)] {
+        let output = Command::cargo_bin("awsipranges")
+            .unwrap()
+            .arg("--quiet")
+            .arg(address)
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(exit_code));
+        assert!(output.stdout.is_empty());
+        assert!(output.stderr.is_empty());
+    }
+}
+
+#[test]
+fn command_quiet_verbose() {
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .arg("--quiet")
+        .arg("--verbose")
+        .arg("1.1.1.1")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not found"));
+}
+
+#[test]
+fn command_global_options_before_command() {
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .args(["-q", "search", "3.141.102.225"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(output.stderr.is_empty());
+
+    let output = Command::cargo_bin("awsipranges")
+        .unwrap()
+        .args(["--offline", "list", "regions"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("us-east-2"));
+
+    // Legacy options cannot be combined with a command
+    Command::cargo_bin("awsipranges")
+        .unwrap()
+        .args(["--ipv4", "search", "3.141.102.225"])
+        .assert()
+        .code(2);
+}